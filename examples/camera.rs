@@ -102,6 +102,10 @@ impl Game for CameraExample {
             self.camera.scale -= vec2!(2.0 * dt, 2.0 * dt);
         }
 
+        // zoom with the scroll wheel
+        let scroll = engine_handle.get_mouse_wheel_delta().y;
+        self.camera.scale += vec2!(0.1 * scroll, 0.1 * scroll);
+
         if engine_handle.is_key_pressed(Key::Enter) {
             self.camera.rotation += 45.0;
         }
@@ -0,0 +1,63 @@
+use std::num::NonZeroU16;
+
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderHandle;
+use bottomless_pit::vectors::Vec2;
+use bottomless_pit::{vec2, Game};
+
+// the physics only runs 10 times a second, the top square jumps between
+// positions while the bottom one is smoothed out using the interpolation alpha
+fn main() {
+    let mut engine = EngineBuilder::new()
+        .set_window_title("Fixed Update")
+        .set_fixed_update_rate(NonZeroU16::new(10).unwrap())
+        .build()
+        .unwrap();
+
+    let material = MaterialBuilder::new().build(&mut engine);
+
+    let game = FixedUpdateExample {
+        material,
+        previous_x: 0.0,
+        x: 0.0,
+        velocity: 200.0,
+    };
+
+    engine.run(game);
+}
+
+struct FixedUpdateExample {
+    material: Material,
+    previous_x: f32,
+    x: f32,
+    velocity: f32,
+}
+
+impl Game for FixedUpdateExample {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        let alpha = render_handle.get_interpolation_alpha();
+        let smooth_x = self.previous_x + (self.x - self.previous_x) * alpha;
+
+        let mut render = render_handle.begin_pass(Colour::BLACK);
+        self.material
+            .add_rectangle(vec2!(self.x, 200.0), vec2!(50.0), Colour::RED, &render);
+        self.material
+            .add_rectangle(vec2!(smooth_x, 350.0), vec2!(50.0), Colour::GREEN, &render);
+        self.material.draw(&mut render);
+    }
+
+    fn fixed_update(&mut self, engine_handle: &mut Engine) {
+        let dt = engine_handle.get_fixed_timestep().unwrap();
+        self.previous_x = self.x;
+        self.x += self.velocity * dt;
+
+        if self.x < 0.0 || self.x > 550.0 {
+            self.velocity = -self.velocity;
+            self.x = self.x.clamp(0.0, 550.0);
+        }
+    }
+
+    fn update(&mut self, _engine_handle: &mut Engine) {}
+}
@@ -0,0 +1,72 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+use bottomless_pit::gamepad::{GamepadAxis, GamepadButton, GamepadEvent};
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderHandle;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+use bottomless_pit::Game;
+
+// real gamepads are only read with the gilrs feature enabled:
+// cargo run --example gamepad --features gilrs
+fn main() {
+    let mut engine = EngineBuilder::new()
+        .set_window_title("Gamepad")
+        .with_resolution((400, 400))
+        .build()
+        .unwrap();
+
+    let material = MaterialBuilder::new().build(&mut engine);
+
+    let game = GamepadExample {
+        material,
+        position: vec2!(175.0),
+        colour: Colour::WHITE,
+    };
+
+    engine.run(game);
+}
+
+struct GamepadExample {
+    material: Material,
+    position: Vec2<f32>,
+    colour: Colour,
+}
+
+impl Game for GamepadExample {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        let mut render = render_handle.begin_pass(Colour::BLACK);
+
+        self.material
+            .add_rectangle(self.position, vec2!(50.0), self.colour, &render);
+        self.material.draw(&mut render);
+    }
+
+    fn update(&mut self, engine_handle: &mut Engine) {
+        for event in engine_handle.get_gamepad_events() {
+            match event {
+                GamepadEvent::Connected(id) => println!("gamepad {:?} connected", id),
+                GamepadEvent::Disconnected(id) => println!("gamepad {:?} disconnected", id),
+                _ => {}
+            }
+        }
+
+        let dt = engine_handle.get_frame_delta_time();
+        for pad in engine_handle.get_connected_gamepads() {
+            let x = engine_handle.get_gamepad_axis(pad, GamepadAxis::LeftStickX);
+            // sticks are positive going up but the screen is positive going down
+            let y = -engine_handle.get_gamepad_axis(pad, GamepadAxis::LeftStickY);
+            let speed =
+                200.0 + 300.0 * engine_handle.get_gamepad_axis(pad, GamepadAxis::RightTrigger);
+            self.position += vec2!(x, y) * speed * dt;
+
+            if engine_handle.is_gamepad_button_pressed(pad, GamepadButton::South) {
+                self.colour = Colour::RED;
+            }
+
+            if engine_handle.is_gamepad_button_released(pad, GamepadButton::South) {
+                self.colour = Colour::WHITE;
+            }
+        }
+    }
+}
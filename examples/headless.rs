@@ -0,0 +1,64 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderHandle;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+use bottomless_pit::Game;
+use image::RgbaImage;
+
+const FRAMES: u32 = 120;
+
+fn main() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((320, 240))
+        .headless()
+        .build()
+        .expect("Failed to create a headless engine");
+
+    let material = MaterialBuilder::new().build(&mut engine);
+
+    let mut game = HeadlessExample {
+        material,
+        position: vec2!(0.0),
+        frames: 0,
+        last_frame: None,
+    };
+
+    engine.run_frames(&mut game, FRAMES);
+
+    println!("ran {} frames without a window", game.frames);
+
+    if let Some(image) = game.last_frame {
+        let x = game.position.x as u32 + 25;
+        println!("pixel inside the rectangle: {:?}", image.get_pixel(x, 25));
+    }
+}
+
+struct HeadlessExample {
+    material: Material,
+    position: Vec2<f32>,
+    frames: u32,
+    last_frame: Option<RgbaImage>,
+}
+
+impl Game for HeadlessExample {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        {
+            let mut render = render_handle.begin_pass(Colour::BLACK);
+
+            self.material
+                .add_rectangle(self.position, vec2!(50.0), Colour::RED, &render);
+            self.material.draw(&mut render);
+        }
+
+        if self.frames == FRAMES {
+            self.last_frame = render_handle.capture().ok();
+        }
+    }
+
+    fn update(&mut self, engine_handle: &mut Engine) {
+        self.position.x += 100.0 * engine_handle.get_frame_delta_time();
+        self.frames += 1;
+    }
+}
@@ -61,5 +61,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     var distance = sqrt(x * x + y * y);
     // TODO: Add blur
     var brightness: f32 = max(0.0, 0.7-distance);
-    return textureSample(light_map, light_map_sampler, in.tex_coords) * (brightness * light.colour);
+    let lit = textureSample(light_map, light_map_sampler, in.tex_coords) * (brightness * light.colour);
+    // multiply blending treats alpha as coverage, the whole screen is covered by the light map
+    return vec4<f32>(lit.rgb, 1.0);
 }
\ No newline at end of file
@@ -3,13 +3,13 @@ use std::f32::consts::PI;
 
 use bottomless_pit::engine_handle::Engine;
 use bottomless_pit::material::{Material, MaterialBuilder};
-use bottomless_pit::render::RenderHandle;
+use bottomless_pit::render::{BlendMode, RenderHandle};
 use bottomless_pit::shader::{Shader, ShaderOptions, UniformData, UniformError};
 use bottomless_pit::texture::UniformTexture;
 use bottomless_pit::{engine_handle::EngineBuilder, *};
 use colour::Colour;
-use encase::ShaderType;
 use resource::LoadingOp;
+use uniforms::Light;
 use vectors::Vec2;
 
 fn main() {
@@ -31,7 +31,9 @@ fn main() {
 
     let light_data = UniformData::new(&light);
 
-    let shader_options = ShaderOptions::with_all(&light_data, &uniform_texture);
+    // the light is multiplied onto the scene so anything in shadow goes dark
+    let shader_options =
+        ShaderOptions::with_all(&light_data, &uniform_texture).with_blend_mode(BlendMode::Multiply);
     let light_shader = Shader::new(
         "examples/light.wgsl",
         shader_options,
@@ -80,13 +82,19 @@ impl Game for TextureExample {
     fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
         self.create_shadow_map(&mut render_handle);
 
-        let mut p2 = render_handle.begin_pass(Colour::BLACK);
+        let mut p2 = render_handle.begin_pass(Colour::WHITE);
         let size = p2.get_size();
         let size = Vec2 {
             x: size.x as f32,
             y: size.y as f32,
         };
 
+        self.rectangles.iter().for_each(|rect| {
+            self.ocluder_material
+                .add_rectangle(rect.pos, rect.size, Colour::BROWN, &p2)
+        });
+        self.ocluder_material.draw(&mut p2);
+
         self.material
             .add_rectangle(Vec2 { x: 0.0, y: 0.0 }, size, Colour::WHITE, &p2);
         self.material.draw(&mut p2);
@@ -100,7 +108,7 @@ impl Game for TextureExample {
         self.light.pos_x = mouse_pos.x / window_size.x as f32;
         self.light.pos_y = mouse_pos.y / window_size.y as f32;
         self.material
-            .update_uniform_data(&self.light, &engine_handle)
+            .update_uniform_data(&self.light, engine_handle)
             .unwrap_or_default();
 
         self.material
@@ -112,7 +120,7 @@ impl Game for TextureExample {
         self.light.aspect_ratio = new_size.x as f32 / new_size.y as f32;
         match self
             .material
-            .update_uniform_data(&self.light, &engine_handle)
+            .update_uniform_data(&self.light, engine_handle)
         {
             Ok(_) => {}
             Err(e) => match e {
@@ -164,11 +172,11 @@ impl TextureExample {
                 };
 
                 for point in arr.iter_mut() {
-                    *point = *point - center_point;
+                    *point -= center_point;
                 }
-                arr.sort_by(|left, right| compare_points(left, right));
+                arr.sort_by(compare_points);
                 for point in arr.iter_mut() {
-                    *point = *point + center_point;
+                    *point += center_point;
                 }
 
                 self.ocluder_material
@@ -179,7 +187,7 @@ impl TextureExample {
         // makes sure there is not light in the squares
         self.rectangles.iter().for_each(|rect| {
             self.ocluder_material
-                .add_rectangle(rect.pos, rect.size, Colour::BLACK, &mut p1)
+                .add_rectangle(rect.pos, rect.size, Colour::BLACK, &p1)
         });
 
         self.ocluder_material.draw(&mut p1);
@@ -214,13 +222,21 @@ impl Rectangle {
     }
 }
 
-#[derive(ShaderType)]
-struct Light {
-    colour: Colour,
-    pos_x: f32,
-    pos_y: f32,
-    brightness: f32,
-    aspect_ratio: f32,
+// ShaderType derives add checks that are never called, the module keeps
+// the allow from hiding anything else in the example
+#[allow(dead_code)]
+mod uniforms {
+    use bottomless_pit::colour::Colour;
+    use encase::ShaderType;
+
+    #[derive(ShaderType)]
+    pub struct Light {
+        pub colour: Colour,
+        pub pos_x: f32,
+        pub pos_y: f32,
+        pub brightness: f32,
+        pub aspect_ratio: f32,
+    }
 }
 
 // Convex Hull Algo
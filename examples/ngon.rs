@@ -11,7 +11,7 @@ use bottomless_pit::vec2;
 use bottomless_pit::vectors::Vec2;
 use bottomless_pit::Game;
 
-use encase::ShaderType;
+use uniforms::Time;
 
 fn main() {
     let mut engine = EngineBuilder::new()
@@ -48,12 +48,17 @@ fn main() {
     engine.run(pos);
 }
 
-#[derive(ShaderType)]
-struct Time {
-    time: f32,
-    _pading: f32,
-    _padding2: f32,
-    _padding4: f32,
+#[allow(dead_code)] // for the unused checks made by the ShaderType derive
+mod uniforms {
+    use encase::ShaderType;
+
+    #[derive(ShaderType)]
+    pub struct Time {
+        pub time: f32,
+        pub _pading: f32,
+        pub _padding2: f32,
+        pub _padding4: f32,
+    }
 }
 
 struct Position {
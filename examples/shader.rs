@@ -10,7 +10,7 @@ use bottomless_pit::vec2;
 use bottomless_pit::vectors::Vec2;
 use bottomless_pit::Game;
 
-use encase::ShaderType;
+use uniforms::MousePos;
 
 fn main() {
     let mut engine = EngineBuilder::new().build().unwrap();
@@ -61,12 +61,17 @@ fn main() {
     engine.run(game);
 }
 
-#[derive(ShaderType, Clone, Copy)]
-struct MousePos {
-    x: f32,
-    y: f32,
-    _junk: f32,
-    _padding2: f32,
+#[allow(dead_code)] // for the unused checks made by the ShaderType derive
+mod uniforms {
+    use encase::ShaderType;
+
+    #[derive(ShaderType, Clone, Copy)]
+    pub struct MousePos {
+        pub x: f32,
+        pub y: f32,
+        pub _junk: f32,
+        pub _padding2: f32,
+    }
 }
 
 struct ShaderExample {
@@ -122,10 +127,10 @@ impl Game for ShaderExample {
 
         self.data = new_data;
         self.mouse_material
-            .update_uniform_data(&self.data, &engine_handle)
+            .update_uniform_data(&self.data, engine_handle)
             .unwrap_or_default();
         self.circle_material
-            .update_uniform_data(&self.theta, &engine_handle)
+            .update_uniform_data(&self.theta, engine_handle)
             .unwrap_or_default();
     }
 }
@@ -0,0 +1,74 @@
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+use bottomless_pit::input::{Key, MouseKey};
+use bottomless_pit::material::{Material, MaterialBuilder};
+use bottomless_pit::render::RenderHandle;
+use bottomless_pit::resource::{LoadingOp, ResourceId};
+use bottomless_pit::sound::{Channel, Sound};
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+use bottomless_pit::Game;
+
+// sound is only played on speakers with the cpal feature enabled:
+// cargo run --example sound --features cpal
+fn main() {
+    let mut engine = EngineBuilder::new()
+        .set_window_title("Sound")
+        .with_resolution((400, 400))
+        .build()
+        .unwrap();
+
+    let beep = Sound::new(&mut engine, "examples/beep.wav", LoadingOp::Blocking);
+    let material = MaterialBuilder::new().build(&mut engine);
+
+    let game = SoundExample {
+        beep,
+        material,
+        looping: None,
+    };
+
+    engine.run(game);
+}
+
+struct SoundExample {
+    beep: ResourceId<Sound>,
+    material: Material,
+    looping: Option<Channel>,
+}
+
+impl Game for SoundExample {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        let mut render = render_handle.begin_pass(Colour::BLACK);
+        let colour = if self.looping.is_some() {
+            Colour::GREEN
+        } else {
+            Colour::RED
+        };
+
+        self.material
+            .add_rectangle(vec2!(175.0), vec2!(50.0), colour, &render);
+        self.material.draw(&mut render);
+    }
+
+    fn update(&mut self, engine_handle: &mut Engine) {
+        // pan the sound towards wherever the mouse is
+        let pan = engine_handle.get_mouse_position().x / engine_handle.get_window_size().x as f32;
+        let pan = pan * 2.0 - 1.0;
+
+        if engine_handle.is_mouse_key_pressed(MouseKey::Left) {
+            let channel = engine_handle.play_sound(&self.beep).unwrap();
+            engine_handle.set_channel_pan(channel, pan);
+        }
+
+        if engine_handle.is_key_pressed(Key::Space) {
+            match self.looping.take() {
+                Some(channel) => engine_handle.stop_channel(channel),
+                None => self.looping = engine_handle.loop_sound(&self.beep).ok(),
+            }
+        }
+
+        if let Some(channel) = self.looping {
+            engine_handle.set_channel_pan(channel, pan);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Contains the [InputMap] which lets games check named actions instead of
+//! specific keys. This makes it easy to support several input devices at
+//! once and to let players rebind their controls.
+//! ```rust,no_run
+//! # use bottomless_pit::action::{Binding, InputMap};
+//! # use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+//! # use bottomless_pit::gamepad::{GamepadAxis, GamepadButton};
+//! # use bottomless_pit::input::{Key, ModifierKeys};
+//! let mut input_map = InputMap::new();
+//! input_map.bind("jump", Binding::key(Key::Space));
+//! input_map.bind("jump", Binding::gamepad_button(GamepadButton::South));
+//! input_map.bind("save", Binding::chord(&[ModifierKeys::Ctrl], Key::S));
+//! input_map.bind("move_x", Binding::key(Key::D));
+//! input_map.bind("move_x", Binding::key(Key::A).scaled(-1.0));
+//! input_map.bind("move_x", Binding::gamepad_axis(GamepadAxis::LeftStickX));
+//!
+//! let mut engine = EngineBuilder::new()
+//!     .with_input_map(input_map)
+//!     .build()
+//!     .unwrap();
+//! # fn update(engine_handle: &mut Engine) {
+//! // later inside of Game::update
+//! if engine_handle.is_action_pressed("jump") {
+//!     // jump
+//! }
+//! let x = engine_handle.action_axis("move_x");
+//! # }
+//! ```
+//! With the `serde` feature enabled an [InputMap] can be saved and loaded
+//! with any serde format, which is how player rebinds can be stored.
+
+use std::collections::BTreeMap;
+
+use crate::gamepad::{GamepadAxis, GamepadButton, GamepadHandle};
+use crate::input::{InputHandle, Key, ModifierKeys, MouseKey};
+
+// how far an analog input has to move before its action counts as down
+const ANALOG_THRESHOLD: f32 = 0.5;
+
+/// Something that can trigger an action
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Input {
+    Key(Key),
+    /// A key that only counts while all the modifiers are held down,
+    /// like Ctrl + S
+    Chord(Vec<ModifierKeys>, Key),
+    MouseKey(MouseKey),
+    /// The button on any connected gamepad
+    GamepadButton(GamepadButton),
+    /// The axis on any connected gamepad
+    GamepadAxis(GamepadAxis),
+}
+
+/// An [Input] and how much it counts towards the action. Scales are
+/// mostly used for axes where something like the A key should move left
+/// and have a scale of -1.0
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Binding {
+    pub input: Input,
+    pub scale: f32,
+}
+
+impl Binding {
+    /// Binds a key with a scale of 1.0
+    pub fn key(key: Key) -> Self {
+        Self::new(Input::Key(key))
+    }
+
+    /// Binds a key that needs all of the modifiers held with a scale of 1.0
+    pub fn chord(modifiers: &[ModifierKeys], key: Key) -> Self {
+        Self::new(Input::Chord(modifiers.to_vec(), key))
+    }
+
+    /// Binds a mouse button with a scale of 1.0
+    pub fn mouse_key(key: MouseKey) -> Self {
+        Self::new(Input::MouseKey(key))
+    }
+
+    /// Binds a gamepad button with a scale of 1.0
+    pub fn gamepad_button(button: GamepadButton) -> Self {
+        Self::new(Input::GamepadButton(button))
+    }
+
+    /// Binds a gamepad stick or trigger with a scale of 1.0
+    pub fn gamepad_axis(axis: GamepadAxis) -> Self {
+        Self::new(Input::GamepadAxis(axis))
+    }
+
+    /// Changes how much the binding counts towards the action
+    pub fn scaled(self, scale: f32) -> Self {
+        Self { scale, ..self }
+    }
+
+    fn new(input: Input) -> Self {
+        Self { input, scale: 1.0 }
+    }
+
+    // previous gets the value from last frame
+    fn value(&self, input: &InputHandle, gamepads: &GamepadHandle, previous: bool) -> f32 {
+        self.raw_value(input, gamepads, previous) * self.scale
+    }
+
+    // the value before scaling, used for checking if the binding is down
+    // so a binding with a small scale can still be pressed
+    fn raw_value(&self, input: &InputHandle, gamepads: &GamepadHandle, previous: bool) -> f32 {
+        match &self.input {
+            Input::Key(key) if previous => input.was_key_down(*key) as u8 as f32,
+            Input::Key(key) => input.is_key_down(*key) as u8 as f32,
+            Input::Chord(modifiers, key) if previous => {
+                let held = modifiers.iter().all(|m| input.check_previous_modifiers(*m));
+                (held && input.was_key_down(*key)) as u8 as f32
+            }
+            Input::Chord(modifiers, key) => {
+                let held = modifiers.iter().all(|m| input.check_modifiers(*m));
+                (held && input.is_key_down(*key)) as u8 as f32
+            }
+            Input::MouseKey(key) if previous => input.was_mouse_key_down(*key) as u8 as f32,
+            Input::MouseKey(key) => input.is_mouse_key_down(*key) as u8 as f32,
+            Input::GamepadButton(button) => {
+                gamepads.is_button_down_on_any(*button, previous) as u8 as f32
+            }
+            Input::GamepadAxis(axis) => gamepads.get_axis_on_any(*axis, previous),
+        }
+    }
+}
+
+/// Holds all the actions and what they are bound to. The input map used
+/// by the engine can be changed at any time with
+/// [Engine::get_input_map_mut](crate::engine_handle::Engine::get_input_map_mut)
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputMap {
+    actions: BTreeMap<String, Vec<Binding>>,
+}
+
+impl InputMap {
+    /// Creates an input map with no actions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a binding to an action, creating the action if it does not exist
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.actions
+            .entry(action.to_string())
+            .or_default()
+            .push(binding);
+    }
+
+    /// Removes every binding of an action that uses the input
+    pub fn unbind(&mut self, action: &str, input: &Input) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|b| &b.input != input);
+        }
+    }
+
+    /// Replaces all of the bindings of an action, this is usefull when
+    /// the player is rebinding controls
+    pub fn set_bindings(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.actions.insert(action.to_string(), bindings);
+    }
+
+    /// Gets all the bindings of an action
+    pub fn get_bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Removes an action and all of its bindings
+    pub fn remove_action(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    /// Iterates over the names of all the actions in alphabetical order
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+
+    pub(crate) fn is_down(
+        &self,
+        action: &str,
+        input: &InputHandle,
+        gamepads: &GamepadHandle,
+        previous: bool,
+    ) -> bool {
+        self.get_bindings(action)
+            .iter()
+            .any(|b| b.raw_value(input, gamepads, previous).abs() >= ANALOG_THRESHOLD)
+    }
+
+    pub(crate) fn get_axis(
+        &self,
+        action: &str,
+        input: &InputHandle,
+        gamepads: &GamepadHandle,
+    ) -> f32 {
+        self.get_bindings(action)
+            .iter()
+            .map(|b| b.value(input, gamepads, false))
+            .sum::<f32>()
+            .clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamepad::VirtualGamepads;
+    use winit::keyboard::ModifiersState;
+
+    fn input_with(keys: &[Key], modifiers: ModifiersState, mouse_keys: &[MouseKey]) -> InputHandle {
+        let mut input = InputHandle::new();
+        let mut frame = input.snapshot(0.0);
+        keys.iter()
+            .for_each(|key| frame.current_keyboard_state[*key as usize] = true);
+        mouse_keys
+            .iter()
+            .for_each(|key| frame.current_mouse_state[*key as usize] = true);
+        frame.modifier_state = modifiers.bits();
+        input.restore(&frame);
+        input
+    }
+
+    fn no_gamepads() -> GamepadHandle {
+        GamepadHandle::new(Box::new(VirtualGamepads::new()))
+    }
+
+    #[test]
+    fn keys_and_mouse_keys() {
+        let mut map = InputMap::new();
+        map.bind("jump", Binding::key(Key::Space));
+        map.bind("shoot", Binding::mouse_key(MouseKey::Left));
+        let gamepads = no_gamepads();
+
+        let input = input_with(&[Key::Space], ModifiersState::empty(), &[]);
+        assert!(map.is_down("jump", &input, &gamepads, false));
+        assert!(!map.is_down("jump", &input, &gamepads, true));
+        assert!(!map.is_down("shoot", &input, &gamepads, false));
+        assert!(!map.is_down("missing", &input, &gamepads, false));
+
+        let input = input_with(&[], ModifiersState::empty(), &[MouseKey::Left]);
+        assert!(map.is_down("shoot", &input, &gamepads, false));
+        assert!(!map.is_down("jump", &input, &gamepads, false));
+    }
+
+    #[test]
+    fn chords_need_the_modifiers() {
+        let mut map = InputMap::new();
+        map.bind("save", Binding::chord(&[ModifierKeys::Ctrl], Key::S));
+        let gamepads = no_gamepads();
+
+        let input = input_with(&[Key::S], ModifiersState::empty(), &[]);
+        assert!(!map.is_down("save", &input, &gamepads, false));
+
+        let input = input_with(&[Key::S], ModifiersState::CONTROL, &[]);
+        assert!(map.is_down("save", &input, &gamepads, false));
+    }
+
+    #[test]
+    fn scaled_keys_are_still_down() {
+        let mut map = InputMap::new();
+        map.bind("walk", Binding::key(Key::W).scaled(0.3));
+        let gamepads = no_gamepads();
+
+        let input = input_with(&[Key::W], ModifiersState::empty(), &[]);
+        assert!(map.is_down("walk", &input, &gamepads, false));
+        assert_eq!(map.get_axis("walk", &input, &gamepads), 0.3);
+    }
+
+    #[test]
+    fn key_axis_adds_and_clamps() {
+        let mut map = InputMap::new();
+        map.bind("move_x", Binding::key(Key::D));
+        map.bind("move_x", Binding::key(Key::A).scaled(-1.0));
+        map.bind("move_x", Binding::key(Key::Right).scaled(2.0));
+        let gamepads = no_gamepads();
+
+        let input = input_with(&[Key::A], ModifiersState::empty(), &[]);
+        assert_eq!(map.get_axis("move_x", &input, &gamepads), -1.0);
+
+        let input = input_with(&[Key::A, Key::D], ModifiersState::empty(), &[]);
+        assert_eq!(map.get_axis("move_x", &input, &gamepads), 0.0);
+
+        let input = input_with(&[Key::Right], ModifiersState::empty(), &[]);
+        assert_eq!(map.get_axis("move_x", &input, &gamepads), 1.0);
+    }
+
+    #[test]
+    fn virtual_gamepad_axes_and_buttons() {
+        let mut map = InputMap::new();
+        map.bind("move_x", Binding::gamepad_axis(GamepadAxis::LeftStickX));
+        map.bind("jump", Binding::gamepad_button(GamepadButton::South));
+        let input = InputHandle::new();
+        let pads = VirtualGamepads::new();
+        let mut gamepads = GamepadHandle::new(Box::new(pads.clone()));
+
+        let pad = pads.connect();
+        pads.set_axis(pad, GamepadAxis::LeftStickX, -1.0);
+        pads.press(pad, GamepadButton::South);
+        gamepads.end_of_frame_refresh();
+
+        assert_eq!(map.get_axis("move_x", &input, &gamepads), -1.0);
+        assert!(map.is_down("move_x", &input, &gamepads, false));
+        assert!(!map.is_down("move_x", &input, &gamepads, true));
+        assert!(map.is_down("jump", &input, &gamepads, false));
+
+        // inside the deadzone nothing is down
+        pads.set_axis(pad, GamepadAxis::LeftStickX, 0.1);
+        gamepads.end_of_frame_refresh();
+        assert_eq!(map.get_axis("move_x", &input, &gamepads), 0.0);
+        assert!(!map.is_down("move_x", &input, &gamepads, false));
+        assert!(map.is_down("move_x", &input, &gamepads, true));
+    }
+
+    #[test]
+    fn unbinding_and_rebinding() {
+        let mut map = InputMap::new();
+        map.bind("jump", Binding::key(Key::Space));
+        map.bind("jump", Binding::key(Key::W));
+        map.unbind("jump", &Input::Key(Key::Space));
+        assert_eq!(map.get_bindings("jump"), &[Binding::key(Key::W)]);
+
+        map.set_bindings("jump", vec![Binding::mouse_key(MouseKey::Right)]);
+        assert_eq!(
+            map.get_bindings("jump"),
+            &[Binding::mouse_key(MouseKey::Right)]
+        );
+
+        map.bind("attack", Binding::key(Key::J));
+        assert_eq!(map.actions().collect::<Vec<_>>(), ["attack", "jump"]);
+        map.remove_action("jump");
+        assert!(map.get_bindings("jump").is_empty());
+    }
+}
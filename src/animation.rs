@@ -0,0 +1,495 @@
+//! Contains [Animation] which steps through frames of a texture over time
+//! ```rust,no_run
+//! # use bottomless_pit::animation::{Animation, AnimationEvent, AnimationMode};
+//! # use bottomless_pit::colour::Colour;
+//! # use bottomless_pit::engine_handle::Engine;
+//! # use bottomless_pit::material::Material;
+//! # use bottomless_pit::render::Renderer;
+//! # use bottomless_pit::resource::ResourceId;
+//! # use bottomless_pit::sprite::SpriteSheet;
+//! # use bottomless_pit::vectors::Vec2;
+//! # fn update(engine_handle: &mut Engine, sheet: ResourceId<SpriteSheet>) {
+//! // built by hand from 32x32 frames in a row
+//! let mut walk = Animation::new(AnimationMode::Loop);
+//! for i in 0..4 {
+//!     let position = Vec2 { x: i as f32 * 32.0, y: 0.0 };
+//!     walk.add_frame(position, Vec2 { x: 32.0, y: 32.0 }, 0.1);
+//! }
+//!
+//! // or from a frame tag in an Aseprite file
+//! let sheet = engine_handle.get_sprite_sheet(sheet).unwrap();
+//! let mut attack = Animation::from_tag(sheet, "attack").unwrap().with_mode(AnimationMode::Once);
+//!
+//! // every update
+//! attack.update(engine_handle);
+//! if attack.get_events().contains(&AnimationEvent::Finished) {
+//!     // go back to idle
+//! }
+//! # }
+//! # fn render(material: &mut Material, walk: &Animation, render: &Renderer) {
+//! // and in render
+//! let (position, size) = (Vec2 { x: 10.0, y: 10.0 }, Vec2 { x: 64.0, y: 64.0 });
+//! material.add_animation(walk, position, size, Colour::WHITE, render);
+//! # }
+//! ```
+
+use crate::engine_handle::Engine;
+use crate::sprite::{SpriteSheet, TagDirection};
+use crate::vectors::Vec2;
+
+// frame length used when a sprite sheet does not say how long a frame is
+const DEFUALT_FRAME_DURATION: f32 = 0.1;
+// stops frames with no duration from looping forever
+const MIN_FRAME_DURATION: f32 = 0.001;
+// stops the duration from dividing by zero, pausing is done with Animation::pause
+const MIN_SPEED: f32 = 0.001;
+
+/// A single frame of an [Animation]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationFrame {
+    /// The top left of the frame on the texture in pixels
+    pub uv_position: Vec2<f32>,
+    /// The size of the frame on the texture in pixels
+    pub uv_size: Vec2<f32>,
+    /// How long the frame is shown for in seconds
+    pub duration: f32,
+}
+
+/// What happens when an [Animation] reaches its last frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnimationMode {
+    /// Starts again from the first frame
+    Loop,
+    /// Plays backwards to the first frame then forwards again
+    PingPong,
+    /// Stops on the last frame
+    Once,
+}
+
+/// Something that happened while an [Animation] was updated
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnimationEvent {
+    /// The animation moved to the frame with this index
+    FrameChanged(usize),
+    /// A looping or ping-pong animation went back to its first frame
+    Looped,
+    /// An animation played once reached its last frame and stopped
+    Finished,
+}
+
+/// A list of frames that are shown one after another. Call [Animation::update] every frame
+/// to move it forward and draw it with
+/// [Material::add_animation](crate::material::Material::add_animation).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    frames: Vec<AnimationFrame>,
+    mode: AnimationMode,
+    current_frame: usize,
+    // time spent on the current frame
+    elapsed: f32,
+    speed: f32,
+    playing: bool,
+    // false while a ping-pong animation is going backwards
+    forwards: bool,
+    // events from the last update
+    events: Vec<AnimationEvent>,
+}
+
+impl Animation {
+    /// Creates an animation with no frames, add them with [Animation::add_frame]
+    pub fn new(mode: AnimationMode) -> Self {
+        Self {
+            frames: Vec::new(),
+            mode,
+            current_frame: 0,
+            elapsed: 0.0,
+            speed: 1.0,
+            playing: true,
+            forwards: true,
+            events: Vec::new(),
+        }
+    }
+
+    /// Creates an animation from frames of a sprite sheet in the given order. Frames without
+    /// a duration in the sheet are shown for 0.1 seconds and names that are not in the sheet
+    /// are skipped.
+    pub fn from_sprite_sheet(sheet: &SpriteSheet, frames: &[&str], mode: AnimationMode) -> Self {
+        let mut animation = Self::new(mode);
+        frames
+            .iter()
+            .filter_map(|name| sheet.get_frame(name))
+            .for_each(|frame| {
+                let duration = frame.duration.unwrap_or(DEFUALT_FRAME_DURATION);
+                animation.add_frame(frame.position, frame.size, duration);
+            });
+
+        animation
+    }
+
+    /// Creates an animation from an Aseprite frame tag using the durations and direction set
+    /// in Aseprite. Forward and reverse tags loop while ping-pong tags use [AnimationMode::PingPong].
+    /// Returns None if the sprite sheet has no tag with that name or the tag uses frames
+    /// that are not in the sheet.
+    pub fn from_tag(sheet: &SpriteSheet, tag: &str) -> Option<Self> {
+        let tag = sheet.get_tag(tag)?;
+        let indices = tag.from..=tag.to;
+        let indices: Vec<usize> = match tag.direction {
+            TagDirection::Forward | TagDirection::PingPong => indices.collect(),
+            TagDirection::Reverse | TagDirection::PingPongReverse => indices.rev().collect(),
+        };
+
+        let mode = match tag.direction {
+            TagDirection::Forward | TagDirection::Reverse => AnimationMode::Loop,
+            TagDirection::PingPong | TagDirection::PingPongReverse => AnimationMode::PingPong,
+        };
+
+        let mut animation = Self::new(mode);
+        for index in indices {
+            let frame = sheet.get_frame_by_index(index)?;
+            let duration = frame.duration.unwrap_or(DEFUALT_FRAME_DURATION);
+            animation.add_frame(frame.position, frame.size, duration);
+        }
+
+        Some(animation)
+    }
+
+    /// Adds a frame to the end of the animation. The position and size are in pixels of the
+    /// texture just like [Material::add_rectangle_with_uv](crate::material::Material::add_rectangle_with_uv)
+    /// and the duration is in seconds.
+    pub fn add_frame(&mut self, uv_position: Vec2<f32>, uv_size: Vec2<f32>, duration: f32) {
+        self.frames.push(AnimationFrame {
+            uv_position,
+            uv_size,
+            duration: duration.max(MIN_FRAME_DURATION),
+        });
+    }
+
+    /// Changes what happens when the animation reaches its last frame
+    pub fn with_mode(self, mode: AnimationMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    /// Changes how fast the animation plays, 2.0 is twice as fast. Speeds below 0.001
+    /// are raised to 0.001, use [Animation::pause] to stop the animation instead.
+    pub fn with_speed(self, speed: f32) -> Self {
+        Self {
+            speed: speed.max(MIN_SPEED),
+            ..self
+        }
+    }
+
+    /// Moves the animation forward by [Engine::get_frame_delta_time]
+    pub fn update(&mut self, engine: &Engine) {
+        self.advance(engine.get_frame_delta_time());
+    }
+
+    /// Moves the animation forward by an amount of seconds, use this inside of
+    /// [Game::fixed_update](crate::Game::fixed_update) with the fixed timestep
+    pub fn advance(&mut self, delta_time: f32) {
+        self.events.clear();
+        if !self.playing || self.frames.is_empty() {
+            return;
+        }
+
+        self.elapsed += delta_time * self.speed;
+        while self.playing && self.elapsed >= self.frames[self.current_frame].duration {
+            self.elapsed -= self.frames[self.current_frame].duration;
+            self.next_frame();
+        }
+    }
+
+    fn next_frame(&mut self) {
+        let last = self.frames.len() - 1;
+        let previous = self.current_frame;
+
+        match self.mode {
+            AnimationMode::Loop if self.current_frame == last => {
+                self.current_frame = 0;
+                self.events.push(AnimationEvent::Looped);
+            }
+            AnimationMode::Loop => self.current_frame += 1,
+            AnimationMode::Once if self.current_frame == last => {
+                self.playing = false;
+                self.elapsed = 0.0;
+                self.events.push(AnimationEvent::Finished);
+            }
+            AnimationMode::Once => self.current_frame += 1,
+            AnimationMode::PingPong if self.forwards => {
+                if self.current_frame == last {
+                    self.forwards = false;
+                    self.current_frame = last.saturating_sub(1);
+                } else {
+                    self.current_frame += 1;
+                }
+            }
+            AnimationMode::PingPong => {
+                if self.current_frame == 0 {
+                    self.forwards = true;
+                    self.current_frame = last.min(1);
+                } else {
+                    self.current_frame -= 1;
+                }
+
+                if self.current_frame == 0 || last == 0 {
+                    self.events.push(AnimationEvent::Looped);
+                }
+            }
+        }
+
+        if self.current_frame != previous {
+            self.events
+                .push(AnimationEvent::FrameChanged(self.current_frame));
+        }
+    }
+
+    /// The events that happened during the last call to [Animation::update] or [Animation::advance]
+    pub fn get_events(&self) -> &[AnimationEvent] {
+        &self.events
+    }
+
+    /// Gets the frame that should be shown right now, returns None if there are no frames
+    pub fn get_current_frame(&self) -> Option<&AnimationFrame> {
+        self.frames.get(self.current_frame)
+    }
+
+    /// Gets the index of the frame that should be shown right now
+    pub fn get_frame_index(&self) -> usize {
+        self.current_frame
+    }
+
+    /// Jumps to a frame, indexes past the end go to the last frame
+    pub fn set_frame_index(&mut self, index: usize) {
+        self.current_frame = index.min(self.frames.len().saturating_sub(1));
+        self.elapsed = 0.0;
+    }
+
+    /// All of the frames in the animation
+    pub fn get_frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// Gets the total time it takes to go from the first to the last frame in seconds
+    pub fn get_duration(&self) -> f32 {
+        self.frames.iter().map(|f| f.duration).sum::<f32>() / self.speed
+    }
+
+    /// Continues the animation after it was paused or finished playing once
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.restart();
+        }
+
+        self.playing = true;
+    }
+
+    /// Stops the animation on its current frame
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Goes back to the first frame and starts playing
+    pub fn restart(&mut self) {
+        self.current_frame = 0;
+        self.elapsed = 0.0;
+        self.forwards = true;
+        self.playing = true;
+        self.events.clear();
+    }
+
+    /// Checks if the animation is moving
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Checks if an [AnimationMode::Once] animation has reached its end
+    pub fn is_finished(&self) -> bool {
+        self.mode == AnimationMode::Once
+            && !self.playing
+            && self.current_frame + 1 >= self.frames.len()
+    }
+
+    /// Changes how fast the animation plays, see [Animation::with_speed]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(MIN_SPEED);
+    }
+
+    /// Changes what happens when the animation reaches its last frame
+    pub fn set_mode(&mut self, mode: AnimationMode) {
+        self.mode = mode;
+    }
+
+    /// Gets what happens when the animation reaches its last frame
+    pub fn get_mode(&self) -> AnimationMode {
+        self.mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AnimationEvent::{Finished, FrameChanged, Looped};
+
+    // durations that add up exactly in floats
+    fn animation(mode: AnimationMode, frames: usize) -> Animation {
+        let mut animation = Animation::new(mode);
+        for i in 0..frames {
+            let position = Vec2 {
+                x: i as f32 * 8.0,
+                y: 0.0,
+            };
+            animation.add_frame(position, Vec2 { x: 8.0, y: 8.0 }, 0.5);
+        }
+
+        animation
+    }
+
+    fn frame_order(animation: &mut Animation, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animation.advance(0.5);
+                animation.get_frame_index()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_order() {
+        let mut walk = animation(AnimationMode::Loop, 3);
+        assert_eq!(frame_order(&mut walk, 7), [1, 2, 0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn ping_pong_order() {
+        let mut walk = animation(AnimationMode::PingPong, 3);
+        assert_eq!(frame_order(&mut walk, 8), [1, 2, 1, 0, 1, 2, 1, 0]);
+
+        let mut single = animation(AnimationMode::PingPong, 1);
+        assert_eq!(frame_order(&mut single, 2), [0, 0]);
+        assert_eq!(single.get_events(), [Looped]);
+    }
+
+    #[test]
+    fn once_order() {
+        let mut attack = animation(AnimationMode::Once, 3);
+        assert_eq!(frame_order(&mut attack, 4), [1, 2, 2, 2]);
+        assert!(attack.is_finished());
+        assert!(!attack.is_playing());
+
+        attack.play();
+        assert_eq!(attack.get_frame_index(), 0);
+        assert!(attack.is_playing());
+    }
+
+    #[test]
+    fn leftover_time_carries_over() {
+        let mut walk = animation(AnimationMode::Loop, 3);
+        walk.advance(1.25);
+        assert_eq!(walk.get_frame_index(), 2);
+        assert_eq!(walk.get_events(), [FrameChanged(1), FrameChanged(2)]);
+
+        // 0.25 was left on the last frame so this is enough to loop
+        walk.advance(0.25);
+        assert_eq!(walk.get_frame_index(), 0);
+        assert_eq!(walk.get_events(), [Looped, FrameChanged(0)]);
+
+        walk.advance(0.25);
+        assert_eq!(walk.get_frame_index(), 0);
+        assert!(walk.get_events().is_empty());
+
+        // twice as fast so the 0.25 left plus 0.25 * 2 goes one frame
+        walk.set_speed(2.0);
+        walk.advance(0.25);
+        assert_eq!(walk.get_frame_index(), 1);
+    }
+
+    #[test]
+    fn events() {
+        let mut bounce = animation(AnimationMode::PingPong, 3);
+        bounce.advance(1.0);
+        assert_eq!(bounce.get_events(), [FrameChanged(1), FrameChanged(2)]);
+        bounce.advance(1.0);
+        assert_eq!(
+            bounce.get_events(),
+            [FrameChanged(1), Looped, FrameChanged(0)]
+        );
+
+        let mut attack = animation(AnimationMode::Once, 2);
+        attack.advance(10.0);
+        assert_eq!(attack.get_events(), [FrameChanged(1), Finished]);
+        attack.advance(10.0);
+        assert!(attack.get_events().is_empty());
+
+        let mut paused = animation(AnimationMode::Loop, 2);
+        paused.pause();
+        paused.advance(10.0);
+        assert_eq!(paused.get_frame_index(), 0);
+        assert!(paused.get_events().is_empty());
+    }
+
+    #[test]
+    fn speed_never_reaches_zero() {
+        let walk = animation(AnimationMode::Loop, 3).with_speed(2.0);
+        assert_eq!(walk.get_duration(), 0.75);
+
+        let mut stopped = animation(AnimationMode::Loop, 3).with_speed(0.0);
+        assert!(stopped.get_duration().is_finite());
+        stopped.set_speed(-1.0);
+        stopped.advance(1.0);
+        assert!(stopped.get_duration().is_finite());
+        assert_eq!(stopped.get_frame_index(), 0);
+    }
+
+    fn tagged_sheet() -> SpriteSheet {
+        let json = br#"{
+            "frames": [
+                { "filename": "0", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 250 },
+                { "filename": "1", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 500 },
+                { "filename": "2", "frame": { "x": 16, "y": 0, "w": 8, "h": 8 } },
+                { "filename": "3", "frame": { "x": 24, "y": 0, "w": 8, "h": 8 }, "duration": 250 }
+            ],
+            "meta": {
+                "frameTags": [
+                    { "name": "forward", "from": 1, "to": 3, "direction": "forward" },
+                    { "name": "reverse", "from": 0, "to": 2, "direction": "reverse" },
+                    { "name": "pingpong", "from": 0, "to": 1, "direction": "pingpong" }
+                ]
+            }
+        }"#;
+
+        SpriteSheet::from_resource_data(json).unwrap()
+    }
+
+    fn x_and_duration(animation: &Animation) -> Vec<(f32, f32)> {
+        animation
+            .get_frames()
+            .iter()
+            .map(|frame| (frame.uv_position.x, frame.duration))
+            .collect()
+    }
+
+    #[test]
+    fn from_tags() {
+        let sheet = tagged_sheet();
+
+        let forward = Animation::from_tag(&sheet, "forward").unwrap();
+        assert_eq!(forward.get_mode(), AnimationMode::Loop);
+        // frames without a duration use the defualt
+        assert_eq!(
+            x_and_duration(&forward),
+            [(8.0, 0.5), (16.0, DEFUALT_FRAME_DURATION), (24.0, 0.25)]
+        );
+
+        let reverse = Animation::from_tag(&sheet, "reverse").unwrap();
+        assert_eq!(reverse.get_mode(), AnimationMode::Loop);
+        assert_eq!(
+            x_and_duration(&reverse),
+            [(16.0, DEFUALT_FRAME_DURATION), (8.0, 0.5), (0.0, 0.25)]
+        );
+
+        let ping_pong = Animation::from_tag(&sheet, "pingpong").unwrap();
+        assert_eq!(ping_pong.get_mode(), AnimationMode::PingPong);
+        assert_eq!(x_and_duration(&ping_pong), [(0.0, 0.25), (8.0, 0.5)]);
+
+        assert!(Animation::from_tag(&sheet, "missing").is_none());
+    }
+}
@@ -0,0 +1,438 @@
+//! Contains [AssetArchive] which packs many asset files into a single file. Once an
+//! archive is mounted with [Engine::mount_archive](crate::engine_handle::Engine::mount_archive)
+//! or [Engine::mount_archive_file](crate::engine_handle::Engine::mount_archive_file)
+//! every resource is looked for inside the mounted archives before the file system,
+//! so the same paths work whether the assets are packed or not. On wasm this turns
+//! hundreds of fetches into a single download.
+//! ```rust,no_run
+//! # use bottomless_pit::archive::ArchiveBuilder;
+//! // in a build script or a small tool
+//! ArchiveBuilder::new()
+//!     .add_directory("assets")
+//!     .unwrap()
+//!     .build()
+//!     .save("assets.bpak")
+//!     .unwrap();
+//! ```
+//! ```rust,no_run
+//! # use bottomless_pit::engine_handle::EngineBuilder;
+//! # use bottomless_pit::resource::LoadingOp;
+//! # use bottomless_pit::texture::Texture;
+//! let mut engine = EngineBuilder::new().build().unwrap();
+//! engine.mount_archive_file("assets.bpak");
+//! // comes out of the archive if it was packed
+//! let texture = Texture::new(&mut engine, "assets/player.png", LoadingOp::Blocking);
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::{Component, Path};
+
+const MAGIC: &[u8; 4] = b"BPAK";
+const VERSION: u8 = 1;
+
+/// A collection of files packed into one, made with [ArchiveBuilder]
+#[derive(Debug)]
+pub struct AssetArchive {
+    // the whole file, entries point into it so nothing is copied when loading
+    data: Vec<u8>,
+    entries: HashMap<String, Range<usize>>,
+}
+
+impl AssetArchive {
+    /// Reads an archive from bytes made with [AssetArchive::to_bytes], this works
+    /// nicely with `include_bytes!` to put the assets right into the executable
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ArchiveError> {
+        let mut reader = Reader(&data);
+        if reader.take(4)? != MAGIC {
+            return Err(ArchiveError::InvalidFile);
+        }
+
+        if reader.u8()? != VERSION {
+            return Err(ArchiveError::UnsupportedVersion);
+        }
+
+        let mut entries = HashMap::new();
+        for _ in 0..reader.u32()? {
+            let len = reader.u32()? as usize;
+            let path = String::from_utf8(reader.take(len)?.to_vec())
+                .map_err(|_| ArchiveError::InvalidFile)?;
+            let start = reader.u64()? as usize;
+            let end = start
+                .checked_add(reader.u64()? as usize)
+                .ok_or(ArchiveError::InvalidFile)?;
+            entries.insert(path, start..end);
+        }
+
+        // everything after the index is file data
+        let data_start = data.len() - reader.0.len();
+        if entries
+            .values()
+            .any(|range| range.start < data_start || range.end > data.len())
+        {
+            return Err(ArchiveError::InvalidFile);
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    /// Reads an archive from a file made with [AssetArchive::save]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        let data = std::fs::read(path)?;
+        Self::from_bytes(data)
+    }
+
+    /// Converts the archive into its file format
+    pub fn to_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Writes the archive to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ArchiveError> {
+        std::fs::write(path, &self.data)?;
+        Ok(())
+    }
+
+    /// Gets the contents of a file in the archive. Paths are compared after
+    /// removing things like `./` so `./assets/a.png` and `assets/a.png` are the same
+    pub fn get_file<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+        let range = self.entries.get(&normalize(path.as_ref()))?;
+        Some(&self.data[range.clone()])
+    }
+
+    /// Checks if there is a file at the path in the archive
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.entries.contains_key(&normalize(path.as_ref()))
+    }
+
+    /// The paths of every file in the archive
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|path| path.as_str())
+    }
+
+    /// The amount of files in the archive
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the archive has no files
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Collects files to be packed into an [AssetArchive]
+#[derive(Debug, Default)]
+pub struct ArchiveBuilder {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl ArchiveBuilder {
+    pub fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    /// Adds a file under the path it will be loaded with, adding the same path
+    /// twice replaces the first one
+    pub fn add_file<P: AsRef<Path>>(mut self, path: P, data: Vec<u8>) -> Self {
+        let path = normalize(path.as_ref());
+        self.files.retain(|(other, _)| *other != path);
+        self.files.push((path, data));
+        self
+    }
+
+    /// Reads every file in the directory and its sub directories. The files keep
+    /// the path they were read from so `assets/player.png` is still loaded with
+    /// `assets/player.png`
+    pub fn add_directory<P: AsRef<Path>>(self, directory: P) -> Result<Self, ArchiveError> {
+        let mut builder = self;
+        let mut entries = std::fs::read_dir(directory)?.collect::<Result<Vec<_>, _>>()?;
+        // keeps archives of the same directory identical
+        entries.sort_by_key(|entry| entry.path());
+
+        for entry in entries {
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                builder = builder.add_directory(path)?;
+            } else {
+                let data = std::fs::read(&path)?;
+                builder = builder.add_file(path, data);
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Packs the files into an archive
+    pub fn build(self) -> AssetArchive {
+        let mut writer = Writer(Vec::new());
+        writer.0.extend_from_slice(MAGIC);
+        writer.u8(VERSION);
+        writer.u32(self.files.len() as u32);
+
+        let index_size: usize = self
+            .files
+            .iter()
+            .map(|(path, _)| 4 + path.len() + 8 + 8)
+            .sum();
+        let mut offset = writer.0.len() + index_size;
+
+        let mut entries = HashMap::new();
+        for (path, data) in self.files.iter() {
+            writer.u32(path.len() as u32);
+            writer.0.extend_from_slice(path.as_bytes());
+            writer.u64(offset as u64);
+            writer.u64(data.len() as u64);
+            entries.insert(path.clone(), offset..offset + data.len());
+            offset += data.len();
+        }
+
+        for (_, data) in self.files.iter() {
+            writer.0.extend_from_slice(data);
+        }
+
+        AssetArchive {
+            data: writer.0,
+            entries,
+        }
+    }
+}
+
+// turns a path into the form used as a key in archives, uses / on every platform
+// and treats \ as a separator too so windows style paths work everywhere
+fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(&path).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+
+    parts.join("/")
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, amount: usize) -> Result<&[u8], ArchiveError> {
+        if self.0.len() < amount {
+            return Err(ArchiveError::InvalidFile);
+        }
+
+        let (taken, rest) = self.0.split_at(amount);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, ArchiveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ArchiveError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ArchiveError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Errors that can occur when making, saving or loading an [AssetArchive]
+#[derive(Debug)]
+pub enum ArchiveError {
+    IoError(std::io::Error),
+    /// The data is not an asset archive or has been cut off
+    InvalidFile,
+    /// The archive was made by a diffrent version of the engine
+    UnsupportedVersion,
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "{}", e),
+            Self::InvalidFile => write!(f, "The file is not a valid asset archive"),
+            Self::UnsupportedVersion => {
+                write!(f, "The asset archive was made by a diffrent version")
+            }
+        }
+    }
+}
+
+impl Error for ArchiveError {}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // builds the bytes of an archive with one entry by hand
+    fn one_entry(path: &str, start: u64, len: u64, data: &[u8]) -> Vec<u8> {
+        let mut writer = Writer(Vec::new());
+        writer.0.extend_from_slice(MAGIC);
+        writer.u8(VERSION);
+        writer.u32(1);
+        writer.u32(path.len() as u32);
+        writer.0.extend_from_slice(path.as_bytes());
+        writer.u64(start);
+        writer.u64(len);
+        writer.0.extend_from_slice(data);
+        writer.0
+    }
+
+    #[test]
+    fn round_trip() {
+        let archive = ArchiveBuilder::new()
+            .add_file("assets/a.png", vec![1, 2, 3])
+            .add_file("./assets/sounds/b.ogg", vec![4, 5])
+            .add_file("empty.txt", Vec::new())
+            .add_file("assets/a.png", vec![6, 7, 8, 9])
+            .build();
+
+        let loaded = AssetArchive::from_bytes(archive.to_bytes().to_vec()).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.get_file("assets/a.png"), Some(&[6, 7, 8, 9][..]));
+        assert_eq!(loaded.get_file("assets/sounds/b.ogg"), Some(&[4, 5][..]));
+        assert_eq!(loaded.get_file("empty.txt"), Some(&[][..]));
+        assert!(!loaded.contains("missing.png"));
+
+        let mut paths: Vec<&str> = loaded.paths().collect();
+        paths.sort();
+        assert_eq!(paths, ["assets/a.png", "assets/sounds/b.ogg", "empty.txt"]);
+    }
+
+    #[test]
+    fn empty_round_trip() {
+        let archive = ArchiveBuilder::new().build();
+        let loaded = AssetArchive::from_bytes(archive.to_bytes().to_vec()).unwrap();
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(normalize(Path::new("./a")), "a");
+        assert_eq!(normalize(Path::new("a/../b")), "b");
+        assert_eq!(normalize(Path::new("a/./b/c")), "a/b/c");
+        assert_eq!(normalize(Path::new("a\\b\\c.png")), "a/b/c.png");
+        assert_eq!(normalize(Path::new(".\\a\\..\\b")), "b");
+        assert_eq!(normalize(Path::new("/a/b")), "a/b");
+    }
+
+    #[test]
+    fn lookups_are_normalized() {
+        let archive = ArchiveBuilder::new()
+            .add_file("assets\\a.png", vec![1])
+            .build();
+        assert!(archive.contains("assets/a.png"));
+        assert!(archive.contains("./assets/b/../a.png"));
+        assert_eq!(archive.get_file("assets\\a.png"), Some(&[1][..]));
+    }
+
+    #[test]
+    fn bad_header() {
+        assert!(matches!(
+            AssetArchive::from_bytes(Vec::new()),
+            Err(ArchiveError::InvalidFile)
+        ));
+        assert!(matches!(
+            AssetArchive::from_bytes(b"NOPE\x01\0\0\0\0".to_vec()),
+            Err(ArchiveError::InvalidFile)
+        ));
+        assert!(matches!(
+            AssetArchive::from_bytes(b"BPAK\x02\0\0\0\0".to_vec()),
+            Err(ArchiveError::UnsupportedVersion)
+        ));
+    }
+
+    #[test]
+    fn truncated_archives() {
+        let archive = ArchiveBuilder::new()
+            .add_file("a.png", vec![1, 2, 3])
+            .add_file("b.png", vec![4, 5, 6])
+            .build();
+        let bytes = archive.to_bytes();
+
+        // every cut off point is either in the index or in the file data
+        for len in 0..bytes.len() {
+            assert!(
+                matches!(
+                    AssetArchive::from_bytes(bytes[..len].to_vec()),
+                    Err(ArchiveError::InvalidFile)
+                ),
+                "archive cut to {} bytes was loaded",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_entries() {
+        // header is 4 + 1 + 4 and the entry is 4 + 1 + 8 + 8 so data starts at 30
+        let valid = one_entry("a", 30, 2, &[1, 2]);
+        assert!(AssetArchive::from_bytes(valid).is_ok());
+
+        let cases = [
+            // past the end of the data
+            one_entry("a", 30, 3, &[1, 2]),
+            // points into the index
+            one_entry("a", 0, 2, &[1, 2]),
+            // start + len overflows
+            one_entry("a", 30, u64::MAX, &[1, 2]),
+        ];
+
+        for bytes in cases {
+            assert!(matches!(
+                AssetArchive::from_bytes(bytes),
+                Err(ArchiveError::InvalidFile)
+            ));
+        }
+    }
+
+    #[test]
+    fn entry_count_larger_than_index() {
+        let mut bytes = one_entry("a", 30, 2, &[1, 2]);
+        bytes[5..9].copy_from_slice(&2u32.to_le_bytes());
+        assert!(matches!(
+            AssetArchive::from_bytes(bytes),
+            Err(ArchiveError::InvalidFile)
+        ));
+    }
+
+    #[test]
+    fn path_that_is_not_utf8() {
+        let mut bytes = one_entry("a", 30, 2, &[1, 2]);
+        bytes[13] = 0xff;
+        assert!(matches!(
+            AssetArchive::from_bytes(bytes),
+            Err(ArchiveError::InvalidFile)
+        ));
+    }
+}
@@ -48,8 +48,27 @@ impl Camera {
         }
     }
 
+    // the smallest rectangle around every corner of the screen in camera space
+    fn visible_area(&self, screen_size: Vec2<u32>) -> (Vec2<f32>, Vec2<f32>) {
+        let width = screen_size.x as f32;
+        let height = screen_size.y as f32;
+        let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+            .map(|(x, y)| self.transform_point(Vec2 { x, y }, screen_size));
+
+        let mut start = corners[0];
+        let mut end = corners[0];
+        for corner in &corners[1..] {
+            start.x = start.x.min(corner.x);
+            start.y = start.y.min(corner.y);
+            end.x = end.x.max(corner.x);
+            end.y = end.y.max(corner.y);
+        }
+
+        (start, end)
+    }
+
     /// This will transform a point in screen space to camera space.
-    /// You can get the screen size from [crate::Engine::get_window_size]
+    /// You can get the screen size from [crate::Engine::get_render_size]
     pub fn transform_point(&self, point: Vec2<f32>, screen_size: Vec2<u32>) -> Vec2<f32> {
         let screen_size = Vec2 {
             x: screen_size.x as f32,
@@ -145,11 +164,14 @@ impl Camera {
 
     /// Sets this camera to the active camera transforming all objects with this camera.
     pub fn set_active<'others>(&'others mut self, renderer: &mut Renderer<'_, 'others>) {
+        // anything batched so far was meant for the previous camera
+        renderer.flush_batch();
         self.write_matrix(renderer.wgpu, renderer.size);
+        renderer.visible_area = self.visible_area(renderer.size);
 
         renderer
             .pass
-            .set_bind_group(1, &self.inner.as_ref().unwrap().bind_group, &[]);
+            .set_bind_group(1, &self.inner.as_ref().unwrap().bind_group);
     }
 }
 
@@ -1,13 +1,21 @@
 //! The colour struct module
 
-use encase::ShaderType;
-/// A struct containing RGBA Colours (spelled properly) with some predefind colour consts
-#[derive(Copy, Clone, Debug, PartialEq, ShaderType)]
-pub struct Colour {
-    r: f32,
-    g: f32,
-    b: f32,
-    a: f32,
+pub use shader_type::Colour;
+
+// the ShaderType derive adds trait checks next to the struct that are never called,
+// so it lives in its own module to keep the allow away from the rest of the file
+#[allow(dead_code)]
+mod shader_type {
+    use encase::ShaderType;
+
+    /// A struct containing RGBA Colours (spelled properly) with some predefind colour consts
+    #[derive(Copy, Clone, Debug, PartialEq, ShaderType)]
+    pub struct Colour {
+        pub(super) r: f32,
+        pub(super) g: f32,
+        pub(super) b: f32,
+        pub(super) a: f32,
+    }
 }
 
 impl Colour {
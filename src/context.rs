@@ -1,6 +1,6 @@
 use crate::engine_handle::{BuildError, DefualtResources, EngineBuilder};
 use crate::layouts;
-use crate::render::make_pipeline;
+use crate::render::{make_pipeline, BatchBuffers, BlendMode};
 use crate::resource::ResourceManager;
 use crate::shader::Shader;
 use crate::text::TextRenderer;
@@ -17,19 +17,29 @@ use winit::window::{Window, WindowAttributes};
 
 pub(crate) struct GraphicsContext {
     pub(crate) wgpu: WgpuClump,
-    pub(crate) surface: wgpu::Surface<'static>,
+    pub(crate) target: RenderTarget,
     pub(crate) texture_sampler: wgpu::Sampler,
     pub(crate) config: wgpu::SurfaceConfiguration,
     pub(crate) camera_bind_group: wgpu::BindGroup,
     pub(crate) camera_buffer: wgpu::Buffer,
     pub(crate) text_renderer: TextRenderer,
-    pub(crate) window: Arc<Window>,
+    pub(crate) batch_buffers: BatchBuffers,
+    // always keep this last so the window is dropped after everything else
+    pub(crate) window: Option<Arc<Window>>,
+}
+
+/// What the engine draws into at the end of every frame
+pub(crate) enum RenderTarget {
+    Surface(wgpu::Surface<'static>),
+    /// used when there is no window, this gets drawn into like any other
+    /// surface but is never presented
+    Headless(wgpu::Texture),
 }
 
 impl GraphicsContext {
     pub fn from_active_loop(
         event_loop: &ActiveEventLoop,
-        mut window_options: WindowOptions,
+        #[allow(unused_mut)] mut window_options: WindowOptions,
         resource_manager: &mut ResourceManager,
         resources: &DefualtResources,
     ) -> Self {
@@ -42,7 +52,6 @@ impl GraphicsContext {
 
         #[cfg(target_arch="wasm32")]
         {
-            use crate::engine_handle::BuildError;
             use winit::platform::web::WindowAttributesExtWebSys;
             use wasm_bindgen::JsCast;
 
@@ -82,17 +91,7 @@ impl GraphicsContext {
 
         let window = Arc::new(event_loop.create_window(window_options.attributes).unwrap());
 
-        let backend = wgpu::Backends::all();
-
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: backend,
-            backend_options: wgpu::BackendOptions {
-                gl: wgpu::GlBackendOptions { gles_minor_version: wgpu::Gles3MinorVersion::Automatic, fence_behavior: wgpu::GlFenceBehavior::Normal },
-                dx12: wgpu::Dx12BackendOptions { shader_compiler: wgpu::Dx12Compiler::Fxc },
-                noop: wgpu::NoopBackendOptions { enable: (false) }
-            },
-            flags: wgpu::InstanceFlags::default(),
-        });
+        let instance = create_instance();
 
         let surface = instance.create_surface(window.clone()).unwrap();
 
@@ -105,7 +104,7 @@ impl GraphicsContext {
         let intermediate = Intermediate {
             surface,
             window,
-            instance,
+            _instance: instance,
             size,
             presentation: window_options.presentation,
         };
@@ -124,20 +123,7 @@ impl GraphicsContext {
             Err(e) => panic!("AHHHHHHHH no adapter: {}", e),
         };
 
-        let limits = adapter.limits();
-
-        let (device, queue) = pollster::block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                required_features: wgpu::Features::empty(),
-                required_limits: limits,
-                label: None,
-                memory_hints: wgpu::MemoryHints::Performance,
-                trace: wgpu::Trace::Off, //TODO: allow users to pass through directory for trace
-            },
-        ))
-        .unwrap();
-
-        let wgpu_clump = WgpuClump { device, queue };
+        let wgpu_clump = request_device(&adapter).unwrap();
 
         let surface_capabilities = pre_made.surface.get_capabilities(&adapter);
         let surface_format = surface_capabilities
@@ -147,8 +133,12 @@ impl GraphicsContext {
             .find(|f| f.is_srgb())
             .unwrap_or(surface_capabilities.formats[0]);
 
+        // COPY_SRC lets frames be read back for screenshots, not every platform supports it
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_capabilities.usages & wgpu::TextureUsages::COPY_SRC);
+
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format: surface_format,
             width: pre_made.size.x,
             height: pre_made.size.y,
@@ -160,6 +150,65 @@ impl GraphicsContext {
 
         pre_made.surface.configure(&wgpu_clump.device, &config);
 
+        Self::from_parts(
+            wgpu_clump,
+            RenderTarget::Surface(pre_made.surface),
+            config,
+            Some(pre_made.window),
+            resource_manager,
+            resources,
+        )
+    }
+
+    /// Creates a context that renders into an offscreen texture instead of a window.
+    /// This does not need an event loop so it can be made right away.
+    pub(crate) fn headless(
+        size: Vec2<u32>,
+        resource_manager: &mut ResourceManager,
+        resources: &DefualtResources,
+    ) -> Result<Self, BuildError> {
+        let instance = create_instance();
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .map_err(|_| BuildError::FailedToCreateAdapter)?;
+
+        let wgpu_clump = request_device(&adapter)?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: HEADLESS_USAGES,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.x,
+            height: size.y,
+            present_mode: wgpu::PresentMode::AutoNoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        let texture = create_headless_texture(&wgpu_clump, &config);
+
+        Ok(Self::from_parts(
+            wgpu_clump,
+            RenderTarget::Headless(texture),
+            config,
+            None,
+            resource_manager,
+            resources,
+        ))
+    }
+
+    fn from_parts(
+        wgpu_clump: WgpuClump,
+        target: RenderTarget,
+        config: wgpu::SurfaceConfiguration,
+        window: Option<Arc<Window>>,
+        resource_manager: &mut ResourceManager,
+        resources: &DefualtResources,
+    ) -> Self {
         let texture_sampler = wgpu_clump.device.create_sampler(&wgpu::SamplerDescriptor {
             // what to do when given cordinates outside the textures height/width
             address_mode_u: wgpu::AddressMode::Repeat,
@@ -186,8 +235,8 @@ impl GraphicsContext {
             0.0,
             1.0,
             0.0,
-            pre_made.size.x as f32,
-            pre_made.size.y as f32,
+            config.width as f32,
+            config.height as f32,
             0.0,
             0.0,
         ];
@@ -288,6 +337,7 @@ impl GraphicsContext {
                 });
 
         let white_pixel = Texture::new_direct(
+            white_pixel_texture,
             white_pixel_view,
             white_pixel_bind_group,
             Vec2 { x: 1.0, y: 1.0 },
@@ -307,28 +357,33 @@ impl GraphicsContext {
             &[LineVertex::desc()],
             &line_shader,
             texture_format,
+            BlendMode::Alpha,
             Some("line_renderer"),
         );
 
         let text_renderer = TextRenderer::new(&wgpu_clump);
+        let batch_buffers = BatchBuffers::new(&wgpu_clump.device);
 
         let line_shader = Shader::from_pipeline(line_pipeline);
         let generic_shader = Shader::defualt(&wgpu_clump, texture_format);
+        let instanced_shader = Shader::defualt_instanced(&wgpu_clump, texture_format);
 
         resource_manager.insert_pipeline(resources.line_pipeline_id, line_shader);
         resource_manager.insert_pipeline(resources.default_pipeline_id, generic_shader);
+        resource_manager.insert_pipeline(resources.instanced_pipeline_id, instanced_shader);
 
         resource_manager.insert_texture(resources.defualt_texture_id, white_pixel);
 
         Self {
             wgpu: wgpu_clump,
-            window: pre_made.window,
-            surface: pre_made.surface,
+            window,
+            target,
             texture_sampler,
             config,
             camera_bind_group,
             camera_buffer,
             text_renderer,
+            batch_buffers,
         }
     }
 
@@ -336,11 +391,84 @@ impl GraphicsContext {
         self.config.format
     }
 
-    pub(crate) fn get_surface_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
-        self.surface.get_current_texture()
+    /// Gets the texture that should be drawn to this frame. The [wgpu::SurfaceTexture]
+    /// is only returned when there is a surface that needs to be presented.
+    pub(crate) fn get_current_frame(
+        &self,
+    ) -> Result<(wgpu::Texture, Option<wgpu::SurfaceTexture>), wgpu::SurfaceError> {
+        match &self.target {
+            RenderTarget::Surface(surface) => {
+                let surface_texture = surface.get_current_texture()?;
+                Ok((surface_texture.texture.clone(), Some(surface_texture)))
+            }
+            RenderTarget::Headless(texture) => Ok((texture.clone(), None)),
+        }
+    }
+
+    /// Applies any changes made to `config`, this either reconfigures the surface
+    /// or recreates the offscreen texture when running headless
+    pub(crate) fn reconfigure(&mut self) {
+        match &mut self.target {
+            RenderTarget::Surface(surface) => surface.configure(&self.wgpu.device, &self.config),
+            RenderTarget::Headless(texture) => {
+                if texture.width() != self.config.width || texture.height() != self.config.height {
+                    *texture = create_headless_texture(&self.wgpu, &self.config);
+                }
+            }
+        }
     }
 }
 
+// the offscreen target needs to be copyable so frames can be read back
+const HEADLESS_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::RENDER_ATTACHMENT
+    .union(wgpu::TextureUsages::TEXTURE_BINDING)
+    .union(wgpu::TextureUsages::COPY_SRC);
+
+fn create_instance() -> wgpu::Instance {
+    wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        backend_options: wgpu::BackendOptions {
+            gl: wgpu::GlBackendOptions { gles_minor_version: wgpu::Gles3MinorVersion::Automatic, fence_behavior: wgpu::GlFenceBehavior::Normal },
+            dx12: wgpu::Dx12BackendOptions { shader_compiler: wgpu::Dx12Compiler::Fxc },
+            noop: wgpu::NoopBackendOptions { enable: (false) }
+        },
+        flags: wgpu::InstanceFlags::default(),
+    })
+}
+
+fn request_device(adapter: &wgpu::Adapter) -> Result<WgpuClump, wgpu::RequestDeviceError> {
+    let limits = adapter.limits();
+
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            required_features: wgpu::Features::empty(),
+            required_limits: limits,
+            label: None,
+            memory_hints: wgpu::MemoryHints::Performance,
+            trace: wgpu::Trace::Off, //TODO: allow users to pass through directory for trace
+        },
+    ))?;
+
+    Ok(WgpuClump { device, queue })
+}
+
+fn create_headless_texture(wgpu: &WgpuClump, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+    wgpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Headless Render Target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+    })
+}
+
 pub(crate) struct WindowOptions {
     attributes: WindowAttributes,
     presentation: wgpu::PresentMode,
@@ -375,7 +503,8 @@ pub(crate) struct Intermediate {
     surface: wgpu::Surface<'static>,
     window: Arc<Window>,
     size: Vec2<u32>,
-    instance: wgpu::Instance,
+    // kept around so the instance lives as long as the surface
+    _instance: wgpu::Instance,
     presentation: wgpu::PresentMode,
 }
 
@@ -3,109 +3,200 @@
 //! builder lets you customize the engine at the start, and the
 //! Engine gives you access to all the crucial logic functions
 
+use encase::private::WriteInto;
+use encase::ShaderType;
 use glyphon::{Attrs, Metrics, Shaping};
 
 use image::ImageError;
 use spin_sleep::SpinSleeper;
-use std::num::NonZeroU64;
-use std::path::Path;
+use std::num::{NonZeroU16, NonZeroU64};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
 use web_time::Instant;
 use wgpu::{CreateSurfaceError, RequestDeviceError};
 use winit::application::ApplicationHandler;
 use winit::error::OsError;
 use winit::event::*;
 use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
-use winit::window::BadIcon;
+use winit::window::{BadIcon, Window};
 
 #[cfg(target_arch = "wasm32")]
 use winit::platform::web::EventLoopExtWebSys;
 
+use crate::action::InputMap;
+use crate::archive::AssetArchive;
 use crate::context::{GraphicsContext, Intermediate, WindowOptions};
-use crate::input::{InputHandle, Key, ModifierKeys, MouseKey};
+use crate::gamepad::{
+    GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, GamepadHandle, GamepadId, NoGamepads,
+};
+use crate::input::{InputHandle, Key, ModifierKeys, MouseKey, Touch};
+use crate::post_processing::{letterbox, PostEffect, PostEffectError, PostProcessing};
 use crate::render::render;
+use crate::replay::{InputFrame, InputRecording, ReplayState};
 use crate::resource;
 use crate::resource::{
-    InProgressResource, Loader, LoadingOp, Resource, ResourceError, ResourceId, ResourceManager,
-    ResourceType,
+    DroppedHandle, EngineResource, HandleCounts, InProgressResource, LoadError, LoadEvent,
+    LoadStatus, Loader, LoadingOp, LoadingProgress, Resource, ResourceError, ResourceHandle,
+    ResourceId, ResourceManager, ResourceType,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::{ReloadKind, ReloadedResource};
 use crate::shader::{FinalShaderOptions, IntermediateOptions, Shader};
+use crate::sound::{AudioBackend, AudioHandle, Channel, Music, Sound, SoundError};
+use crate::sprite::SpriteSheet;
 use crate::text::Font;
-use crate::texture::{SamplerType, Texture};
+use crate::texture::{Texture, TextureOptions};
+use crate::tilemap::Tilemap;
 use crate::vectors::Vec2;
 use crate::Game;
 
+// the most fixed updates that can happen in one frame
+const MAX_FIXED_UPDATES: u32 = 8;
+
 /// The thing that makes the computer go
 pub struct Engine {
     event_loop: Option<EventLoop<BpEvent>>,
     input_handle: InputHandle,
+    gamepad_handle: GamepadHandle,
+    input_map: InputMap,
     window_options: Option<WindowOptions>,
-    proxy: EventLoopProxy<BpEvent>,
+    proxy: BpProxy,
+    // only exists when headless as there is no event loop to recive events
+    headless_events: Option<Receiver<BpEvent>>,
     cursor_visibility: bool,
     should_close: bool,
+    screenshot_request: Option<PathBuf>,
+    replay: ReplayState,
     close_key: Option<Key>,
     target_fps: Option<u16>,
+    // seconds between each call to Game::fixed_update
+    fixed_timestep: Option<f32>,
+    // time that has passed but not yet been simulated by Game::fixed_update
+    fixed_accumulator: f32,
     last_frame: Instant,
     spin_sleeper: SpinSleeper,
     current_frametime: Instant,
     size: Vec2<u32>,
     pub(crate) resource_manager: ResourceManager,
     pub(crate) loader: Loader,
+    audio: AudioHandle,
     pub(crate) defualt_resources: DefualtResources,
     ma_frame_time: f32,
     pub(crate) context: Option<GraphicsContext>,
+    pub(crate) post_processing: PostProcessing,
+    // resources that finished since the game was last told about them
+    load_events: Vec<LoadEvent>,
+    // handles send their resource here once the last clone is dropped
+    handle_sender: Sender<DroppedHandle>,
+    dropped_handles: Receiver<DroppedHandle>,
+    handle_counts: HandleCounts,
 }
 
 impl Engine {
-    fn new(builder: EngineBuilder) -> Result<Self, BuildError> {
+    fn new(mut builder: EngineBuilder) -> Result<Self, BuildError> {
         cfg_if::cfg_if! {
             if #[cfg(target_arch = "wasm32")] {
                 std::panic::set_hook(Box::new(console_error_panic_hook::hook));
                 console_log::init_with_level(log::Level::Info).expect("Couldn't initialize logger");
             } else {
-                env_logger::init();
+                // multiple engines can be made in one process when running headless
+                let _ = env_logger::try_init();
+            }
+        }
+
+        // a virtual resolution with no pixels cant be scaled up to the window
+        if let Some(size) = builder.virtual_resolution {
+            if size.x == 0 || size.y == 0 {
+                return Err(BuildError::ZeroVirtualResolution);
             }
         }
 
         let cursor_visibility = true;
-        let input_handle = InputHandle::new();
+        let mut input_handle = InputHandle::new();
+        input_handle.set_touch_mouse_emulation(builder.touch_mouse_emulation);
         let size: Vec2<u32> = builder.resolution.into();
         let target_fps = builder.target_fps;
+        let fixed_timestep = builder
+            .fixed_update_rate
+            .map(|rate| 1.0 / rate.get() as f32);
+        let virtual_resolution = builder.virtual_resolution;
+        let headless = builder.headless;
+        let hot_reloading = builder.hot_reloading;
+        let input_map = builder.input_map.take().unwrap_or_default();
+        let gamepad_backend = builder
+            .gamepad_backend
+            .take()
+            .unwrap_or_else(GamepadHandle::defualt_backend);
+        let audio_backend = builder
+            .audio_backend
+            .take()
+            .unwrap_or_else(|| AudioHandle::defualt_backend(headless));
+
+        let (event_loop, proxy, headless_events) = if headless {
+            let (sender, receiver) = mpsc::channel();
+            (None, BpProxy::Channel(sender), Some(receiver))
+        } else {
+            let event_loop: EventLoop<BpEvent> = EventLoop::with_user_event().build().unwrap();
+            let proxy = BpProxy::EventLoop(event_loop.create_proxy());
+            (Some(event_loop), proxy, None)
+        };
 
-        let event_loop: EventLoop<BpEvent> = EventLoop::with_user_event().build().unwrap();
-        let proxy = event_loop.create_proxy();
-
-        let resource_manager = ResourceManager::new();
+        let mut resource_manager = ResourceManager::new();
+        let (handle_sender, dropped_handles) = mpsc::channel();
 
         let close_key = builder.close_key;
 
         let line_id = resource::generate_id::<Shader>();
         let generic_id = resource::generate_id::<Shader>();
+        let instanced_id = resource::generate_id::<Shader>();
         let white_pixel_id = resource::generate_id::<Texture>();
 
         let defualt_resources = DefualtResources {
             default_pipeline_id: generic_id,
             defualt_texture_id: white_pixel_id,
             line_pipeline_id: line_id,
+            instanced_pipeline_id: instanced_id,
+        };
+
+        let (context, window_options) = if headless {
+            let context =
+                GraphicsContext::headless(size, &mut resource_manager, &defualt_resources)?;
+            (Some(context), None)
+        } else {
+            (None, Some(builder.into()))
         };
 
         Ok(Self {
             input_handle,
-            event_loop: Some(event_loop),
-            window_options: Some(builder.into()),
+            gamepad_handle: GamepadHandle::new(gamepad_backend),
+            input_map,
+            event_loop,
+            window_options,
             proxy,
+            headless_events,
             cursor_visibility,
             should_close: false,
+            screenshot_request: None,
+            replay: ReplayState::Idle,
             close_key,
             target_fps,
+            fixed_timestep,
+            fixed_accumulator: 0.0,
             last_frame: Instant::now(),
             current_frametime: Instant::now(),
             spin_sleeper: SpinSleeper::default(),
             size,
-            context: None,
+            context,
             resource_manager,
-            loader: Loader::new(),
+            loader: Loader::new(hot_reloading),
+            audio: AudioHandle::new(audio_backend),
             defualt_resources,
             ma_frame_time: 0.0,
+            post_processing: PostProcessing::new(virtual_resolution),
+            load_events: Vec::new(),
+            handle_sender,
+            dropped_handles,
+            handle_counts: HandleCounts::new(),
         })
     }
 
@@ -161,47 +252,199 @@ impl Engine {
         self.input_handle.is_mouse_key_released(key)
     }
 
-    /// Gives the current position of the mouse in physical pixels
+    /// Gives the current position of the mouse in physical pixels. When using
+    /// [EngineBuilder::with_virtual_resolution] this is in virtual pixels instead and
+    /// can be outside of the virtual resolution when the mouse is over the black bars.
     pub fn get_mouse_position(&self) -> Vec2<f32> {
-        self.input_handle.get_mouse_position()
+        self.window_to_virtual(self.input_handle.get_mouse_position())
     }
 
-    /// Returns how much the mouse has moved in the last frame
+    // moves a position on the window into the virtual resolution if there is one
+    fn window_to_virtual(&self, position: Vec2<f32>) -> Vec2<f32> {
+        match self.virtual_letterbox() {
+            Some((offset, scale)) => (position - offset) / scale,
+            None => position,
+        }
+    }
+
+    // None when there is no virtual resolution or the window is minimised
+    fn virtual_letterbox(&self) -> Option<(Vec2<f32>, f32)> {
+        let virtual_size = self.post_processing.get_virtual_resolution()?;
+        Some(letterbox(self.size, virtual_size)).filter(|(_, scale)| *scale > 0.0)
+    }
+
+    fn touch_to_virtual(&self, touch: &Touch) -> Touch {
+        Touch {
+            position: self.window_to_virtual(touch.position),
+            start_position: self.window_to_virtual(touch.start_position),
+            ..*touch
+        }
+    }
+
+    /// Returns how much the mouse has moved in the last frame, in virtual
+    /// pixels when using [EngineBuilder::with_virtual_resolution]
     pub fn get_mouse_delta(&self) -> Vec2<f32> {
-        self.input_handle.get_mouse_delta()
+        let delta = self.input_handle.get_mouse_delta();
+        match self.virtual_letterbox() {
+            Some((_, scale)) => delta / scale,
+            None => delta,
+        }
+    }
+
+    /// Returns how far the mouse wheel or trackpad has scrolled in the last frame
+    /// measured in lines. `y` is positive when scrolling up and `x` is positive
+    /// when scrolling left, trackpads that report pixels are converted to lines.
+    pub fn get_mouse_wheel_delta(&self) -> Vec2<f32> {
+        self.input_handle.get_wheel_delta()
+    }
+
+    /// Checks if any input bound to the action is down
+    pub fn is_action_down(&self, action: &str) -> bool {
+        self.input_map
+            .is_down(action, &self.input_handle, &self.gamepad_handle, false)
+    }
+
+    // uses the input from last frame
+    fn was_action_down(&self, action: &str) -> bool {
+        self.input_map
+            .is_down(action, &self.input_handle, &self.gamepad_handle, true)
+    }
+
+    /// Checks if every input bound to the action is up
+    pub fn is_action_up(&self, action: &str) -> bool {
+        !self.is_action_down(action)
+    }
+
+    /// Only returns true on the frame where the action starts being down
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        !self.was_action_down(action) && self.is_action_down(action)
+    }
+
+    /// Returns true on the frame where the action stops being down
+    pub fn is_action_released(&self, action: &str) -> bool {
+        self.was_action_down(action) && !self.is_action_down(action)
+    }
+
+    /// Adds together every input bound to the action multiplied by
+    /// their scales, the result is between -1.0 and 1.0
+    pub fn action_axis(&self, action: &str) -> f32 {
+        self.input_map
+            .get_axis(action, &self.input_handle, &self.gamepad_handle)
+    }
+
+    /// Gets the actions and their bindings
+    pub fn get_input_map(&self) -> &InputMap {
+        &self.input_map
+    }
+
+    /// Gets the actions and their bindings so they can be changed
+    pub fn get_input_map_mut(&mut self) -> &mut InputMap {
+        &mut self.input_map
+    }
+
+    /// Replaces all the actions, usefull for loading the players saved controls
+    pub fn set_input_map(&mut self, input_map: InputMap) {
+        self.input_map = input_map;
+    }
+
+    /// Gets every finger currently on the screen as well as any
+    /// that were lifted this frame
+    pub fn get_touches(&self) -> Vec<Touch> {
+        self.input_handle
+            .get_touches()
+            .iter()
+            .map(|touch| self.touch_to_virtual(touch))
+            .collect()
+    }
+
+    /// Gets a touch by its id if the finger is still on the screen
+    pub fn get_touch(&self, id: u64) -> Option<Touch> {
+        self.input_handle
+            .get_touch(id)
+            .map(|touch| self.touch_to_virtual(touch))
+    }
+
+    /// When enabled the first finger on the screen acts like the mouse
+    /// holding down [MouseKey::Left]. This lets games made for the mouse
+    /// work on touch screens without any changes.
+    pub fn set_touch_mouse_emulation(&mut self, emulate: bool) {
+        self.input_handle.set_touch_mouse_emulation(emulate);
+    }
+
+    /// Checks if a gamepad button is down
+    pub fn is_gamepad_button_down(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepad_handle.is_button_down(gamepad, button)
+    }
+
+    /// Checks if a gamepad button is up, buttons on disconnected gamepads are always up
+    pub fn is_gamepad_button_up(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepad_handle.is_button_up(gamepad, button)
+    }
+
+    /// Only returns true on the frame where the button is first pressed
+    pub fn is_gamepad_button_pressed(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepad_handle.is_button_pressed(gamepad, button)
+    }
+
+    /// Returns true on the frame where the button is released
+    pub fn is_gamepad_button_released(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepad_handle.is_button_released(gamepad, button)
+    }
+
+    /// Gets the position of a stick or trigger with the deadzone applied
+    pub fn get_gamepad_axis(&self, gamepad: GamepadId, axis: GamepadAxis) -> f32 {
+        self.gamepad_handle.get_axis(gamepad, axis)
+    }
+
+    /// Gets the ids of all connected gamepads in order
+    pub fn get_connected_gamepads(&self) -> Vec<GamepadId> {
+        self.gamepad_handle.get_connected()
+    }
+
+    /// Checks if a gamepad is still connected
+    pub fn is_gamepad_connected(&self, gamepad: GamepadId) -> bool {
+        self.gamepad_handle.is_connected(gamepad)
+    }
+
+    /// Returns all the gamepad events that happened since the last frame, this is
+    /// how to tell when gamepads are connected and disconnected
+    pub fn get_gamepad_events(&self) -> &[GamepadEvent] {
+        self.gamepad_handle.get_events()
+    }
+
+    /// Sets how far a stick or trigger has to move before it registers, from 0.0 to 1.0.
+    /// Values inside of the deadzone read as 0.0 and everything outside is rescaled
+    /// to still reach 1.0. The defualt is 0.15
+    pub fn set_gamepad_deadzone(&mut self, deadzone: f32) {
+        self.gamepad_handle.set_deadzone(deadzone);
     }
 
-    /// Checks if the window has focus
+    /// Gets the current deadzone for sticks and triggers
+    pub fn get_gamepad_deadzone(&self) -> f32 {
+        self.gamepad_handle.get_deadzone()
+    }
+
+    /// Checks if the window has focus. A headless engine is always considered focused.
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn window_has_focus(&self) -> bool {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.has_focus()
+        self.window().map(|w| w.has_focus()).unwrap_or(true)
     }
 
     /// Checks if the window is maximized not fullscreened
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn is_window_maximized(&self) -> bool {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.is_maximized()
+        self.window().map(|w| w.is_maximized()).unwrap_or(false)
     }
 
     /// Checks to see if the window is minimized
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn is_window_minimized(&self) -> bool {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.is_minimized().unwrap_or(false)
+        self.window()
+            .and_then(|w| w.is_minimized())
+            .unwrap_or(false)
     }
 
     /// Checks to see if the window is fullscreen not maximized
@@ -209,33 +452,27 @@ impl Engine {
     /// When called outside of the functions in the [Game] trait
     pub fn is_window_fullscreen(&self) -> bool {
         // based on limited docs knowledge this should work
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.fullscreen().is_some()
+        self.window()
+            .map(|w| w.fullscreen().is_some())
+            .unwrap_or(false)
     }
 
-    /// Will maximize the window
+    /// Will maximize the window, does nothing when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn maximize_window(&self) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.set_maximized(true);
+        if let Some(window) = self.window() {
+            window.set_maximized(true);
+        }
     }
 
-    /// Will minimize the window
+    /// Will minimize the window, does nothing when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn minimize_window(&self) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.set_minimized(true);
+        if let Some(window) = self.window() {
+            window.set_minimized(true);
+        }
     }
 
     /// Will close the window and stop the program
@@ -253,69 +490,51 @@ impl Engine {
         let image_bytes = image.into_raw();
         let icon = winit::window::Icon::from_rgba(image_bytes, width, height)?;
 
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-
-        context.window.set_window_icon(Some(icon));
+        if let Some(window) = self.window() {
+            window.set_window_icon(Some(icon));
+        }
         Ok(())
     }
 
-    /// Sets the window title
+    /// Sets the window title, does nothing when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn set_window_title(&self, title: &str) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.set_title(title);
+        if let Some(window) = self.window() {
+            window.set_title(title);
+        }
     }
 
-    /// Changes the Position of the window in Physical Pixles
+    /// Changes the Position of the window in Physical Pixles, does nothing when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn set_window_position(&self, x: f32, y: f32) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context
-            .window
-            .set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+        if let Some(window) = self.window() {
+            window.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+        }
     }
 
-    /// Sets the physical minimum size of the window
+    /// Sets the physical minimum size of the window, does nothing when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn set_window_min_size(&self, width: f32, height: f32) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context
-            .window
-            .set_min_inner_size(Some(winit::dpi::PhysicalSize::new(width, height)));
+        if let Some(window) = self.window() {
+            window.set_min_inner_size(Some(winit::dpi::PhysicalSize::new(width, height)));
+        }
     }
 
+    /// Requests a new physical size for the window, does nothing when headless
     pub fn set_window_size(&self, width: f32, height: f32) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        let _ = context.window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+        if let Some(window) = self.window() {
+            let _ = window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+        }
     }
 
-    /// Gets the physical postion of the window
+    /// Gets the physical postion of the window, this is always `None` when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn get_window_position(&self) -> Option<Vec2<i32>> {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        match context.window.outer_position() {
+        match self.window()?.outer_position() {
             Ok(v) => Some((v.x, v.y).into()),
             Err(_) => None,
         }
@@ -326,15 +545,25 @@ impl Engine {
         self.size
     }
 
-    /// Gets the scale factor to help handle diffrence between phyiscial and logical pixels
+    /// Gets the virtual resolution set with [EngineBuilder::with_virtual_resolution]
+    pub fn get_virtual_resolution(&self) -> Option<Vec2<u32>> {
+        self.post_processing.get_virtual_resolution()
+    }
+
+    /// Gets the size of what [Game::render] draws onto, this is the virtual resolution
+    /// when there is one and otherwise the window size
+    pub fn get_render_size(&self) -> Vec2<u32> {
+        self.post_processing
+            .get_virtual_resolution()
+            .unwrap_or(self.size)
+    }
+
+    /// Gets the scale factor to help handle diffrence between phyiscial and logical pixels.
+    /// This is always `1.0` when headless
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn get_window_scale_factor(&self) -> f64 {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.scale_factor()
+        self.window().map(|w| w.scale_factor()).unwrap_or(1.0)
     }
 
     /// Toggels fullscreen mode may fail on certain Operating Systems
@@ -343,16 +572,14 @@ impl Engine {
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn toggle_fullscreen(&self) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
+        let Some(window) = self.window() else {
+            return;
+        };
+
         if self.is_window_fullscreen() {
-            context
-                .window
-                .set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
+            window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
         } else {
-            context.window.set_fullscreen(None);
+            window.set_fullscreen(None);
         }
     }
 
@@ -360,11 +587,9 @@ impl Engine {
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn hide_cursor(&mut self) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.set_cursor_visible(false);
+        if let Some(window) = self.window() {
+            window.set_cursor_visible(false);
+        }
         self.cursor_visibility = false;
     }
 
@@ -372,16 +597,31 @@ impl Engine {
     /// # Panics
     /// When called outside of the functions in the [Game] trait
     pub fn show_cursor(&mut self) {
-        let context = self
-            .context
-            .as_ref()
-            .expect("Context hasnt been created yet run inside impl Game");
-        context.window.set_cursor_visible(true);
+        if let Some(window) = self.window() {
+            window.set_cursor_visible(true);
+        }
         self.cursor_visibility = true;
     }
 
     /// Gets the time since the previous frame or change in time between now and last frame
+    /// While input is recorded or played back this is the delta time saved in the
+    /// [InputRecording] so both runs see the same value
     pub fn get_frame_delta_time(&self) -> f32 {
+        let frame = match &self.replay {
+            ReplayState::Idle => None,
+            ReplayState::Recording(recording) => recording.frames.last(),
+            // next_frame has already moved past the frame being replayed
+            ReplayState::Replaying {
+                recording,
+                next_frame,
+                ..
+            } => recording.frames.get(next_frame.wrapping_sub(1)),
+        };
+
+        if let Some(frame) = frame {
+            return frame.delta_time;
+        }
+
         Instant::now().duration_since(self.last_frame).as_secs_f32()
     }
 
@@ -413,9 +653,7 @@ impl Engine {
             .as_mut()
             .expect("Context hasnt been created yet run inside impl Game");
         context.config.present_mode = wgpu::PresentMode::AutoNoVsync;
-        context
-            .surface
-            .configure(&context.wgpu.device, &context.config);
+        context.reconfigure();
     }
 
     /// Will turn off vysnc if the platform suports it, using
@@ -429,9 +667,7 @@ impl Engine {
             .as_mut()
             .expect("Context hasnt been created yet run inside impl Game");
         context.config.present_mode = wgpu::PresentMode::AutoVsync;
-        context
-            .surface
-            .configure(&context.wgpu.device, &context.config);
+        context.reconfigure();
     }
 
     /// Sets a target fps cap. The thread will spin sleep using the
@@ -442,6 +678,35 @@ impl Engine {
         self.target_fps = Some(fps);
     }
 
+    /// Sets how many times per second [Game::fixed_update] is called, see
+    /// [EngineBuilder::set_fixed_update_rate] for more details
+    pub fn set_fixed_update_rate(&mut self, updates_per_second: NonZeroU16) {
+        self.fixed_timestep = Some(1.0 / updates_per_second.get() as f32);
+    }
+
+    /// Stops calling [Game::fixed_update] and throws away any time that was not simulated
+    pub fn remove_fixed_update_rate(&mut self) {
+        self.fixed_timestep = None;
+        self.fixed_accumulator = 0.0;
+    }
+
+    /// Gets the time in seconds that each call to [Game::fixed_update] simulates,
+    /// returns None when there is no fixed update rate
+    pub fn get_fixed_timestep(&self) -> Option<f32> {
+        self.fixed_timestep
+    }
+
+    /// Gets how far between the last and next [Game::fixed_update] the current frame is,
+    /// from 0.0 to 1.0. This is used to smoothly draw things that move in fixed updates
+    /// by blending their previous and current positions. Without a fixed update rate this
+    /// is always 0.0
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        match self.fixed_timestep {
+            Some(step) => self.fixed_accumulator / step,
+            None => 0.0,
+        }
+    }
+
     /// Measures string based on the default font. To measure a string with a custom font
     /// use [TextMaterial::get_measurements()](../text/struct.TextMaterial.html#method.get_measurements)
     /// # Panics
@@ -501,16 +766,81 @@ impl Engine {
         let path = path.as_ref();
         let ip_resource = InProgressResource::new(path, id, ResourceType::Bytes, loading_op);
 
-        self.loader.blocking_load(ip_resource, self.proxy.clone());
+        self.loader.blocking_load(ip_resource, self.get_proxy());
         typed_id
     }
 
+    /// Mounts an [AssetArchive] so resources are looked for inside of it before the
+    /// file system. Archives mounted later are checked first which lets them override
+    /// files in earlier ones.
+    pub fn mount_archive(&mut self, archive: AssetArchive) {
+        self.loader.get_vfs().mount(archive);
+    }
+
+    /// Mounts an [AssetArchive] from a file, see [Engine::mount_archive]. On native this
+    /// reads the archive right away. On wasm the archive is downloaded in the background and
+    /// anything that loads before the download finishes waits for it. Errors are logged and
+    /// the archive is skipped.
+    pub fn mount_archive_file<P: AsRef<Path>>(&mut self, path: P) {
+        self.loader.get_vfs().mount_file(path.as_ref());
+    }
+
+    /// Removes all mounted archives so every resource is loaded from the file system again
+    pub fn unmount_archives(&mut self) {
+        self.loader.get_vfs().unmount_all();
+    }
+
     /// This returns the current number of resources that
     /// are loading
     pub fn get_loading_resource_count(&self) -> usize {
         self.loader.get_loading_resources()
     }
 
+    /// Frees a resource, for textures and shaders this frees the memory on the GPU as well.
+    /// Anything still using the resource acts like it hasnt loaded yet, so materials
+    /// using an unloaded texture will stop drawing. Resources that are still loading
+    /// are thrown away once they finish. The engine's own defualt resources are never
+    /// unloaded, since every material falls back on them.
+    pub fn unload<T: EngineResource>(&mut self, id: ResourceId<T>) {
+        if self.defualt_resources.contains(id.get_id()) {
+            log::warn!("tried to unload one of the engine's defualt resources, ignoring it");
+            return;
+        }
+
+        self.loader.forget(id.get_id());
+        T::remove(self, id);
+    }
+
+    /// Makes a reference counted handle for a resource which unloads the resource once
+    /// every handle for it has been dropped, see [ResourceHandle]
+    pub fn create_handle<T: EngineResource>(&mut self, id: ResourceId<T>) -> ResourceHandle<T> {
+        ResourceHandle::new(id, &self.handle_sender, &mut self.handle_counts)
+    }
+
+    /// Gets how far along a resource is in loading. Returns `None` for resources that
+    /// were not loaded from a file.
+    /// ```rust,no_run
+    /// # use bottomless_pit::engine_handle::Engine;
+    /// # use bottomless_pit::resource::{LoadStatus, LoadingOp};
+    /// # use bottomless_pit::texture::Texture;
+    /// # fn update(engine: &mut Engine) {
+    /// let texture = Texture::new(engine, "player.png", LoadingOp::Background);
+    /// match engine.get_load_status(texture) {
+    ///     Some(LoadStatus::Failed(e)) => println!("the player is missing becuase {}", e),
+    ///     _ => {}
+    /// }
+    /// # }
+    /// ```
+    pub fn get_load_status<T>(&self, id: ResourceId<T>) -> Option<&LoadStatus> {
+        self.loader.get_status(id.get_id())
+    }
+
+    /// Gets how much of the current round of loading is done, great for drawing a loading bar.
+    /// See [LoadingProgress] for when a round of loading starts.
+    pub fn get_loading_progress(&self) -> LoadingProgress {
+        self.loader.get_progress()
+    }
+
     /// Attemps to fetch a byte resource.
     ///
     /// Returns `None` if the resource isnt loaded yet.
@@ -518,12 +848,30 @@ impl Engine {
         self.resource_manager.get_byte_resource(&id)
     }
 
-    pub(crate) fn get_proxy(&self) -> EventLoopProxy<BpEvent> {
+    /// Attemps to fetch a sprite sheet.
+    ///
+    /// Returns `None` if the resource isnt loaded yet.
+    pub fn get_sprite_sheet(&self, id: ResourceId<SpriteSheet>) -> Option<&SpriteSheet> {
+        self.resource_manager.get_sprite_sheet(&id)
+    }
+
+    /// Attemps to fetch a tilemap.
+    ///
+    /// Returns `None` if the resource isnt loaded yet.
+    pub fn get_tilemap(&self, id: ResourceId<Tilemap>) -> Option<&Tilemap> {
+        self.resource_manager.get_tilemap(&id)
+    }
+
+    pub(crate) fn get_proxy(&self) -> BpProxy {
         self.proxy.clone()
     }
 
-    pub(crate) fn get_resources(&self) -> &ResourceManager {
-        &self.resource_manager
+    fn window(&self) -> Option<&Window> {
+        self.context
+            .as_ref()
+            .expect("Context hasnt been created yet run inside impl Game")
+            .window
+            .as_deref()
     }
 
     pub(crate) fn defualt_material_bg_id(&self) -> ResourceId<Texture> {
@@ -538,11 +886,23 @@ impl Engine {
         self.defualt_resources.line_pipeline_id
     }
 
-    /// Takes the struct that implements the Game trait and starts the winit event loop running the game
-    pub fn run<T>(mut self, game: T)
+    pub(crate) fn instanced_pipe_id(&self) -> ResourceId<Shader> {
+        self.defualt_resources.instanced_pipeline_id
+    }
+
+    /// Takes the struct that implements the Game trait and starts the winit event loop running the game.
+    /// When the engine is headless this will step frames untill [Engine::close] is called.
+    pub fn run<T>(mut self, mut game: T)
     where
         T: Game + 'static,
     {
+        if self.is_headless() {
+            while !self.should_close {
+                self.step(&mut game);
+            }
+            return;
+        }
+
         let event_loop = self.event_loop.take().unwrap(); //should never panic
         #[cfg(target_arch = "wasm32")]
         {
@@ -554,7 +914,309 @@ impl Engine {
         }
     }
 
-    fn update(&mut self, elwt: &ActiveEventLoop) {
+    /// Plays a sound once, the returned [Channel] can be used to
+    /// change the volume and pan of the sound while it plays.
+    pub fn play_sound(&mut self, sound: &ResourceId<Sound>) -> Result<Channel, SoundError> {
+        let sound = self
+            .resource_manager
+            .get_sound(sound)
+            .ok_or(SoundError::NotLoadedYet)?;
+
+        Ok(self.audio.play(sound, false))
+    }
+
+    /// Plays a sound over and over untill [Engine::stop_channel] is called
+    pub fn loop_sound(&mut self, sound: &ResourceId<Sound>) -> Result<Channel, SoundError> {
+        let sound = self
+            .resource_manager
+            .get_sound(sound)
+            .ok_or(SoundError::NotLoadedYet)?;
+
+        Ok(self.audio.play(sound, true))
+    }
+
+    /// Sets the volume of a playing sound, 1.0 is the original volume
+    pub fn set_channel_volume(&mut self, channel: Channel, volume: f32) {
+        self.audio.set_volume(channel, volume);
+    }
+
+    /// Sets the pan of a playing sound from -1.0 being only the left
+    /// speaker to 1.0 being only the right speaker
+    pub fn set_channel_pan(&mut self, channel: Channel, pan: f32) {
+        self.audio.set_pan(channel, pan);
+    }
+
+    /// Stops a playing sound
+    pub fn stop_channel(&mut self, channel: Channel) {
+        self.audio.stop(channel);
+    }
+
+    /// Stops every playing sound, music is unaffected
+    pub fn stop_all_sounds(&mut self) {
+        self.audio.stop_all();
+    }
+
+    /// Checks if the sound on this channel is still playing
+    pub fn is_channel_playing(&self, channel: Channel) -> bool {
+        self.audio.is_playing(channel)
+    }
+
+    /// Starts playing a music track, replacing any music that is already playing
+    pub fn play_music(
+        &mut self,
+        music: &ResourceId<Music>,
+        looping: bool,
+    ) -> Result<(), SoundError> {
+        let music = self
+            .resource_manager
+            .get_music(music)
+            .ok_or(SoundError::NotLoadedYet)?;
+
+        self.audio.play_music(music, looping)
+    }
+
+    /// Stops the current music track
+    pub fn stop_music(&mut self) {
+        self.audio.stop_music();
+    }
+
+    /// Checks if there is a music track playing
+    pub fn is_music_playing(&self) -> bool {
+        self.audio.is_music_playing()
+    }
+
+    /// Sets the volume of the music, 1.0 is the original volume
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.audio.set_music_volume(volume);
+    }
+
+    /// Sets the volume of everything that is played
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.audio.set_master_volume(volume);
+    }
+
+    /// Gets the volume of everything that is played
+    pub fn get_master_volume(&self) -> f32 {
+        self.audio.get_master_volume()
+    }
+
+    /// Saves the next rendered frame as an image at the given path, the format is
+    /// picked from the file extension. Any problems saving the image are logged.
+    /// To get the image directly use [RenderHandle::capture](crate::render::RenderHandle::capture)
+    pub fn request_screenshot<P: AsRef<Path>>(&mut self, path: P) {
+        self.screenshot_request = Some(path.as_ref().to_path_buf());
+    }
+
+    pub(crate) fn take_screenshot_request(&mut self) -> Option<PathBuf> {
+        self.screenshot_request.take()
+    }
+
+    /// Adds a [PostEffect] to the end of the post processing chain. Every frame the
+    /// effects are ran in the order they were added after [Game::render] has finished.
+    pub fn add_post_effect(&mut self, effect: PostEffect) -> ResourceId<PostEffect> {
+        self.post_processing.add(effect)
+    }
+
+    /// Takes a [PostEffect] out of the chain, returns None if it was already removed
+    pub fn remove_post_effect(&mut self, id: &ResourceId<PostEffect>) -> Option<PostEffect> {
+        self.post_processing.remove(id)
+    }
+
+    /// Removes every [PostEffect]
+    pub fn clear_post_effects(&mut self) {
+        self.post_processing.clear();
+    }
+
+    /// Turns a [PostEffect] on or off while keeping its place in the chain
+    pub fn set_post_effect_enabled(
+        &mut self,
+        id: &ResourceId<PostEffect>,
+        enabled: bool,
+    ) -> Result<(), PostEffectError> {
+        self.post_processing.set_enabled(id, enabled)
+    }
+
+    /// Changes the uniform data of a [PostEffect] made with
+    /// [PostEffect::with_uniform_data]. The data has to be the same type it was made with.
+    pub fn update_post_effect_uniform<T: ShaderType + WriteInto>(
+        &mut self,
+        id: &ResourceId<PostEffect>,
+        data: &T,
+    ) -> Result<(), PostEffectError> {
+        let mut buffer = encase::UniformBuffer::new(Vec::new());
+        buffer.write(data).unwrap();
+
+        let wgpu = self.context.as_ref().map(|c| &c.wgpu);
+        self.post_processing
+            .update_uniform(id, buffer.into_inner(), wgpu)
+    }
+
+    /// Starts saving the input of every frame, any recording or replay
+    /// that was already going is thrown away
+    pub fn start_recording_input(&mut self) {
+        self.stop_replaying_input();
+        self.replay = ReplayState::Recording(InputRecording::default());
+    }
+
+    /// Stops recording and returns everything that was recorded,
+    /// returns None if nothing was being recorded
+    pub fn stop_recording_input(&mut self) -> Option<InputRecording> {
+        match std::mem::replace(&mut self.replay, ReplayState::Idle) {
+            ReplayState::Recording(recording) => Some(recording),
+            other => {
+                self.replay = other;
+                None
+            }
+        }
+    }
+
+    /// Checks if input is currently being recorded
+    pub fn is_recording_input(&self) -> bool {
+        matches!(self.replay, ReplayState::Recording(_))
+    }
+
+    /// Plays back an [InputRecording] starting on the next frame. Until the recording
+    /// runs out all real input is ignored and [Game::update] sees the recorded input and
+    /// frame delta times instead. Any recording that was going is thrown away.
+    pub fn play_input_recording(&mut self, recording: InputRecording) {
+        self.stop_replaying_input();
+
+        // the game sees these while the real handles keep tracking the hardware
+        let mut replay_input = InputHandle::new();
+        replay_input.set_touch_mouse_emulation(self.input_handle.is_emulating_mouse());
+        let mut replay_gamepads = GamepadHandle::new(Box::new(NoGamepads));
+        replay_gamepads.set_deadzone(self.gamepad_handle.get_deadzone());
+
+        let live_input = std::mem::replace(&mut self.input_handle, replay_input);
+        let live_gamepads = std::mem::replace(&mut self.gamepad_handle, replay_gamepads);
+
+        self.replay = ReplayState::Replaying {
+            recording,
+            next_frame: 0,
+            live_input: Box::new(live_input),
+            live_gamepads: Box::new(live_gamepads),
+        };
+    }
+
+    /// Checks if an [InputRecording] is being played back
+    pub fn is_replaying_input(&self) -> bool {
+        matches!(self.replay, ReplayState::Replaying { .. })
+    }
+
+    /// Stops playing back an [InputRecording] and gives control back to real input
+    pub fn stop_replaying_input(&mut self) {
+        if let ReplayState::Replaying {
+            mut live_input,
+            mut live_gamepads,
+            ..
+        } = std::mem::replace(&mut self.replay, ReplayState::Idle)
+        {
+            // settings changed during the replay should stick around
+            live_input.set_touch_mouse_emulation(self.input_handle.is_emulating_mouse());
+            live_gamepads.set_deadzone(self.gamepad_handle.get_deadzone());
+            self.input_handle = *live_input;
+            self.gamepad_handle = *live_gamepads;
+        }
+    }
+
+    // runs all the logic of the game for one frame
+    fn update_game<T: Game>(&mut self, game: &mut T) {
+        self.replay_frame();
+
+        if let Some(step) = self.fixed_timestep {
+            // after a long stall drop the time instead of trying to catch up all at once
+            self.fixed_accumulator = (self.fixed_accumulator + self.get_frame_delta_time())
+                .min(step * MAX_FIXED_UPDATES as f32);
+
+            while self.fixed_accumulator >= step {
+                game.fixed_update(self);
+                self.fixed_accumulator -= step;
+            }
+        }
+
+        game.update(self);
+    }
+
+    // called right before Game::update so the game sees exactly what is recorded
+    fn replay_frame(&mut self) {
+        match &mut self.replay {
+            ReplayState::Idle => {}
+            ReplayState::Recording(recording) => {
+                let delta_time = Instant::now().duration_since(self.last_frame).as_secs_f32();
+                let (gamepads, gamepad_events) = self.gamepad_handle.snapshot();
+                recording.frames.push(InputFrame {
+                    gamepads,
+                    gamepad_events,
+                    ..self.input_handle.snapshot(delta_time)
+                });
+            }
+            ReplayState::Replaying {
+                recording,
+                next_frame,
+                ..
+            } => match recording.frames.get(*next_frame) {
+                Some(frame) => {
+                    self.input_handle.restore(frame);
+                    self.gamepad_handle
+                        .restore(&frame.gamepads, &frame.gamepad_events);
+                    *next_frame += 1;
+                }
+                None => self.stop_replaying_input(),
+            },
+        }
+    }
+
+    /// Returns true when the engine was built with [EngineBuilder::headless]
+    pub fn is_headless(&self) -> bool {
+        self.headless_events.is_some()
+    }
+
+    /// Runs a single frame of a headless engine. This finishes any loaded resources, calls
+    /// [Game::update] then [Game::render] which draws into the offscreen texture.
+    /// # Panics
+    /// When the engine was not built with [EngineBuilder::headless]
+    pub fn step<T: Game>(&mut self, game: &mut T) {
+        assert!(
+            self.is_headless(),
+            "step can only be used on a headless engine, use run instead"
+        );
+
+        let events = self.headless_events.as_ref().unwrap();
+        let finished_resources = events.try_iter().collect::<Vec<BpEvent>>();
+        for event in finished_resources {
+            self.handle_event(event);
+        }
+        self.send_load_events(game);
+
+        if self.is_loading() {
+            self.update();
+        } else {
+            self.update_game(game);
+            self.update();
+            self.current_frametime = Instant::now();
+
+            if let Err(e) = render(game, self) {
+                log::error!("{:?}", e);
+            }
+        }
+    }
+
+    /// Steps a headless engine the specified number of frames or untill [Engine::close] is called.
+    /// # Panics
+    /// When the engine was not built with [EngineBuilder::headless]
+    pub fn run_frames<T: Game>(&mut self, game: &mut T, frames: u32) {
+        for _ in 0..frames {
+            if self.should_close {
+                break;
+            }
+
+            self.step(game);
+        }
+    }
+
+    fn update(&mut self) {
+        // taken before last_frame moves so replays drive the audio with recorded times
+        let frame_delta = self.get_frame_delta_time();
         self.last_frame = Instant::now();
         let dt = self
             .last_frame
@@ -562,16 +1224,41 @@ impl Engine {
             .as_secs_f32();
 
         self.ma_frame_time = (self.ma_frame_time + dt) / 2.0;
+        self.audio.update(frame_delta);
+
+        let dropped_handles = self.dropped_handles.try_iter().collect::<Vec<_>>();
+        for (id, unload) in dropped_handles {
+            // a new handle was made after the last one was dropped
+            let alive = self.handle_counts.get(&id);
+            if alive.is_some_and(|count| count.strong_count() > 0) {
+                continue;
+            }
+
+            self.handle_counts.remove(&id);
+            unload(self, id);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.loader.check_for_changes(&self.proxy);
 
         if self.should_close {
-            elwt.exit();
             return;
         }
 
         self.input_handle.end_of_frame_refresh();
+        self.gamepad_handle.end_of_frame_refresh();
+        if let ReplayState::Replaying {
+            live_input,
+            live_gamepads,
+            ..
+        } = &mut self.replay
+        {
+            live_input.end_of_frame_refresh();
+            live_gamepads.end_of_frame_refresh();
+        }
+
         if let Some(key) = self.close_key {
             if self.input_handle.is_key_down(key) {
-                elwt.exit();
+                self.should_close = true;
                 return;
             }
         }
@@ -589,6 +1276,12 @@ impl Engine {
     }
 
     fn input(&mut self, event: &WindowEvent) -> bool {
+        // real input would get mixed in with the recording so it goes to the
+        // live input which is put back once the replay is over
+        if let ReplayState::Replaying { live_input, .. } = &mut self.replay {
+            return live_input.process_input(event);
+        }
+
         self.input_handle.process_input(event)
     }
 
@@ -598,37 +1291,69 @@ impl Engine {
             .as_mut()
             .expect("Context hasnt been created yet run inside impl Game");
 
-        log::warn!("scale factor {}", context.window.scale_factor());
+        if let Some(window) = &context.window {
+            log::warn!("scale factor {}", window.scale_factor());
+        }
 
         if new_size.x > 0 && new_size.y > 0 {
             context.config.width = new_size.x;
             context.config.height = new_size.y;
-            context
-                .surface
-                .configure(&context.wgpu.device, &context.config);
+            context.reconfigure();
             self.size = new_size;
+            self.post_processing.resize(new_size, &context.wgpu);
+            // the virtual resolution never changes size
+            let render_size = self
+                .post_processing
+                .get_virtual_resolution()
+                .unwrap_or(new_size);
             context.wgpu.queue.write_buffer(
                 &context.camera_buffer,
                 48,
-                bytemuck::cast_slice(&[new_size.x as f32, new_size.y as f32]),
+                bytemuck::cast_slice(&[render_size.x as f32, render_size.y as f32]),
             );
         }
     }
 
+    fn handle_event(&mut self, event: BpEvent) {
+        match event {
+            BpEvent::ResourceLoaded(resource) => self.handle_resource(resource),
+            #[cfg(not(target_arch = "wasm32"))]
+            BpEvent::ResourceReloaded(resource) => self.handle_reload(resource),
+            BpEvent::AdapterReady(_) => {}
+        }
+    }
+
     fn handle_resource(&mut self, resource: Result<Resource, ResourceError>) {
         match resource {
             Ok(data) => {
                 self.loader.remove_item_loading(data.loading_op);
-                match data.resource_type {
+                if self.loader.get_status(data.id).is_none() {
+                    log::info!("resource at: {:?} was unloaded while loading", data.path);
+                    return;
+                }
+
+                let id = data.id;
+                let path = data.path.clone();
+                let bytes = data.data.len();
+                let result = match data.resource_type {
                     ResourceType::Bytes => self.add_finished_bytes(data.data, data.id, &data.path),
-                    ResourceType::Image(mag, min) => {
-                        self.add_finished_image(data.data, data.id, mag, min, &data.path)
+                    ResourceType::Image(options) => {
+                        self.add_finished_image(data.data, data.id, options, &data.path)
+                    }
+                    ResourceType::RawImage(size, options) => {
+                        self.add_finished_raw_image(data.data, data.id, size, options)
                     }
                     ResourceType::Shader(options) => {
                         self.add_finished_shader(data.data, data.id, options, &data.path)
                     }
                     ResourceType::Font => self.add_finished_font(data),
-                }
+                    ResourceType::Sound => self.add_finished_sound(data),
+                    ResourceType::Music => self.add_finished_music(data),
+                    ResourceType::SpriteSheet => self.add_finished_sprite_sheet(data),
+                    ResourceType::Tilemap => self.add_finished_tilemap(data),
+                };
+
+                self.finish_loading(id, path, result.map(|_| bytes));
             }
             Err(e) => {
                 log::error!(
@@ -638,49 +1363,175 @@ impl Engine {
                 );
 
                 self.loader.remove_item_loading(e.loading_op);
+                if self.loader.get_status(e.id).is_none() {
+                    return;
+                }
 
                 match e.resource_type {
                     ResourceType::Bytes => self.add_defualt_bytes(e.id),
-                    ResourceType::Image(..) => self.add_defualt_image(e.id),
+                    ResourceType::Image(..) | ResourceType::RawImage(..) => {
+                        self.add_defualt_image(e.id)
+                    }
                     ResourceType::Shader(_) => self.add_defualt_shader(e.id),
                     ResourceType::Font => self.add_defualt_font(e.id),
+                    ResourceType::Sound => self.add_defualt_sound(e.id),
+                    ResourceType::Music => self.add_defualt_music(e.id),
+                    ResourceType::SpriteSheet => self.add_defualt_sprite_sheet(e.id),
+                    ResourceType::Tilemap => self.add_defualt_tilemap(e.id),
                 }
+
+                let error = LoadError::ReadError(e.error.to_string());
+                self.finish_loading(e.id, e.path, Err(error));
             }
         }
     }
 
-    fn add_finished_bytes(&mut self, data: Vec<u8>, id: NonZeroU64, path: &Path) {
+    fn finish_loading(&mut self, id: NonZeroU64, path: PathBuf, result: Result<usize, LoadError>) {
+        let error = result.as_ref().err().cloned();
+        self.loader.finish_loading(id, result);
+        self.load_events.push(LoadEvent::new(id, path, error));
+    }
+
+    // gives the game every load event since the last time this was called
+    fn send_load_events<T: Game>(&mut self, game: &mut T) {
+        for event in std::mem::take(&mut self.load_events) {
+            game.on_resource_loaded(event, self);
+        }
+    }
+
+    // unlike a normal load anything going wrong keeps the old version around
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn handle_reload(&mut self, resource: ReloadedResource) {
+        // unloaded while it was being read
+        if self.loader.get_status(resource.id).is_none() {
+            return;
+        }
+
+        let data = match resource.data {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!(
+                    "could not reload resource at: {:?}, becuase: {:?}, keeping the old version",
+                    resource.path,
+                    e
+                );
+                return;
+            }
+        };
+
+        match resource.kind {
+            ReloadKind::Image(options) => {
+                let typed_id: ResourceId<Texture> = ResourceId::from_number(resource.id);
+                match Texture::from_resource_data(self, None, data, options) {
+                    Ok(texture) => {
+                        self.resource_manager.insert_texture(typed_id, texture);
+                        self.post_processing.texture_changed(&typed_id);
+                        log::info!(
+                            "texture resource at: {:?} reloaded succesfully",
+                            resource.path
+                        );
+                    }
+                    Err(e) => log::error!("{:?}, keeping the old version", e),
+                }
+            }
+            ReloadKind::Shader => {
+                let typed_id: ResourceId<Shader> = ResourceId::from_number(resource.id);
+                let context = self.context.as_ref().unwrap();
+                let Some(shader) = self.resource_manager.get_mut_shader(&typed_id) else {
+                    return;
+                };
+
+                match shader.reload(&data, &context.wgpu) {
+                    Ok(()) => log::info!(
+                        "shader resource at: {:?} reloaded succesfully",
+                        resource.path
+                    ),
+                    Err(e) => log::error!(
+                        "could not reload shader at: {:?}. {}, keeping the old version",
+                        resource.path,
+                        e
+                    ),
+                }
+            }
+            ReloadKind::Font => {
+                let typed_id: ResourceId<Font> = ResourceId::from_number(resource.id);
+                let context = self.context.as_mut().unwrap();
+                let font = match context.text_renderer.load_font_from_bytes(&data) {
+                    Ok(font) => font,
+                    Err(e) => {
+                        log::error!(
+                            "could not reload font at: {:?}. {}, keeping the old version",
+                            resource.path,
+                            e
+                        );
+                        return;
+                    }
+                };
+
+                if let Some(old_font) = self.resource_manager.insert_font(typed_id, font) {
+                    context.text_renderer.unload_font(&old_font);
+                }
+                log::info!("Font resource at: {:?} reloaded succesfully", resource.path);
+            }
+        }
+    }
+
+    fn add_finished_bytes(
+        &mut self,
+        data: Vec<u8>,
+        id: NonZeroU64,
+        path: &Path,
+    ) -> Result<(), LoadError> {
         let typed_id: ResourceId<Vec<u8>> = ResourceId::from_number(id);
         self.resource_manager.insert_bytes(typed_id, data);
         log::info!("byte resource at: {:?} loaded succesfully", path);
+        Ok(())
     }
 
     fn add_finished_image(
         &mut self,
         data: Vec<u8>,
         id: NonZeroU64,
-        mag: SamplerType,
-        min: SamplerType,
+        options: TextureOptions,
         path: &Path,
-    ) {
+    ) -> Result<(), LoadError> {
         let typed_id: ResourceId<Texture> = ResourceId::from_number(id);
-        let texture = Texture::from_resource_data(self, None, data, mag, min);
+        let texture = Texture::from_resource_data(self, None, data, options);
         match texture {
             Ok(texture) => {
                 self.resource_manager.insert_texture(typed_id, texture);
                 log::info!("texture resource at: {:?} loaded succesfully", path);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("{:?}, loading defualt replacement", e);
+                self.add_defualt_image(id);
+                Err(LoadError::InvalidData(format!("{:?}", e)))
             }
-            Err(e) => log::error!("{:?}, loading defualt replacement", e),
         }
     }
 
+    fn add_finished_raw_image(
+        &mut self,
+        data: Vec<u8>,
+        id: NonZeroU64,
+        size: Vec2<u32>,
+        options: TextureOptions,
+    ) -> Result<(), LoadError> {
+        let typed_id: ResourceId<Texture> = ResourceId::from_number(id);
+        let texture = Texture::from_rgba_data(self, &data, size, None, options);
+        self.resource_manager.insert_texture(typed_id, texture);
+        log::info!("texture resource from memory loaded succesfully");
+        Ok(())
+    }
+
     fn add_finished_shader(
         &mut self,
         data: Vec<u8>,
         id: NonZeroU64,
         options: IntermediateOptions,
         path: &Path,
-    ) {
+    ) -> Result<(), LoadError> {
         let final_option =
             FinalShaderOptions::from_intermediate(options, self.context.as_ref().unwrap());
 
@@ -690,22 +1541,103 @@ impl Engine {
             Ok(shader) => {
                 self.resource_manager.insert_pipeline(typed_id, shader);
                 log::info!("shader resource at: {:?} loaded succesfully", path);
+                Ok(())
             }
             Err(e) => {
                 log::error!("{:?}. loading defualt replacement", e);
                 self.add_defualt_shader(id);
+                Err(LoadError::InvalidData(e.to_string()))
             }
         }
     }
 
-    fn add_finished_font(&mut self, resource: Resource) {
+    fn add_finished_font(&mut self, resource: Resource) -> Result<(), LoadError> {
         let typed_id: ResourceId<Font> = ResourceId::from_number(resource.id);
 
         let context = self.context.as_mut().unwrap();
 
-        let font = context.text_renderer.load_font_from_bytes(&resource.data);
-        self.resource_manager.insert_font(typed_id, font);
-        log::info!("Font resource at: {:?} loaded succesfully", resource.path);
+        match context.text_renderer.load_font_from_bytes(&resource.data) {
+            Ok(font) => {
+                self.resource_manager.insert_font(typed_id, font);
+                log::info!("Font resource at: {:?} loaded succesfully", resource.path);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("{}, loading defualt replacement", e);
+                self.add_defualt_font(resource.id);
+                Err(LoadError::InvalidData(e.to_string()))
+            }
+        }
+    }
+
+    fn add_finished_sound(&mut self, resource: Resource) -> Result<(), LoadError> {
+        let typed_id: ResourceId<Sound> = ResourceId::from_number(resource.id);
+        match Sound::from_resource_data(resource.data) {
+            Ok(sound) => {
+                self.resource_manager.insert_sound(typed_id, sound);
+                log::info!("sound resource at: {:?} loaded succesfully", resource.path);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("{}, loading defualt replacement", e);
+                self.add_defualt_sound(resource.id);
+                Err(LoadError::InvalidData(e.to_string()))
+            }
+        }
+    }
+
+    fn add_finished_music(&mut self, resource: Resource) -> Result<(), LoadError> {
+        let typed_id: ResourceId<Music> = ResourceId::from_number(resource.id);
+        match Music::from_resource_data(resource.data) {
+            Ok(music) => {
+                self.resource_manager.insert_music(typed_id, music);
+                log::info!("music resource at: {:?} loaded succesfully", resource.path);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("{}, loading defualt replacement", e);
+                self.add_defualt_music(resource.id);
+                Err(LoadError::InvalidData(e.to_string()))
+            }
+        }
+    }
+
+    fn add_finished_sprite_sheet(&mut self, resource: Resource) -> Result<(), LoadError> {
+        let typed_id: ResourceId<SpriteSheet> = ResourceId::from_number(resource.id);
+        match SpriteSheet::from_resource_data(&resource.data) {
+            Ok(sheet) => {
+                self.resource_manager.insert_sprite_sheet(typed_id, sheet);
+                log::info!(
+                    "sprite sheet resource at: {:?} loaded succesfully",
+                    resource.path
+                );
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("{}, loading defualt replacement", e);
+                self.add_defualt_sprite_sheet(resource.id);
+                Err(LoadError::InvalidData(e.to_string()))
+            }
+        }
+    }
+
+    fn add_finished_tilemap(&mut self, resource: Resource) -> Result<(), LoadError> {
+        let typed_id: ResourceId<Tilemap> = ResourceId::from_number(resource.id);
+        match Tilemap::from_resource_data(&resource.data, &resource.path, &resource.extra_files) {
+            Ok(map) => {
+                self.resource_manager.insert_tilemap(typed_id, map);
+                log::info!(
+                    "tilemap resource at: {:?} loaded succesfully",
+                    resource.path
+                );
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("{}, loading defualt replacement", e);
+                self.add_defualt_tilemap(resource.id);
+                Err(LoadError::InvalidData(e.to_string()))
+            }
+        }
     }
 
     fn add_defualt_bytes(&mut self, id: NonZeroU64) {
@@ -741,6 +1673,30 @@ impl Engine {
         self.resource_manager.insert_font(typed_id, font);
     }
 
+    fn add_defualt_sound(&mut self, id: NonZeroU64) {
+        let typed_id: ResourceId<Sound> = ResourceId::from_number(id);
+        self.resource_manager
+            .insert_sound(typed_id, Sound::defualt());
+    }
+
+    fn add_defualt_music(&mut self, id: NonZeroU64) {
+        let typed_id: ResourceId<Music> = ResourceId::from_number(id);
+        self.resource_manager
+            .insert_music(typed_id, Music::defualt());
+    }
+
+    fn add_defualt_sprite_sheet(&mut self, id: NonZeroU64) {
+        let typed_id: ResourceId<SpriteSheet> = ResourceId::from_number(id);
+        self.resource_manager
+            .insert_sprite_sheet(typed_id, SpriteSheet::default());
+    }
+
+    fn add_defualt_tilemap(&mut self, id: NonZeroU64) {
+        let typed_id: ResourceId<Tilemap> = ResourceId::from_number(id);
+        self.resource_manager
+            .insert_tilemap(typed_id, Tilemap::default());
+    }
+
     pub(crate) fn is_loading(&self) -> bool {
         // self.loader.get_loading_resources() > 0
         #[cfg(not(target_arch = "wasm32"))]
@@ -783,7 +1739,8 @@ impl<T: Game> ApplicationHandler<BpEvent> for (T, Engine) {
     ) {
         let (game, engine) = self;
         log::info!("event: {:?}, {:?}", event, window_id);
-        if window_id == engine.context.as_ref().unwrap().window.id() && !engine.input(&event) {
+        let engine_window = engine.context.as_ref().unwrap().window.as_ref();
+        if engine_window.map(|w| w.id()) == Some(window_id) && !engine.input(&event) {
             match event {
                 WindowEvent::CloseRequested => event_loop.exit(),
                 WindowEvent::Resized(physical_size) => {
@@ -792,12 +1749,16 @@ impl<T: Game> ApplicationHandler<BpEvent> for (T, Engine) {
                 }
                 WindowEvent::RedrawRequested => {
                     if engine.is_loading() {
-                        engine.update(event_loop);
+                        engine.update();
                     } else {
-                        game.update(engine);
-                        engine.update(event_loop);
+                        engine.update_game(game);
+                        engine.update();
                         engine.current_frametime = Instant::now();
 
+                        if engine.should_close {
+                            event_loop.exit();
+                        }
+
                         match render(game, engine) {
                             Ok(_) => {}
                             // reconfigure surface if lost
@@ -817,16 +1778,16 @@ impl<T: Game> ApplicationHandler<BpEvent> for (T, Engine) {
     }
 
     fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: BpEvent) {
-        let (_, engine) = self;
-        match event {
-            BpEvent::ResourceLoaded(resource) => engine.handle_resource(resource),
-            BpEvent::AdapterReady(thing) => {}
-        }
+        let (game, engine) = self;
+        engine.handle_event(event);
+        engine.send_load_events(game);
     }
 
     fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
         let (_, engine) = self;
-        engine.context.as_ref().unwrap().window.request_redraw();
+        if let Some(window) = &engine.context.as_ref().unwrap().window {
+            window.request_redraw();
+        }
     }
 }
 
@@ -841,6 +1802,14 @@ pub struct EngineBuilder {
     pub(crate) window_title: String,
     pub(crate) resizable: bool,
     pub(crate) vsync: wgpu::PresentMode,
+    headless: bool,
+    audio_backend: Option<Box<dyn AudioBackend>>,
+    gamepad_backend: Option<Box<dyn GamepadBackend>>,
+    touch_mouse_emulation: bool,
+    input_map: Option<InputMap>,
+    fixed_update_rate: Option<NonZeroU16>,
+    virtual_resolution: Option<Vec2<u32>>,
+    hot_reloading: bool,
 }
 
 impl EngineBuilder {
@@ -855,6 +1824,14 @@ impl EngineBuilder {
     ///     window_title: "Bottonless-Pit Game".into(),
     ///     resizable: true,
     ///     vysnc: false,
+    ///     headless: false,
+    ///     audio_backend: None,
+    ///     gamepad_backend: None,
+    ///     touch_mouse_emulation: false,
+    ///     input_map: None,
+    ///     fixed_update_rate: None,
+    ///     virtual_resolution: None,
+    ///     hot_reloading: false,
     /// }
     pub fn new() -> Self {
         Self {
@@ -866,6 +1843,14 @@ impl EngineBuilder {
             window_title: "Bottomless-Pit Game".into(),
             resizable: true,
             vsync: wgpu::PresentMode::AutoVsync,
+            headless: false,
+            audio_backend: None,
+            gamepad_backend: None,
+            touch_mouse_emulation: false,
+            input_map: None,
+            fixed_update_rate: None,
+            virtual_resolution: None,
+            hot_reloading: false,
         }
     }
 
@@ -874,6 +1859,23 @@ impl EngineBuilder {
         Self { resolution, ..self }
     }
 
+    /// Renders the game at a fixed size which is then scaled up to fit the window, great
+    /// for pixel art. The game is scaled by the biggest whole number that fits so every
+    /// pixel stays the same size, with black bars filling the rest of the window.
+    /// [Engine::get_mouse_position] gives positions in virtual pixels and
+    /// [Engine::get_render_size] should be used instead of the window size for things
+    /// like [Camera::transform_point](crate::camera::Camera::transform_point).
+    /// Building the engine fails if the width or height is 0.
+    pub fn with_virtual_resolution(self, width: u32, height: u32) -> Self {
+        Self {
+            virtual_resolution: Some(Vec2 {
+                x: width,
+                y: height,
+            }),
+            ..self
+        }
+    }
+
     /// Will cause the window to be fullscreen upon launch
     pub fn fullscreen(self) -> Self {
         Self {
@@ -893,6 +1895,28 @@ impl EngineBuilder {
         }
     }
 
+    /// Calls [Game::fixed_update] the given amount of times per second of game time,
+    /// this happens zero or more times each frame before [Game::update] depending on how
+    /// much time has passed. Fixed updates always simulate the same amount of time
+    /// which makes things like physics behave the same no matter the framerate.
+    /// Use [Engine::get_interpolation_alpha] or
+    /// [RenderHandle::get_interpolation_alpha](crate::render::RenderHandle::get_interpolation_alpha)
+    /// to draw smoothly between fixed updates. This works alongside [EngineBuilder::set_target_fps].
+    /// ```rust,no_run
+    /// # use std::num::NonZeroU16;
+    /// # use bottomless_pit::engine_handle::EngineBuilder;
+    /// let engine = EngineBuilder::new()
+    ///     .set_fixed_update_rate(NonZeroU16::new(60).unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn set_fixed_update_rate(self, updates_per_second: NonZeroU16) -> Self {
+        Self {
+            fixed_update_rate: Some(updates_per_second),
+            ..self
+        }
+    }
+
     /// Will cause the framerate to be uncapped if the platform supports it using
     /// wgpu's [PresentMode::AutoNoVsync](https://docs.rs/wgpu/latest/wgpu/enum.PresentMode.html)
     /// by defualt the engine uses
@@ -937,6 +1961,84 @@ impl EngineBuilder {
         }
     }
 
+    /// Builds the engine without a window. Everything is rendered into an offscreen
+    /// texture the size of the resolution, which makes it possible to run a [Game]
+    /// on machines without a display like CI servers. Frames are advanced with
+    /// [Engine::step] or [Engine::run_frames] instead of an event loop.
+    /// ```rust,no_run
+    /// # use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+    /// # use bottomless_pit::render::RenderHandle;
+    /// # use bottomless_pit::Game;
+    /// # struct MyGame;
+    /// # impl Game for MyGame {
+    /// #     fn render<'o>(&'o mut self, _render: RenderHandle<'o>) {}
+    /// #     fn update(&mut self, _engine: &mut Engine) {}
+    /// # }
+    /// let mut engine = EngineBuilder::new()
+    ///     .with_resolution((320, 240))
+    ///     .headless()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut game = MyGame;
+    /// engine.run_frames(&mut game, 60);
+    /// ```
+    pub fn headless(self) -> Self {
+        Self {
+            headless: true,
+            ..self
+        }
+    }
+
+    /// Sets where sound is played. By defualt sound is played through the systems
+    /// audio device when the `cpal` feature is enabled and otherwise goes nowhere.
+    /// See [sound](crate::sound) for the backends that come with the engine.
+    pub fn with_audio_backend<B: AudioBackend + 'static>(self, backend: B) -> Self {
+        Self {
+            audio_backend: Some(Box::new(backend)),
+            ..self
+        }
+    }
+
+    /// Sets the actions that can be checked with [Engine::is_action_down]
+    /// and [Engine::action_axis]
+    pub fn with_input_map(self, input_map: InputMap) -> Self {
+        Self {
+            input_map: Some(input_map),
+            ..self
+        }
+    }
+
+    /// Makes the first finger on a touch screen act like the mouse, see
+    /// [Engine::set_touch_mouse_emulation]
+    pub fn emulate_mouse_with_touch(self) -> Self {
+        Self {
+            touch_mouse_emulation: true,
+            ..self
+        }
+    }
+
+    /// Sets where gamepad input comes from. By defualt real gamepads are read when
+    /// the `gilrs` feature is enabled and otherwise there are no gamepads.
+    /// See [gamepad](crate::gamepad) for the backends that come with the engine.
+    pub fn with_gamepad_backend<B: GamepadBackend + 'static>(self, backend: B) -> Self {
+        Self {
+            gamepad_backend: Some(Box::new(backend)),
+            ..self
+        }
+    }
+
+    /// Watches the files of every [Texture], [Shader] and [Font] loaded from disk and
+    /// reloads them when they change, keeping the same [ResourceId] so anything using
+    /// them picks up the new version. If a shader fails to compile the error is logged
+    /// and the old shader keeps being used. This does nothing on wasm.
+    pub fn with_hot_reloading(self) -> Self {
+        Self {
+            hot_reloading: true,
+            ..self
+        }
+    }
+
     /// Attempts to buld the Engine
     pub fn build(self) -> Result<Engine, BuildError> {
         Engine::new(self)
@@ -960,6 +2062,8 @@ pub enum BuildError {
     CreateSurfaceError(CreateSurfaceError),
     /// Occurs when the WGPU adapter cannot be found.
     FailedToCreateAdapter,
+    /// The width or height given to [EngineBuilder::with_virtual_resolution] was 0
+    ZeroVirtualResolution,
     /// Occurs when the WGPU device cannot be made. This usually
     /// means the OS does not support the minimum graphics features.
     RequestDeviceError(RequestDeviceError),
@@ -983,6 +2087,7 @@ impl std::fmt::Display for BuildError {
             Self::WindowOsError(e) => write!(f, "{}", e),
             Self::CreateSurfaceError(e) => write!(f, "{}", e),
             Self::FailedToCreateAdapter => write!(f, "unable to create Adapater"),
+            Self::ZeroVirtualResolution => write!(f, "the virtual resolution cant be 0 pixels"),
             Self::RequestDeviceError(e) => write!(f, "{}", e),
             #[cfg(target_arch = "wasm32")]
             Self::CantGetWebWindow => write!(f, "could not get web Window"),
@@ -1060,10 +2165,236 @@ pub(crate) struct DefualtResources {
     pub(crate) defualt_texture_id: ResourceId<Texture>,
     pub(crate) default_pipeline_id: ResourceId<Shader>,
     pub(crate) line_pipeline_id: ResourceId<Shader>,
+    pub(crate) instanced_pipeline_id: ResourceId<Shader>,
+}
+
+impl DefualtResources {
+    // ids are unique across every resource type so the raw number is enough
+    fn contains(&self, id: NonZeroU64) -> bool {
+        [
+            self.defualt_texture_id.get_id(),
+            self.default_pipeline_id.get_id(),
+            self.line_pipeline_id.get_id(),
+            self.instanced_pipeline_id.get_id(),
+        ]
+        .contains(&id)
+    }
 }
 
 #[derive(Debug)]
 pub(crate) enum BpEvent {
     ResourceLoaded(Result<Resource, ResourceError>),
+    #[cfg(not(target_arch = "wasm32"))]
+    ResourceReloaded(ReloadedResource),
+    // will be used once adapters are requested asynchronously on the web
+    #[allow(dead_code)]
     AdapterReady((Option<wgpu::Adapter>, Intermediate)),
 }
+
+/// Sends events back to the engine. Windowed engines go through the winit
+/// event loop while headless engines use a channel that is drained every step.
+#[derive(Clone)]
+pub(crate) enum BpProxy {
+    EventLoop(EventLoopProxy<BpEvent>),
+    Channel(Sender<BpEvent>),
+}
+
+impl BpProxy {
+    /// Fails when the engine has already been dropped
+    pub(crate) fn send_event(&self, event: BpEvent) -> Result<(), ()> {
+        match self {
+            Self::EventLoop(proxy) => proxy.send_event(event).map_err(|_| ()),
+            Self::Channel(sender) => sender.send(event).map_err(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::RenderHandle;
+    use winit::event::{DeviceId, ElementState, MouseButton};
+
+    struct Idle;
+
+    impl Game for Idle {
+        fn render<'o>(&'o mut self, _render_handle: RenderHandle<'o>) {}
+        fn update(&mut self, _engine_handle: &mut Engine) {}
+    }
+
+    fn mouse(engine: &mut Engine, state: ElementState) {
+        engine.input(&WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state,
+            button: MouseButton::Left,
+        });
+    }
+
+    #[test]
+    fn input_during_a_replay_is_kept_for_afterwards() {
+        let mut engine = EngineBuilder::new()
+            .with_resolution((16, 16))
+            .headless()
+            .build()
+            .unwrap();
+        let mut game = Idle;
+
+        // a recording where the mouse is never touched
+        engine.start_recording_input();
+        engine.run_frames(&mut game, 2);
+        let recording = engine.stop_recording_input().unwrap();
+
+        mouse(&mut engine, ElementState::Pressed);
+        engine.step(&mut game);
+        assert!(engine.is_mouse_key_down(MouseKey::Left));
+
+        engine.play_input_recording(recording);
+        engine.step(&mut game);
+        assert!(engine.is_replaying_input());
+        assert!(!engine.is_mouse_key_down(MouseKey::Left));
+
+        // let go while the replay is still going
+        mouse(&mut engine, ElementState::Released);
+        engine.run_frames(&mut game, 2);
+        assert!(!engine.is_replaying_input());
+        assert!(!engine.is_mouse_key_down(MouseKey::Left));
+
+        mouse(&mut engine, ElementState::Pressed);
+        assert!(engine.is_mouse_key_down(MouseKey::Left));
+    }
+
+    fn touch(engine: &mut Engine, phase: winit::event::TouchPhase, x: f64, y: f64) {
+        engine.input(&WindowEvent::Touch(winit::event::Touch {
+            device_id: DeviceId::dummy(),
+            phase,
+            location: winit::dpi::PhysicalPosition::new(x, y),
+            force: None,
+            id: 3,
+        }));
+    }
+
+    #[test]
+    fn touches_are_in_virtual_pixels() {
+        let mut engine = EngineBuilder::new()
+            .with_resolution((64, 48))
+            .with_virtual_resolution(32, 24)
+            .emulate_mouse_with_touch()
+            .headless()
+            .build()
+            .unwrap();
+
+        touch(&mut engine, winit::event::TouchPhase::Started, 10.0, 20.0);
+        touch(&mut engine, winit::event::TouchPhase::Moved, 40.0, 30.0);
+
+        let expected_position = Vec2 { x: 20.0, y: 15.0 };
+        let finger = engine.get_touch(3).unwrap();
+        assert_eq!(finger.position, expected_position);
+        assert_eq!(finger.start_position, Vec2 { x: 5.0, y: 10.0 });
+        assert_eq!(engine.get_touches(), [finger]);
+        assert_eq!(engine.get_mouse_position(), expected_position);
+    }
+
+    #[test]
+    fn defualt_resources_cant_be_unloaded() {
+        let mut engine = EngineBuilder::new()
+            .with_resolution((16, 16))
+            .headless()
+            .build()
+            .unwrap();
+
+        engine.unload(engine.defualt_material_bg_id());
+        engine.unload(engine.defualt_pipe_id());
+        engine.unload(engine.line_pipe_id());
+        engine.unload(engine.instanced_pipe_id());
+
+        let resources = &engine.resource_manager;
+        assert!(resources
+            .get_texture(&engine.defualt_material_bg_id())
+            .is_some());
+        assert!(resources.get_pipeline(&engine.defualt_pipe_id()).is_some());
+        assert!(resources.get_pipeline(&engine.line_pipe_id()).is_some());
+        assert!(resources
+            .get_pipeline(&engine.instanced_pipe_id())
+            .is_some());
+    }
+
+    #[test]
+    fn handles_for_the_same_resource_share_a_count() {
+        let mut engine = EngineBuilder::new()
+            .with_resolution((16, 16))
+            .headless()
+            .build()
+            .unwrap();
+        let mut game = Idle;
+
+        let texture = Texture::from_rgba(&mut engine, Vec2 { x: 1, y: 1 }, &[255; 4]);
+        engine.step(&mut game);
+
+        let first = engine.create_handle(texture);
+        let second = engine.create_handle(texture);
+        drop(first);
+        engine.step(&mut game);
+        assert!(engine.resource_manager.get_texture(&texture).is_some());
+
+        // made after the last handle was dropped but before the engine unloaded it
+        drop(second);
+        let third = engine.create_handle(texture);
+        engine.step(&mut game);
+        assert!(engine.resource_manager.get_texture(&texture).is_some());
+
+        drop(third);
+        engine.step(&mut game);
+        assert!(engine.resource_manager.get_texture(&texture).is_none());
+    }
+
+    #[test]
+    fn zero_virtual_resolution_is_rejected() {
+        let built = EngineBuilder::new()
+            .with_virtual_resolution(0, 24)
+            .headless()
+            .build();
+
+        assert!(matches!(built, Err(BuildError::ZeroVirtualResolution)));
+    }
+
+    #[test]
+    fn reloading_a_broken_font_keeps_the_old_one() {
+        let mut engine = EngineBuilder::new()
+            .with_resolution((16, 16))
+            .headless()
+            .build()
+            .unwrap();
+        let mut game = Idle;
+
+        let path = "examples/Comic.ttf";
+        let font = Font::new(path, &mut engine, LoadingOp::Blocking);
+        engine.step(&mut game);
+        assert!(engine.resource_manager.get_font(&font).is_some());
+
+        let faces = |engine: &Engine| {
+            engine
+                .context
+                .as_ref()
+                .unwrap()
+                .text_renderer
+                .font_system
+                .db()
+                .len()
+        };
+        let faces_before = faces(&engine);
+
+        // files that were empty or only half written when they were read, and not fonts at all
+        let data = std::fs::read(path).unwrap();
+        for broken in [Vec::new(), data[..12].to_vec(), b"not a font".to_vec()] {
+            engine.handle_reload(ReloadedResource {
+                path: path.into(),
+                data: Ok(broken),
+                id: font.get_id(),
+                kind: ReloadKind::Font,
+            });
+
+            assert!(engine.resource_manager.get_font(&font).is_some());
+            assert_eq!(faces(&engine), faces_before);
+        }
+    }
+}
@@ -0,0 +1,690 @@
+//! Contains the types used for gamepad input
+//! ```rust,no_run
+//! # use bottomless_pit::engine_handle::Engine;
+//! # use bottomless_pit::gamepad::{GamepadAxis, GamepadButton};
+//! # fn update(engine_handle: &mut Engine) {
+//! for pad in engine_handle.get_connected_gamepads() {
+//!     if engine_handle.is_gamepad_button_pressed(pad, GamepadButton::South) {
+//!         // jump
+//!     }
+//!
+//!     let x = engine_handle.get_gamepad_axis(pad, GamepadAxis::LeftStickX);
+//!     // move by x
+//! }
+//! # }
+//! ```
+//! Where gamepad input comes from is decided by the [GamepadBackend]. With the `gilrs`
+//! feature enabled real controllers are read using [gilrs](https://docs.rs/gilrs),
+//! without it no gamepads will ever be connected. [VirtualGamepads] can be used to
+//! feed in input by hand, which is usefull for testing.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const BUTTON_COUNT: usize = 17;
+const AXIS_COUNT: usize = 6;
+// how far a trigger has to be pushed before its button counts as down
+const TRIGGER_THRESHOLD: f32 = 0.5;
+
+/// Identifies a connected gamepad. Ids are given out by the [GamepadBackend]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub usize);
+
+/// Representation of gamepad buttons using the layout of an Xbox controller,
+/// the face buttons are named after where they are so they mean the same
+/// thing on every brand of controller.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadButton {
+    /// A on Xbox, Cross on PlayStation
+    South,
+    /// B on Xbox, Circle on PlayStation
+    East,
+    /// Y on Xbox, Triangle on PlayStation
+    North,
+    /// X on Xbox, Square on PlayStation
+    West,
+    LeftBumper,
+    RightBumper,
+    /// The trigger counts as down once [GamepadAxis::LeftTrigger] is pushed
+    /// past halfway, this ignores the deadzone
+    LeftTrigger,
+    /// The trigger counts as down once [GamepadAxis::RightTrigger] is pushed
+    /// past halfway, this ignores the deadzone
+    RightTrigger,
+    Select,
+    Start,
+    /// The Xbox or PlayStation button
+    Mode,
+    /// Pressing in the left stick
+    LeftStick,
+    /// Pressing in the right stick
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Representation of the analog inputs of a gamepad. Sticks go from -1.0 to 1.0
+/// where up and right are positive, triggers go from 0.0 to 1.0.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadButton {
+    // in the same order as the discriminants
+    pub(crate) const ALL: [Self; BUTTON_COUNT] = [
+        Self::South,
+        Self::East,
+        Self::North,
+        Self::West,
+        Self::LeftBumper,
+        Self::RightBumper,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+        Self::Select,
+        Self::Start,
+        Self::Mode,
+        Self::LeftStick,
+        Self::RightStick,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+    ];
+}
+
+impl GamepadAxis {
+    // in the same order as the discriminants
+    pub(crate) const ALL: [Self; AXIS_COUNT] = [
+        Self::LeftStickX,
+        Self::LeftStickY,
+        Self::RightStickX,
+        Self::RightStickY,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+    ];
+
+    // the other half of a stick, the deadzone is applied to both at once
+    fn stick_partner(self) -> Option<Self> {
+        match self {
+            Self::LeftStickX => Some(Self::LeftStickY),
+            Self::LeftStickY => Some(Self::LeftStickX),
+            Self::RightStickX => Some(Self::RightStickY),
+            Self::RightStickY => Some(Self::RightStickX),
+            Self::LeftTrigger | Self::RightTrigger => None,
+        }
+    }
+
+    // the button that is pressed when this axis is pushed far enough
+    fn trigger_button(self) -> Option<GamepadButton> {
+        match self {
+            Self::LeftTrigger => Some(GamepadButton::LeftTrigger),
+            Self::RightTrigger => Some(GamepadButton::RightTrigger),
+            _ => None,
+        }
+    }
+}
+
+/// Something that happened to a gamepad. These are produced by a
+/// [GamepadBackend] and the ones from the current frame can be read with
+/// [Engine::get_gamepad_events](crate::engine_handle::Engine::get_gamepad_events)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamepadEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+    ButtonPressed(GamepadId, GamepadButton),
+    ButtonReleased(GamepadId, GamepadButton),
+    /// The raw value of the axis before any deadzone is applied
+    AxisChanged(GamepadId, GamepadAxis, f32),
+}
+
+/// Somewhere gamepad input can come from. Custom backends can be set with
+/// [EngineBuilder::with_gamepad_backend](crate::engine_handle::EngineBuilder::with_gamepad_backend).
+pub trait GamepadBackend {
+    /// Called once per frame, push every event that has happened since the last call
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>);
+}
+
+/// A backend that never has any gamepads connected
+pub struct NoGamepads;
+
+impl GamepadBackend for NoGamepads {
+    fn poll(&mut self, _events: &mut Vec<GamepadEvent>) {}
+}
+
+/// Gamepads that are controlled from code. Clone this before giving it to
+/// [EngineBuilder::with_gamepad_backend](crate::engine_handle::EngineBuilder::with_gamepad_backend)
+/// and use the clone to connect pads and press buttons. Input shows up on the next frame.
+/// ```rust,no_run
+/// # use bottomless_pit::engine_handle::EngineBuilder;
+/// # use bottomless_pit::gamepad::{GamepadButton, VirtualGamepads};
+/// let gamepads = VirtualGamepads::new();
+/// let mut engine = EngineBuilder::new()
+///     .headless()
+///     .with_gamepad_backend(gamepads.clone())
+///     .build()
+///     .unwrap();
+///
+/// let pad = gamepads.connect();
+/// gamepads.press(pad, GamepadButton::Start);
+/// ```
+#[derive(Clone, Default)]
+pub struct VirtualGamepads {
+    inner: Arc<Mutex<VirtualInner>>,
+}
+
+#[derive(Default)]
+struct VirtualInner {
+    next_id: usize,
+    queued: Vec<GamepadEvent>,
+}
+
+impl VirtualGamepads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connects a new virtual gamepad
+    pub fn connect(&self) -> GamepadId {
+        let mut inner = self.inner.lock().unwrap();
+        let id = GamepadId(inner.next_id);
+        inner.next_id += 1;
+        inner.queued.push(GamepadEvent::Connected(id));
+        id
+    }
+
+    pub fn disconnect(&self, id: GamepadId) {
+        self.push(GamepadEvent::Disconnected(id));
+    }
+
+    pub fn press(&self, id: GamepadId, button: GamepadButton) {
+        self.push(GamepadEvent::ButtonPressed(id, button));
+    }
+
+    pub fn release(&self, id: GamepadId, button: GamepadButton) {
+        self.push(GamepadEvent::ButtonReleased(id, button));
+    }
+
+    pub fn set_axis(&self, id: GamepadId, axis: GamepadAxis, value: f32) {
+        self.push(GamepadEvent::AxisChanged(id, axis, value));
+    }
+
+    fn push(&self, event: GamepadEvent) {
+        self.inner.lock().unwrap().queued.push(event);
+    }
+}
+
+impl GamepadBackend for VirtualGamepads {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        events.append(&mut self.inner.lock().unwrap().queued);
+    }
+}
+
+/// Reads real controllers using [gilrs](https://docs.rs/gilrs)
+#[cfg(feature = "gilrs")]
+pub struct GilrsBackend {
+    gilrs: gilrs::Gilrs,
+    // gilrs does not tell us about pads that were plugged in before it started
+    startup_pads: Vec<GamepadId>,
+}
+
+#[cfg(feature = "gilrs")]
+impl GilrsBackend {
+    /// Fails when gilrs does not support the platform or cannot access the gamepads
+    pub fn new() -> Result<Self, Box<gilrs::Error>> {
+        let gilrs = gilrs::Gilrs::new()?;
+        let startup_pads = gilrs
+            .gamepads()
+            .map(|(id, _)| GamepadId(id.into()))
+            .collect();
+
+        Ok(Self {
+            gilrs,
+            startup_pads,
+        })
+    }
+}
+
+#[cfg(feature = "gilrs")]
+impl GamepadBackend for GilrsBackend {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        use gilrs::EventType;
+
+        events.extend(self.startup_pads.drain(..).map(GamepadEvent::Connected));
+
+        while let Some(event) = self.gilrs.next_event() {
+            let id = GamepadId(event.id.into());
+            let converted = match event.event {
+                EventType::Connected => Some(GamepadEvent::Connected(id)),
+                EventType::Disconnected => Some(GamepadEvent::Disconnected(id)),
+                EventType::ButtonPressed(button, _) => {
+                    convert_gilrs_button(button).map(|b| GamepadEvent::ButtonPressed(id, b))
+                }
+                EventType::ButtonReleased(button, _) => {
+                    convert_gilrs_button(button).map(|b| GamepadEvent::ButtonReleased(id, b))
+                }
+                // analog triggers are reported as buttons with a value
+                EventType::ButtonChanged(gilrs::Button::LeftTrigger2, value, _) => Some(
+                    GamepadEvent::AxisChanged(id, GamepadAxis::LeftTrigger, value),
+                ),
+                EventType::ButtonChanged(gilrs::Button::RightTrigger2, value, _) => Some(
+                    GamepadEvent::AxisChanged(id, GamepadAxis::RightTrigger, value),
+                ),
+                EventType::AxisChanged(axis, value, _) => {
+                    convert_gilrs_axis(axis).map(|a| GamepadEvent::AxisChanged(id, a, value))
+                }
+                _ => None,
+            };
+
+            events.extend(converted);
+        }
+    }
+}
+
+#[cfg(feature = "gilrs")]
+fn convert_gilrs_button(button: gilrs::Button) -> Option<GamepadButton> {
+    use gilrs::Button;
+
+    let button = match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        // gilrs calls the bumpers triggers and the triggers trigger2
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        // the trigger buttons come from the axis so the two cant disagree
+        Button::LeftTrigger2 | Button::RightTrigger2 => return None,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    };
+
+    Some(button)
+}
+
+#[cfg(feature = "gilrs")]
+fn convert_gilrs_axis(axis: gilrs::Axis) -> Option<GamepadAxis> {
+    use gilrs::Axis;
+
+    let axis = match axis {
+        Axis::LeftStickX => GamepadAxis::LeftStickX,
+        Axis::LeftStickY => GamepadAxis::LeftStickY,
+        Axis::RightStickX => GamepadAxis::RightStickX,
+        Axis::RightStickY => GamepadAxis::RightStickY,
+        Axis::LeftZ => GamepadAxis::LeftTrigger,
+        Axis::RightZ => GamepadAxis::RightTrigger,
+        _ => return None,
+    };
+
+    Some(axis)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct GamepadState {
+    pub(crate) previous_buttons: [bool; BUTTON_COUNT],
+    pub(crate) current_buttons: [bool; BUTTON_COUNT],
+    pub(crate) previous_axes: [f32; AXIS_COUNT],
+    pub(crate) axes: [f32; AXIS_COUNT],
+}
+
+pub(crate) struct GamepadHandle {
+    backend: Box<dyn GamepadBackend>,
+    gamepads: HashMap<GamepadId, GamepadState>,
+    // events from the current frame
+    events: Vec<GamepadEvent>,
+    deadzone: f32,
+}
+
+impl GamepadHandle {
+    pub(crate) fn new(backend: Box<dyn GamepadBackend>) -> Self {
+        Self {
+            backend,
+            gamepads: HashMap::new(),
+            events: Vec::new(),
+            deadzone: 0.15,
+        }
+    }
+
+    /// Picks the best backend available on this platform
+    pub(crate) fn defualt_backend() -> Box<dyn GamepadBackend> {
+        #[cfg(feature = "gilrs")]
+        match GilrsBackend::new() {
+            Ok(backend) => return Box::new(backend),
+            Err(e) => log::warn!("could not start gamepad support: {}", e),
+        }
+
+        Box::new(NoGamepads)
+    }
+
+    pub(crate) fn end_of_frame_refresh(&mut self) {
+        for state in self.gamepads.values_mut() {
+            state.previous_buttons = state.current_buttons;
+            state.previous_axes = state.axes;
+        }
+
+        self.events.clear();
+        self.backend.poll(&mut self.events);
+
+        for event in self.events.iter() {
+            match *event {
+                GamepadEvent::Connected(id) => {
+                    self.gamepads.entry(id).or_default();
+                }
+                GamepadEvent::Disconnected(id) => {
+                    self.gamepads.remove(&id);
+                }
+                GamepadEvent::ButtonPressed(id, button) => {
+                    if let Some(state) = self.gamepads.get_mut(&id) {
+                        state.current_buttons[button as usize] = true;
+                    }
+                }
+                GamepadEvent::ButtonReleased(id, button) => {
+                    if let Some(state) = self.gamepads.get_mut(&id) {
+                        state.current_buttons[button as usize] = false;
+                    }
+                }
+                GamepadEvent::AxisChanged(id, axis, value) => {
+                    if let Some(state) = self.gamepads.get_mut(&id) {
+                        state.axes[axis as usize] = value;
+                        if let Some(button) = axis.trigger_button() {
+                            state.current_buttons[button as usize] = value >= TRIGGER_THRESHOLD;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copies the state of every gamepad and the events from this frame
+    pub(crate) fn snapshot(&self) -> (Vec<(GamepadId, GamepadState)>, Vec<GamepadEvent>) {
+        let mut gamepads = self
+            .gamepads
+            .iter()
+            .map(|(id, state)| (*id, state.clone()))
+            .collect::<Vec<_>>();
+        gamepads.sort_by_key(|(id, _)| *id);
+
+        (gamepads, self.events.clone())
+    }
+
+    /// Overwrites the state of every gamepad with one from [GamepadHandle::snapshot]
+    pub(crate) fn restore(
+        &mut self,
+        gamepads: &[(GamepadId, GamepadState)],
+        events: &[GamepadEvent],
+    ) {
+        self.gamepads = gamepads.iter().cloned().collect();
+        self.events = events.to_vec();
+    }
+
+    pub(crate) fn get_connected(&self) -> Vec<GamepadId> {
+        let mut ids = self.gamepads.keys().copied().collect::<Vec<GamepadId>>();
+        ids.sort();
+        ids
+    }
+
+    pub(crate) fn is_connected(&self, id: GamepadId) -> bool {
+        self.gamepads.contains_key(&id)
+    }
+
+    pub(crate) fn get_events(&self) -> &[GamepadEvent] {
+        &self.events
+    }
+
+    pub(crate) fn is_button_down(&self, id: GamepadId, button: GamepadButton) -> bool {
+        let index = button as usize;
+        self.gamepads
+            .get(&id)
+            .is_some_and(|s| s.current_buttons[index])
+    }
+
+    pub(crate) fn is_button_up(&self, id: GamepadId, button: GamepadButton) -> bool {
+        !self.is_button_down(id, button)
+    }
+
+    pub(crate) fn is_button_pressed(&self, id: GamepadId, button: GamepadButton) -> bool {
+        let index = button as usize;
+        self.gamepads
+            .get(&id)
+            .is_some_and(|s| !s.previous_buttons[index] && s.current_buttons[index])
+    }
+
+    pub(crate) fn is_button_released(&self, id: GamepadId, button: GamepadButton) -> bool {
+        let index = button as usize;
+        self.gamepads
+            .get(&id)
+            .is_some_and(|s| s.previous_buttons[index] && !s.current_buttons[index])
+    }
+
+    pub(crate) fn get_axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.gamepads
+            .get(&id)
+            .map(|state| self.apply_deadzone(&state.axes, axis))
+            .unwrap_or(0.0)
+    }
+
+    /// Checks the button on every connected gamepad, optionally as it was last frame
+    pub(crate) fn is_button_down_on_any(&self, button: GamepadButton, previous: bool) -> bool {
+        let index = button as usize;
+        self.gamepads.values().any(|s| match previous {
+            true => s.previous_buttons[index],
+            false => s.current_buttons[index],
+        })
+    }
+
+    /// Gets the axis furthest from the center out of every connected gamepad,
+    /// optionally as it was last frame
+    pub(crate) fn get_axis_on_any(&self, axis: GamepadAxis, previous: bool) -> f32 {
+        self.gamepads
+            .values()
+            .map(|s| match previous {
+                true => self.apply_deadzone(&s.previous_axes, axis),
+                false => self.apply_deadzone(&s.axes, axis),
+            })
+            .fold(0.0, |furthest, value| {
+                if value.abs() > furthest.abs() {
+                    value
+                } else {
+                    furthest
+                }
+            })
+    }
+
+    fn apply_deadzone(&self, axes: &[f32; AXIS_COUNT], axis: GamepadAxis) -> f32 {
+        let value = axes[axis as usize];
+        match axis.stick_partner() {
+            // sticks use a circular deadzone so diagonals are not cut off
+            Some(partner) => {
+                let other = axes[partner as usize];
+                let length = (value * value + other * other).sqrt();
+                if length <= self.deadzone {
+                    0.0
+                } else {
+                    let scaled = ((length - self.deadzone) / (1.0 - self.deadzone)).min(1.0);
+                    value / length * scaled
+                }
+            }
+            None if value <= self.deadzone => 0.0,
+            None => ((value - self.deadzone) / (1.0 - self.deadzone)).min(1.0),
+        }
+    }
+
+    pub(crate) fn get_deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    pub(crate) fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn virtual_handle() -> (VirtualGamepads, GamepadHandle) {
+        let pads = VirtualGamepads::new();
+        let handle = GamepadHandle::new(Box::new(pads.clone()));
+        (pads, handle)
+    }
+
+    #[test]
+    fn connect_and_disconnect() {
+        let (pads, mut handle) = virtual_handle();
+        let first = pads.connect();
+        let second = pads.connect();
+        handle.end_of_frame_refresh();
+
+        assert_eq!(handle.get_connected(), vec![first, second]);
+        assert_eq!(
+            handle.get_events(),
+            &[
+                GamepadEvent::Connected(first),
+                GamepadEvent::Connected(second)
+            ]
+        );
+
+        pads.disconnect(first);
+        handle.end_of_frame_refresh();
+        assert!(!handle.is_connected(first));
+        assert!(handle.is_connected(second));
+        assert_eq!(handle.get_events(), &[GamepadEvent::Disconnected(first)]);
+
+        // events only last one frame
+        handle.end_of_frame_refresh();
+        assert!(handle.get_events().is_empty());
+    }
+
+    #[test]
+    fn button_press_and_release_edges() {
+        let (pads, mut handle) = virtual_handle();
+        let pad = pads.connect();
+        pads.press(pad, GamepadButton::South);
+        handle.end_of_frame_refresh();
+
+        assert!(handle.is_button_pressed(pad, GamepadButton::South));
+        assert!(handle.is_button_down(pad, GamepadButton::South));
+        assert!(handle.is_button_up(pad, GamepadButton::East));
+
+        handle.end_of_frame_refresh();
+        assert!(!handle.is_button_pressed(pad, GamepadButton::South));
+        assert!(handle.is_button_down(pad, GamepadButton::South));
+
+        pads.release(pad, GamepadButton::South);
+        handle.end_of_frame_refresh();
+        assert!(handle.is_button_released(pad, GamepadButton::South));
+        assert!(handle.is_button_up(pad, GamepadButton::South));
+
+        handle.end_of_frame_refresh();
+        assert!(!handle.is_button_released(pad, GamepadButton::South));
+    }
+
+    #[test]
+    fn input_on_unknown_pads_is_ignored() {
+        let (pads, mut handle) = virtual_handle();
+        pads.press(GamepadId(7), GamepadButton::South);
+        handle.end_of_frame_refresh();
+
+        assert!(!handle.is_connected(GamepadId(7)));
+        assert!(!handle.is_button_down_on_any(GamepadButton::South, false));
+    }
+
+    #[test]
+    fn circular_stick_deadzone() {
+        let (pads, mut handle) = virtual_handle();
+        let pad = pads.connect();
+        // each half is inside the deadzone but the length is not
+        pads.set_axis(pad, GamepadAxis::LeftStickX, 0.12);
+        pads.set_axis(pad, GamepadAxis::LeftStickY, 0.12);
+        pads.set_axis(pad, GamepadAxis::RightStickX, 0.1);
+        handle.end_of_frame_refresh();
+
+        assert_eq!(handle.get_deadzone(), 0.15);
+        assert_eq!(handle.get_axis(pad, GamepadAxis::RightStickX), 0.0);
+
+        let x = handle.get_axis(pad, GamepadAxis::LeftStickX);
+        let y = handle.get_axis(pad, GamepadAxis::LeftStickY);
+        assert!(x > 0.0);
+        assert_eq!(x, y);
+        let expected = (0.12_f32.hypot(0.12) - 0.15) / 0.85;
+        assert!((x.hypot(y) - expected).abs() < 1e-6);
+
+        pads.set_axis(pad, GamepadAxis::LeftStickX, -1.0);
+        pads.set_axis(pad, GamepadAxis::LeftStickY, 0.0);
+        handle.end_of_frame_refresh();
+        assert_eq!(handle.get_axis(pad, GamepadAxis::LeftStickX), -1.0);
+        assert_eq!(handle.get_axis(pad, GamepadAxis::LeftStickY), 0.0);
+    }
+
+    #[test]
+    fn trigger_deadzone_and_button() {
+        let (pads, mut handle) = virtual_handle();
+        let pad = pads.connect();
+        pads.set_axis(pad, GamepadAxis::LeftTrigger, 0.1);
+        handle.end_of_frame_refresh();
+
+        assert_eq!(handle.get_axis(pad, GamepadAxis::LeftTrigger), 0.0);
+        assert!(handle.is_button_up(pad, GamepadButton::LeftTrigger));
+
+        pads.set_axis(pad, GamepadAxis::LeftTrigger, 0.8);
+        handle.end_of_frame_refresh();
+        assert!(handle.is_button_pressed(pad, GamepadButton::LeftTrigger));
+        assert!(handle.is_button_up(pad, GamepadButton::RightTrigger));
+
+        pads.set_axis(pad, GamepadAxis::LeftTrigger, 0.2);
+        handle.end_of_frame_refresh();
+        assert!(handle.is_button_released(pad, GamepadButton::LeftTrigger));
+    }
+
+    #[test]
+    fn any_pad_uses_the_furthest_axis() {
+        let (pads, mut handle) = virtual_handle();
+        let first = pads.connect();
+        let second = pads.connect();
+        pads.set_axis(first, GamepadAxis::LeftStickX, 0.5);
+        pads.set_axis(second, GamepadAxis::LeftStickX, -1.0);
+        handle.end_of_frame_refresh();
+
+        assert_eq!(handle.get_axis_on_any(GamepadAxis::LeftStickX, false), -1.0);
+        assert_eq!(handle.get_axis_on_any(GamepadAxis::LeftStickX, true), 0.0);
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let (pads, mut handle) = virtual_handle();
+        let pad = pads.connect();
+        pads.press(pad, GamepadButton::Start);
+        pads.set_axis(pad, GamepadAxis::RightStickY, 1.0);
+        handle.end_of_frame_refresh();
+        let (gamepads, events) = handle.snapshot();
+
+        pads.disconnect(pad);
+        handle.end_of_frame_refresh();
+        assert!(!handle.is_connected(pad));
+
+        handle.restore(&gamepads, &events);
+        assert!(handle.is_button_pressed(pad, GamepadButton::Start));
+        assert_eq!(handle.get_axis(pad, GamepadAxis::RightStickY), 1.0);
+        assert_eq!(handle.get_events(), events.as_slice());
+        assert_eq!(handle.snapshot(), (gamepads, events));
+    }
+}
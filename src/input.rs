@@ -1,4 +1,4 @@
-//! Contains Both the MouseKey and Key Enums for input
+//! Contains the MouseKey and Key Enums as well as [Touch] for input
 //! ```rust,no_run
 //! impl Game For Struct {
 //!     fn update(&mut self, engine_handle: &mut Engine) {
@@ -16,22 +16,36 @@
 //!     }
 //! }
 
-const INPUT_MAP_SIZE: usize = 112;
+pub(crate) const INPUT_MAP_SIZE: usize = 112;
+// how many pixels of trackpad scrolling count as one line of a scroll wheel
+const PIXELS_PER_LINE: f32 = 20.0;
 
-use winit::event::{ElementState, KeyEvent, Modifiers, MouseButton, WindowEvent};
+use winit::event::{ElementState, KeyEvent, Modifiers, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event::{Touch as WinitTouch, TouchPhase as WinitTouchPhase};
 use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
 
+use crate::replay::InputFrame;
 use crate::vectors::Vec2;
 
 pub(crate) struct InputHandle {
     previous_keyboard_state: [bool; INPUT_MAP_SIZE],
     current_keyboard_state: [bool; INPUT_MAP_SIZE],
     modifier_state: ModifiersState,
+    previous_modifier_state: ModifiersState,
     previous_mouse_state: [bool; 6],
     current_mouse_state: [bool; 6],
     current_text: Option<String>,
     mouse_position: Vec2<f32>,
     mouse_delta: Vec2<f32>,
+    wheel_delta: Vec2<f32>,
+    // active touches as well as the ones that ended this frame
+    touches: Vec<Touch>,
+    emulate_mouse: bool,
+    // the touch that is pretending to be the mouse
+    primary_touch: Option<u64>,
+    // a tap that starts and ends in one frame keeps the mouse down untill the next
+    // frame, otherwise the click would never be seen
+    release_touch_mouse: bool,
 }
 
 impl InputHandle {
@@ -40,19 +54,75 @@ impl InputHandle {
             previous_keyboard_state: [false; INPUT_MAP_SIZE],
             current_keyboard_state: [false; INPUT_MAP_SIZE],
             modifier_state: ModifiersState::empty(),
+            previous_modifier_state: ModifiersState::empty(),
             previous_mouse_state: [false; 6],
             current_mouse_state: [false; 6],
             current_text: None,
             mouse_position: Vec2 { x: 0.0, y: 0.0 },
             mouse_delta: Vec2 { x: 0.0, y: 0.0 },
+            wheel_delta: Vec2 { x: 0.0, y: 0.0 },
+            touches: Vec::new(),
+            emulate_mouse: false,
+            primary_touch: None,
+            release_touch_mouse: false,
         }
     }
 
     pub(crate) fn end_of_frame_refresh(&mut self) {
         self.previous_keyboard_state = self.current_keyboard_state;
         self.previous_mouse_state = self.current_mouse_state;
+        self.previous_modifier_state = self.modifier_state;
         self.current_text = None;
         self.mouse_delta = Vec2 { x: 0.0, y: 0.0 };
+        self.wheel_delta = Vec2 { x: 0.0, y: 0.0 };
+
+        self.touches
+            .retain(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled));
+        self.touches.iter_mut().for_each(|t| {
+            t.phase = TouchPhase::Stationary;
+            t.started = false;
+        });
+
+        if self.release_touch_mouse {
+            self.release_touch_mouse = false;
+            self.current_mouse_state[MouseKey::Left as usize] = false;
+        }
+    }
+
+    /// Copies everything the game can see about this frame's input
+    pub(crate) fn snapshot(&self, delta_time: f32) -> InputFrame {
+        InputFrame {
+            delta_time,
+            previous_keyboard_state: self.previous_keyboard_state,
+            current_keyboard_state: self.current_keyboard_state,
+            previous_modifier_state: self.previous_modifier_state.bits(),
+            modifier_state: self.modifier_state.bits(),
+            previous_mouse_state: self.previous_mouse_state,
+            current_mouse_state: self.current_mouse_state,
+            current_text: self.current_text.clone(),
+            mouse_position: self.mouse_position,
+            mouse_delta: self.mouse_delta,
+            wheel_delta: self.wheel_delta,
+            touches: self.touches.clone(),
+            gamepads: Vec::new(),
+            gamepad_events: Vec::new(),
+        }
+    }
+
+    /// Overwrites the input state with one made by [InputHandle::snapshot]
+    pub(crate) fn restore(&mut self, frame: &InputFrame) {
+        self.previous_keyboard_state = frame.previous_keyboard_state;
+        self.current_keyboard_state = frame.current_keyboard_state;
+        self.previous_modifier_state =
+            ModifiersState::from_bits_truncate(frame.previous_modifier_state);
+        self.modifier_state = ModifiersState::from_bits_truncate(frame.modifier_state);
+        self.previous_mouse_state = frame.previous_mouse_state;
+        self.current_mouse_state = frame.current_mouse_state;
+        self.current_text = frame.current_text.clone();
+        self.mouse_position = frame.mouse_position;
+        self.mouse_delta = frame.mouse_delta;
+        self.wheel_delta = frame.wheel_delta;
+        self.touches = frame.touches.clone();
     }
 
     pub(crate) fn process_input(&mut self, event: &WindowEvent) -> bool {
@@ -73,7 +143,20 @@ impl InputHandle {
                 self.mouse_position = pos;
                 true
             }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Vec2 { x: *x, y: *y },
+                    MouseScrollDelta::PixelDelta(position) => Vec2 {
+                        x: position.x as f32 / PIXELS_PER_LINE,
+                        y: position.y as f32 / PIXELS_PER_LINE,
+                    },
+                };
+
+                self.wheel_delta += delta;
+                true
+            }
             WindowEvent::ModifiersChanged(m) => self.process_modifiers(m),
+            WindowEvent::Touch(touch) => self.process_touch(touch),
             _ => false,
         }
     }
@@ -109,6 +192,94 @@ impl InputHandle {
         true
     }
 
+    fn process_touch(&mut self, event: &WinitTouch) -> bool {
+        let position = Vec2 {
+            x: event.location.x as f32,
+            y: event.location.y as f32,
+        };
+        let phase: TouchPhase = event.phase.into();
+
+        let started = match self.touches.iter_mut().find(|t| t.id == event.id) {
+            Some(touch) => {
+                touch.position = position;
+                touch.phase = phase;
+                touch.started |= phase == TouchPhase::Started;
+                touch.started
+            }
+            None => {
+                let started = phase == TouchPhase::Started;
+                self.touches.push(Touch {
+                    id: event.id,
+                    position,
+                    start_position: position,
+                    phase,
+                    started,
+                });
+                started
+            }
+        };
+
+        if self.emulate_mouse {
+            self.emulate_mouse_from_touch(event.id, position, phase, started);
+        }
+
+        true
+    }
+
+    fn emulate_mouse_from_touch(
+        &mut self,
+        id: u64,
+        position: Vec2<f32>,
+        phase: TouchPhase,
+        started: bool,
+    ) {
+        let left = MouseKey::Left as usize;
+
+        match phase {
+            TouchPhase::Started if self.primary_touch.is_none() => {
+                self.primary_touch = Some(id);
+                // the "mouse" teleports to where the finger went down
+                self.mouse_position = position;
+                self.current_mouse_state[left] = true;
+                self.release_touch_mouse = false;
+            }
+            TouchPhase::Moved if self.primary_touch == Some(id) => {
+                self.mouse_delta += position - self.mouse_position;
+                self.mouse_position = position;
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled if self.primary_touch == Some(id) => {
+                self.primary_touch = None;
+                self.mouse_position = position;
+                if started {
+                    self.release_touch_mouse = true;
+                } else {
+                    self.current_mouse_state[left] = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn set_touch_mouse_emulation(&mut self, emulate: bool) {
+        self.emulate_mouse = emulate;
+        let release_pending = std::mem::take(&mut self.release_touch_mouse);
+        if !emulate && (self.primary_touch.take().is_some() || release_pending) {
+            self.current_mouse_state[MouseKey::Left as usize] = false;
+        }
+    }
+
+    pub(crate) fn is_emulating_mouse(&self) -> bool {
+        self.emulate_mouse
+    }
+
+    pub(crate) fn get_touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
+    pub(crate) fn get_touch(&self, id: u64) -> Option<&Touch> {
+        self.touches.iter().find(|t| t.id == id)
+    }
+
     fn process_modifiers(&mut self, modifier: &Modifiers) -> bool {
         self.modifier_state = modifier.state();
 
@@ -135,6 +306,22 @@ impl InputHandle {
         self.previous_keyboard_state[index] && !self.current_keyboard_state[index]
     }
 
+    pub(crate) fn was_key_down(&self, key: Key) -> bool {
+        let index = key as usize;
+        self.previous_keyboard_state[index]
+    }
+
+    pub(crate) fn was_mouse_key_down(&self, key: MouseKey) -> bool {
+        let index = key as usize;
+        self.previous_mouse_state[index]
+    }
+
+    pub(crate) fn check_previous_modifiers(&self, modifer: ModifierKeys) -> bool {
+        let state: ModifiersState = modifer.into();
+
+        self.previous_modifier_state.intersects(state)
+    }
+
     pub(crate) fn is_mouse_key_down(&self, key: MouseKey) -> bool {
         let index = key as usize;
         self.current_mouse_state[index]
@@ -172,11 +359,72 @@ impl InputHandle {
     pub(crate) fn get_mouse_delta(&self) -> Vec2<f32> {
         self.mouse_delta
     }
+
+    pub(crate) fn get_wheel_delta(&self) -> Vec2<f32> {
+        self.wheel_delta
+    }
+}
+
+/// A finger on a touch screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Touch {
+    /// Unique for as long as the finger is down, ids may be reused afterwards
+    pub id: u64,
+    /// Where the finger is, in the same space as
+    /// [Engine::get_mouse_position](crate::engine_handle::Engine::get_mouse_position)
+    pub position: Vec2<f32>,
+    /// Where the finger first touched the screen
+    pub start_position: Vec2<f32>,
+    /// The last thing the finger did this frame, a quick tap can start and end in
+    /// the same frame so use [Touch::just_started] to catch every new touch
+    pub phase: TouchPhase,
+    // kept seperate from the phase so it survives a move or lift in the same frame
+    pub(crate) started: bool,
+}
+
+impl Touch {
+    /// Checks if the finger touched the screen this frame, even if it has
+    /// already moved or been lifted
+    pub fn just_started(&self) -> bool {
+        self.started
+    }
+
+    /// Checks if the finger was lifted or cancelled this frame
+    pub fn just_ended(&self) -> bool {
+        matches!(self.phase, TouchPhase::Ended | TouchPhase::Cancelled)
+    }
+}
+
+/// What a [Touch] has done during the current frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TouchPhase {
+    /// The finger touched the screen this frame
+    Started,
+    /// The finger moved this frame
+    Moved,
+    /// The finger is still on the screen but has not moved
+    Stationary,
+    /// The finger was lifted this frame, the touch is gone next frame
+    Ended,
+    /// The system cancelled the touch this frame, the touch is gone next frame
+    Cancelled,
+}
+
+impl From<WinitTouchPhase> for TouchPhase {
+    fn from(value: WinitTouchPhase) -> Self {
+        match value {
+            WinitTouchPhase::Started => Self::Started,
+            WinitTouchPhase::Moved => Self::Moved,
+            WinitTouchPhase::Ended => Self::Ended,
+            WinitTouchPhase::Cancelled => Self::Cancelled,
+        }
+    }
 }
 
 /// Representation of mouse buttons
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MouseKey {
     Left,
     Right,
@@ -202,6 +450,7 @@ impl From<MouseButton> for MouseKey {
 /// Representation of keyboard keys
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Key {
     Key1,
     Key2,
@@ -442,6 +691,7 @@ impl From<KeyCode> for Key {
 /// Note that these do not diffrentiate between sides so LeftShift will
 /// be considered the same as RightShift for modifiers. To get specific keys use [Key](Key::LeftAlt)
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ModifierKeys {
     Shift,
     Alt,
@@ -459,3 +709,115 @@ impl From<ModifierKeys> for ModifiersState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::dpi::PhysicalPosition;
+    use winit::event::DeviceId;
+
+    fn touch(input: &mut InputHandle, id: u64, phase: WinitTouchPhase, x: f64) {
+        input.process_input(&WindowEvent::Touch(WinitTouch {
+            device_id: DeviceId::dummy(),
+            phase,
+            location: PhysicalPosition::new(x, 10.0),
+            force: None,
+            id,
+        }));
+    }
+
+    #[test]
+    fn started_survives_a_move_in_the_same_frame() {
+        let mut input = InputHandle::new();
+        touch(&mut input, 1, WinitTouchPhase::Started, 0.0);
+        touch(&mut input, 1, WinitTouchPhase::Moved, 5.0);
+
+        let finger = input.get_touch(1).unwrap();
+        assert!(finger.just_started());
+        assert_eq!(finger.phase, TouchPhase::Moved);
+        assert_eq!(finger.start_position, Vec2 { x: 0.0, y: 10.0 });
+
+        input.end_of_frame_refresh();
+        let finger = input.get_touch(1).unwrap();
+        assert!(!finger.just_started());
+        assert_eq!(finger.phase, TouchPhase::Stationary);
+    }
+
+    #[test]
+    fn tap_in_one_frame_is_seen_then_removed() {
+        let mut input = InputHandle::new();
+        touch(&mut input, 1, WinitTouchPhase::Started, 0.0);
+        touch(&mut input, 1, WinitTouchPhase::Ended, 0.0);
+
+        let finger = input.get_touch(1).unwrap();
+        assert!(finger.just_started());
+        assert!(finger.just_ended());
+
+        input.end_of_frame_refresh();
+        assert!(input.get_touches().is_empty());
+    }
+
+    #[test]
+    fn emulated_tap_in_one_frame_clicks() {
+        let mut input = InputHandle::new();
+        input.set_touch_mouse_emulation(true);
+        let left = MouseKey::Left as usize;
+
+        touch(&mut input, 1, WinitTouchPhase::Started, 4.0);
+        touch(&mut input, 1, WinitTouchPhase::Ended, 4.0);
+        assert!(!input.previous_mouse_state[left] && input.current_mouse_state[left]);
+        assert_eq!(input.mouse_position, Vec2 { x: 4.0, y: 10.0 });
+
+        input.end_of_frame_refresh();
+        assert!(input.previous_mouse_state[left] && !input.current_mouse_state[left]);
+    }
+
+    #[test]
+    fn emulation_follows_only_the_first_finger() {
+        let mut input = InputHandle::new();
+        input.set_touch_mouse_emulation(true);
+        let left = MouseKey::Left as usize;
+
+        touch(&mut input, 1, WinitTouchPhase::Started, 0.0);
+        input.end_of_frame_refresh();
+        touch(&mut input, 2, WinitTouchPhase::Started, 50.0);
+        touch(&mut input, 1, WinitTouchPhase::Moved, 8.0);
+        assert_eq!(input.mouse_position, Vec2 { x: 8.0, y: 10.0 });
+        assert_eq!(input.mouse_delta, Vec2 { x: 8.0, y: 0.0 });
+
+        input.end_of_frame_refresh();
+        touch(&mut input, 1, WinitTouchPhase::Ended, 8.0);
+        assert!(!input.current_mouse_state[left]);
+    }
+
+    fn scroll(input: &mut InputHandle, delta: MouseScrollDelta) {
+        input.process_input(&WindowEvent::MouseWheel {
+            device_id: DeviceId::dummy(),
+            delta,
+            phase: WinitTouchPhase::Moved,
+        });
+    }
+
+    #[test]
+    fn wheel_lines_and_pixels_add_up_then_reset() {
+        let mut input = InputHandle::new();
+        // lines are kept as they are, up and left are positive like in winit
+        scroll(&mut input, MouseScrollDelta::LineDelta(1.0, -2.0));
+        assert_eq!(input.get_wheel_delta(), Vec2 { x: 1.0, y: -2.0 });
+
+        // trackpads report pixels which are turned into lines with the same signs
+        let pixels = PhysicalPosition::new(-2.0 * PIXELS_PER_LINE as f64, 10.0);
+        scroll(&mut input, MouseScrollDelta::PixelDelta(pixels));
+        let half_line = 10.0 / PIXELS_PER_LINE;
+        assert_eq!(
+            input.get_wheel_delta(),
+            Vec2 {
+                x: -1.0,
+                y: -2.0 + half_line
+            }
+        );
+
+        input.end_of_frame_refresh();
+        assert_eq!(input.get_wheel_delta(), Vec2 { x: 0.0, y: 0.0 });
+    }
+}
@@ -40,20 +40,30 @@
 //! }
 #![allow(clippy::needless_doctest_main)]
 
+pub mod action;
+pub mod animation;
+pub mod archive;
 pub mod buffer;
 pub mod camera;
 pub mod colour;
 mod context;
 pub mod engine_handle;
+pub mod gamepad;
 pub mod input;
 mod layouts;
 pub mod material;
 pub mod matrix_math;
+pub mod particles;
+pub mod post_processing;
 pub mod render;
+pub mod replay;
 pub mod resource;
 pub mod shader;
+pub mod sound;
+pub mod sprite;
 pub mod text;
 pub mod texture;
+pub mod tilemap;
 pub mod vectors;
 mod vertex;
 
@@ -62,6 +72,7 @@ pub use mint;
 
 use engine_handle::Engine;
 use render::RenderHandle;
+use resource::LoadEvent;
 use vectors::Vec2;
 /// The Trait needed for structs to be used in with the Engine
 pub trait Game {
@@ -69,9 +80,19 @@ pub trait Game {
     fn render<'o>(&'o mut self, render_handle: RenderHandle<'o>);
     /// Updating code goes here
     fn update(&mut self, engine_handle: &mut Engine);
+    /// Called at a fixed rate before [Game::update] when the engine was built with
+    /// [EngineBuilder::set_fixed_update_rate](engine_handle::EngineBuilder::set_fixed_update_rate).
+    /// Each call simulates [Engine::get_fixed_timestep](engine_handle::Engine::get_fixed_timestep)
+    /// seconds. Input is updated once per frame so the same key press can be seen by
+    /// several fixed updates, or none if no fixed update happens that frame
+    fn fixed_update(&mut self, _engine_handle: &mut Engine) {}
     /// Things to do when the window closes
     fn on_close(&self) {}
     fn on_resize(&mut self, _new_window_size: Vec2<u32>, _engine_handle: &mut Engine) {}
+    /// Called whenever a resource finishes loading or fails to load, failed resources
+    /// have already been replaced with a defualt so this is just for letting the player
+    /// know something is wrong or for tracking loading yourself
+    fn on_resource_loaded(&mut self, _event: LoadEvent, _engine_handle: &mut Engine) {}
 }
 
 #[rustfmt::skip]
@@ -10,21 +10,25 @@
 //!         self.default_material.draw(&mut renderer);
 //!     }
 //! }
+use std::collections::HashSet;
 use std::f32::consts::PI;
 use std::marker::PhantomData;
 
 use encase::private::WriteInto;
 use encase::ShaderType;
+use wgpu::util::DeviceExt;
 
+use crate::animation::Animation;
 use crate::colour::Colour;
 use crate::context::WgpuClump;
 use crate::engine_handle::Engine;
-use crate::render::Renderer;
+use crate::render::{BlendMode, Renderer};
 use crate::resource::ResourceId;
 use crate::shader::{Shader, UniformData, UniformError};
+use crate::sprite::SpriteSheet;
 use crate::texture::{Texture, UniformTexture};
 use crate::vectors::Vec2;
-use crate::vertex::{self, LineVertex, Vertex};
+use crate::vertex::{self, InstanceVertex, LineVertex, Vertex};
 
 /// A material represents a unique combination of a Texture
 /// and Shader, while also containing all nessicary buffers.
@@ -41,6 +45,13 @@ pub struct Material<T = ()> {
     pub(crate) index_size: u64,
     inner: Option<InnerBuffer>,
     texture_id: ResourceId<Texture>,
+    blend_mode: Option<BlendMode>,
+    // batched materials keep their shapes on the CPU untill they are
+    // handed to the renderer's batch
+    staged: Option<StagedShapes>,
+    layer: i32,
+    // sprite frames that were not found, so the warning is only logged once
+    missing_frames: HashSet<String>,
     _marker: PhantomData<T>,
 }
 
@@ -67,6 +78,10 @@ impl<T> Material<T> {
             index_size,
             inner: None,
             texture_id,
+            blend_mode: builder.blend_mode,
+            staged: builder.batched.then(StagedShapes::default),
+            layer: builder.layer,
+            missing_frames: HashSet::new(),
             _marker: PhantomData,
         }
     }
@@ -76,6 +91,22 @@ impl<T> Material<T> {
         self.texture_id = texture
     }
 
+    /// Changes how the material blends with what has already been drawn. `None` uses
+    /// the blend mode of the material's shader.
+    pub fn change_blend_mode(&mut self, blend_mode: Option<BlendMode>) {
+        self.blend_mode = blend_mode
+    }
+
+    /// Changes the layer the material is drawn on, see [MaterialBuilder::set_layer]
+    pub fn change_layer(&mut self, layer: i32) {
+        self.layer = layer
+    }
+
+    /// Gets the layer the material is drawn on
+    pub fn get_layer(&self) -> i32 {
+        self.layer
+    }
+
     /// Will queue a Rectangle to be draw.
     pub fn add_rectangle(
         &mut self,
@@ -134,6 +165,51 @@ impl<T> Material<T> {
         self.push_rectangle(wgpu, verts);
     }
 
+    /// Queues a rectangle showing a named frame of a [SpriteSheet]. The material's texture should be
+    /// the image the sprite sheet was made for. Nothing is drawn while the sprite sheet is loading
+    /// or if it has no frame with that name, a missing frame is only logged the first time.
+    pub fn add_sprite(
+        &mut self,
+        sheet: &ResourceId<SpriteSheet>,
+        frame: &str,
+        position: Vec2<f32>,
+        size: Vec2<f32>,
+        colour: Colour,
+        render: &Renderer,
+    ) {
+        let Some(sheet) = render.resources.get_sprite_sheet(sheet) else {
+            return;
+        };
+
+        match sheet.get_frame(frame) {
+            Some(frame) => {
+                let (uv_position, uv_size) = (frame.position, frame.size);
+                self.add_rectangle_with_uv(position, size, uv_position, uv_size, colour, render);
+            }
+            None if !self.missing_frames.contains(frame) => {
+                log::warn!("sprite sheet has no frame named: {}", frame);
+                self.missing_frames.insert(frame.to_string());
+            }
+            None => {}
+        }
+    }
+
+    /// Queues a rectangle showing the current frame of an [Animation]. The material's texture should
+    /// be the image the animation's frames are on.
+    pub fn add_animation(
+        &mut self,
+        animation: &Animation,
+        position: Vec2<f32>,
+        size: Vec2<f32>,
+        colour: Colour,
+        render: &Renderer,
+    ) {
+        if let Some(frame) = animation.get_current_frame() {
+            let (uv_position, uv_size) = (frame.uv_position, frame.uv_size);
+            self.add_rectangle_with_uv(position, size, uv_position, uv_size, colour, render);
+        }
+    }
+
     /// Queues a rectangle that will be rotated around its centerpoint. Rotation is in degrees
     pub fn add_rectangle_with_rotation(
         &mut self,
@@ -288,21 +364,6 @@ impl<T> Material<T> {
             return;
         }
 
-        if self.inner.is_none() {
-            let (vert, ind) = Self::create_buffers(
-                &render.wgpu.device,
-                self.vertex_size,
-                50,
-                self.index_size,
-                50,
-            );
-
-            self.inner = Some(InnerBuffer {
-                vertex_buffer: vert,
-                index_buffer: ind,
-            });
-        }
-
         let wgpu = render.wgpu;
 
         let vertices = (0..number_of_sides)
@@ -313,9 +374,28 @@ impl<T> Material<T> {
             .map(|point| Vertex::from_2d([point.x, point.y], [0.0, 0.0], colour.as_raw()))
             .collect::<Vec<Vertex>>();
 
-        let number_of_vertices = self.get_vertex_number() as u16;
         let number_of_triangles = (number_of_sides - 2) as u16;
 
+        if self.staged.is_some() {
+            let indicies = (1..number_of_triangles + 1)
+                .flat_map(|i| [0, i, i + 1])
+                .collect::<Vec<u16>>();
+            self.stage(&vertices, &indicies);
+            return;
+        }
+
+        if self.inner.is_none() {
+            let (vert, ind) =
+                Self::create_buffers(&wgpu.device, self.vertex_size, 50, self.index_size, 50);
+
+            self.inner = Some(InnerBuffer {
+                vertex_buffer: vert,
+                index_buffer: ind,
+            });
+        }
+
+        let number_of_vertices = self.get_vertex_number() as u16;
+
         let mut indicies = (1..number_of_triangles + 1)
             .flat_map(|i| {
                 [
@@ -455,6 +535,11 @@ impl<T> Material<T> {
     }
 
     fn push_rectangle(&mut self, wgpu: &WgpuClump, verts: [Vertex; 4]) {
+        if self.staged.is_some() {
+            self.stage(&verts, &[0, 1, 2, 3, 0, 2]);
+            return;
+        }
+
         if self.inner.is_none() {
             let (vert, ind) =
                 Self::create_buffers(&wgpu.device, self.vertex_size, 50, self.index_size, 50);
@@ -512,6 +597,11 @@ impl<T> Material<T> {
     }
 
     fn push_triangle(&mut self, wgpu: &WgpuClump, verts: [Vertex; 3]) {
+        if self.staged.is_some() {
+            self.stage(&verts, &[0, 1, 2]);
+            return;
+        }
+
         if self.inner.is_none() {
             let (vert, ind) =
                 Self::create_buffers(&wgpu.device, self.vertex_size, 50, self.index_size, 50);
@@ -570,13 +660,57 @@ impl<T> Material<T> {
         self.index_count += 6 * self.index_size;
     }
 
+    // indicies are relative to the first of the vertices
+    fn stage(&mut self, vertices: &[Vertex], indicies: &[u16]) {
+        let staged = self.staged.as_mut().unwrap();
+        staged.vertices.extend_from_slice(vertices);
+        staged
+            .indicies
+            .extend(indicies.iter().map(|&index| index as u32));
+
+        self.vertex_count += vertices.len() as u64 * self.vertex_size;
+        self.index_count += indicies.len() as u64 * self.index_size;
+    }
+
+    pub(crate) fn texture_size_from(&self, render: &Renderer) -> Option<Vec2<f32>> {
+        render
+            .resources
+            .get_texture(&self.texture_id)
+            .map(|t| t.size)
+    }
+
     // there where 'others: 'pass notation says that 'others lives longer than 'pass
-    /// Draws all queued shapes to the screen.
+    /// Draws all queued shapes to the screen. Batched materials instead add their shapes
+    /// to the renderer's batch which is drawn by [Renderer::flush_batch].
     pub fn draw<'others>(&'others mut self, information: &mut Renderer<'_, 'others>) {
         if self.vertex_count == 0 {
             return;
         }
 
+        if let Some(staged) = &mut self.staged {
+            let resources = information.resources;
+            if resources.get_pipeline(&self.pipeline_id).is_none()
+                || resources.get_texture(&self.texture_id).is_none()
+            {
+                return;
+            }
+
+            information.batch.add(
+                self.layer,
+                self.pipeline_id,
+                self.blend_mode,
+                self.texture_id,
+                &staged.vertices,
+                &staged.indicies,
+            );
+
+            staged.vertices.clear();
+            staged.indicies.clear();
+            self.vertex_count = 0;
+            self.index_count = 0;
+            return;
+        }
+
         // returns early bc stuff inst loaded so we just ignore it ! :3
         let Some(shader) = information.resources.get_pipeline(&self.pipeline_id) else {
             return;
@@ -593,9 +727,10 @@ impl<T> Material<T> {
         // some data put in which means this should be Some(T)
         let buffers = self.inner.as_ref().unwrap();
 
-        shader.set_active(information);
+        information.pass.set_layer(self.layer);
+        shader.set_active(information, self.blend_mode);
 
-        information.pass.set_bind_group(0, texture, &[]);
+        information.pass.set_bind_group(0, texture);
 
         information
             .pass
@@ -665,6 +800,9 @@ pub struct MaterialBuilder<T> {
     // in the case of a texture the defualt is just the White_Pixel
     texture_change: Option<ResourceId<Texture>>,
     shader_change: Option<ResourceId<Shader>>,
+    blend_mode: Option<BlendMode>,
+    batched: bool,
+    layer: i32,
     _marker: PhantomData<T>,
 }
 
@@ -675,6 +813,9 @@ impl<T> MaterialBuilder<T> {
         Self {
             texture_change: None,
             shader_change: None,
+            blend_mode: None,
+            batched: false,
+            layer: 0,
             _marker: PhantomData,
         }
     }
@@ -684,6 +825,9 @@ impl<T> MaterialBuilder<T> {
         Self {
             texture_change: Some(texture),
             shader_change: self.shader_change,
+            blend_mode: self.blend_mode,
+            batched: self.batched,
+            layer: self.layer,
             _marker: PhantomData,
         }
     }
@@ -693,6 +837,9 @@ impl<T> MaterialBuilder<T> {
         Self {
             texture_change: self.texture_change,
             shader_change: Some(shader),
+            blend_mode: self.blend_mode,
+            batched: self.batched,
+            layer: self.layer,
             _marker: PhantomData,
         }
     }
@@ -705,6 +852,57 @@ impl<T> MaterialBuilder<T> {
         MaterialBuilder {
             texture_change: self.texture_change,
             shader_change: self.shader_change,
+            blend_mode: self.blend_mode,
+            batched: self.batched,
+            layer: self.layer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets how the Material blends with what has already been drawn. This overrides the
+    /// blend mode of the shader, which is [BlendMode::Alpha] unless it was changed in
+    /// [ShaderOptions](crate::shader::ShaderOptions::with_blend_mode)
+    pub fn set_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self {
+            texture_change: self.texture_change,
+            shader_change: self.shader_change,
+            blend_mode: Some(blend_mode),
+            batched: self.batched,
+            layer: self.layer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Batched materials do not draw straight away, instead their shapes are collected by
+    /// the [Renderer] and drawn alongside every other batched material using the same layer,
+    /// shader, blend mode and texture. This is much faster when there are lots of small materials,
+    /// but everything batched is drawn on top of anything on the same layer drawn before
+    /// [Renderer::flush_batch]. This has nothing to do with [MaterialBuilder::set_layer],
+    /// materials that are not batched are still drawn on their layer.
+    pub fn set_batched(self, batched: bool) -> Self {
+        Self {
+            texture_change: self.texture_change,
+            shader_change: self.shader_change,
+            blend_mode: self.blend_mode,
+            batched,
+            layer: self.layer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the layer the material is drawn on. Everything drawn with a [Renderer] is
+    /// sorted so lower layers are drawn first and higher layers end up on top, no matter
+    /// what order things were drawn in or which camera was active. Things on the same layer
+    /// are drawn in the order they were drawn in, apart from
+    /// [batched](MaterialBuilder::set_batched) shapes which all end up on top of their layer.
+    /// The defualt layer is 0.
+    pub fn set_layer(self, layer: i32) -> Self {
+        Self {
+            texture_change: self.texture_change,
+            shader_change: self.shader_change,
+            blend_mode: self.blend_mode,
+            batched: self.batched,
+            layer,
             _marker: PhantomData,
         }
     }
@@ -713,6 +911,13 @@ impl<T> MaterialBuilder<T> {
     pub fn build(self, engine_handle: &mut Engine) -> Material<T> {
         Material::from_builder(self, engine_handle)
     }
+
+    /// Turns the builder into an [InstancedMaterial]. A custom shader has to be made
+    /// with [ShaderOptions::with_instancing](crate::shader::ShaderOptions::with_instancing),
+    /// batching does not apply to instanced materials.
+    pub fn build_instanced(self, engine_handle: &mut Engine) -> InstancedMaterial<T> {
+        InstancedMaterial::from_builder(self, engine_handle)
+    }
 }
 
 impl<T> Default for MaterialBuilder<T> {
@@ -721,6 +926,275 @@ impl<T> Default for MaterialBuilder<T> {
     }
 }
 
+/// A material that draws many copies of the same quad in a single draw call. Each
+/// instance has its own position, size, rotation, UV rectangle and tint, which makes
+/// this great for particles, bullets and anything else there are thousands of.
+/// ```rust,no_run
+/// # use bottomless_pit::colour::Colour;
+/// # use bottomless_pit::engine_handle::EngineBuilder;
+/// # use bottomless_pit::material::{InstancedMaterial, MaterialBuilder};
+/// # use bottomless_pit::render::Renderer;
+/// # use bottomless_pit::vectors::Vec2;
+/// let mut engine = EngineBuilder::new().build().unwrap();
+/// let mut bullets: InstancedMaterial = MaterialBuilder::new().build_instanced(&mut engine);
+/// # fn render<'o>(bullets: &'o mut InstancedMaterial, mut renderer: Renderer<'o, 'o>) {
+/// // later inside of Game::render
+/// for i in 0..1000 {
+///     let position = Vec2 { x: (i % 40) as f32 * 20.0, y: (i / 40) as f32 * 20.0 };
+///     bullets.add_instance(position, Vec2 { x: 8.0, y: 8.0 }, Colour::RED);
+/// }
+/// bullets.draw(&mut renderer);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InstancedMaterial<T = ()> {
+    pipeline_id: ResourceId<Shader>,
+    texture_id: ResourceId<Texture>,
+    blend_mode: Option<BlendMode>,
+    instances: Vec<InstanceVertex>,
+    inner: Option<InstanceBuffers>,
+    layer: i32,
+    _marker: PhantomData<T>,
+}
+
+impl<T> InstancedMaterial<T> {
+    fn from_builder(builder: MaterialBuilder<T>, engine: &mut Engine) -> Self {
+        let pipeline_id = match builder.shader_change {
+            Some(rs) => rs,
+            None => engine.instanced_pipe_id(),
+        };
+
+        let texture_id = builder
+            .texture_change
+            .unwrap_or(engine.defualt_material_bg_id());
+
+        Self {
+            pipeline_id,
+            texture_id,
+            blend_mode: builder.blend_mode,
+            instances: Vec::new(),
+            inner: None,
+            layer: builder.layer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Swaps out the inner texture of the material
+    pub fn change_texture(&mut self, texture: ResourceId<Texture>) {
+        self.texture_id = texture
+    }
+
+    /// Changes how the material blends with what has already been drawn. `None` uses
+    /// the blend mode of the material's shader.
+    pub fn change_blend_mode(&mut self, blend_mode: Option<BlendMode>) {
+        self.blend_mode = blend_mode
+    }
+
+    /// Changes the layer the material is drawn on, see [MaterialBuilder::set_layer]
+    pub fn change_layer(&mut self, layer: i32) {
+        self.layer = layer
+    }
+
+    /// Gets the layer the instances are drawn on
+    pub fn get_layer(&self) -> i32 {
+        self.layer
+    }
+
+    /// Queues an instance showing the whole texture
+    pub fn add_instance(&mut self, position: Vec2<f32>, size: Vec2<f32>, colour: Colour) {
+        self.instances.push(InstanceVertex {
+            position: position.to_raw(),
+            size: size.to_raw(),
+            rotation: 0.0,
+            uv_position: [0.0, 0.0],
+            uv_size: [1.0, 1.0],
+            colour: colour.as_raw(),
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Queues an instance with both UV and rotation. The UV cordinates are in pixels
+    /// of the texture and the rotation is in degrees around the center of the instance.
+    pub fn add_instance_ex(
+        &mut self,
+        position: Vec2<f32>,
+        size: Vec2<f32>,
+        colour: Colour,
+        rotation: f32,
+        uv_position: Vec2<f32>,
+        uv_size: Vec2<f32>,
+        render: &Renderer,
+    ) {
+        // doesnt matter what i put here bc the texture isnt loaded regardless
+        let texture_size = render
+            .resources
+            .get_texture(&self.texture_id)
+            .map(|t| t.size)
+            .unwrap_or(Vec2 { x: 1.0, y: 1.0 });
+
+        self.instances.push(InstanceVertex {
+            position: position.to_raw(),
+            size: size.to_raw(),
+            rotation,
+            uv_position: (uv_position / texture_size).to_raw(),
+            uv_size: (uv_size / texture_size).to_raw(),
+            colour: colour.as_raw(),
+        });
+    }
+
+    pub(crate) fn texture_size_from(&self, render: &Renderer) -> Option<Vec2<f32>> {
+        render
+            .resources
+            .get_texture(&self.texture_id)
+            .map(|t| t.size)
+    }
+
+    /// Returns the number of instances queued
+    pub fn get_instance_number(&self) -> u64 {
+        self.instances.len() as u64
+    }
+
+    /// Returns the size of the texture in pixels.
+    /// Returns None when the texture is not loaded yet
+    pub fn get_texture_size(&self, engine: &Engine) -> Option<Vec2<f32>> {
+        engine
+            .resource_manager
+            .get_texture(&self.texture_id)
+            .map(|t| t.size)
+    }
+
+    /// Draws every queued instance in one draw call
+    pub fn draw<'others>(&'others mut self, information: &mut Renderer<'_, 'others>) {
+        if self.instances.is_empty() {
+            return;
+        }
+
+        // returns early bc stuff inst loaded so we just ignore it ! :3
+        let Some(shader) = information.resources.get_pipeline(&self.pipeline_id) else {
+            return;
+        };
+
+        let Some(texture) = information
+            .resources
+            .get_texture(&self.texture_id)
+            .map(|t| &t.bind_group)
+        else {
+            return;
+        };
+
+        if !shader.is_instanced() {
+            log::warn!(
+                "instanced materials need a shader made with ShaderOptions::with_instancing"
+            );
+            self.instances.clear();
+            return;
+        }
+
+        let wgpu = information.wgpu;
+        let buffers = self
+            .inner
+            .get_or_insert_with(|| InstanceBuffers::new(&wgpu.device));
+
+        let instance_bytes: &[u8] = bytemuck::cast_slice(&self.instances);
+        if instance_bytes.len() as u64 > buffers.instance_buffer.size() {
+            // everything is rewritten each frame so the old data does not need copying
+            buffers.instance_buffer = InstanceBuffers::make_instance_buffer(
+                &wgpu.device,
+                instance_bytes.len() as u64 * 2,
+            );
+        }
+        wgpu.queue
+            .write_buffer(&buffers.instance_buffer, 0, instance_bytes);
+
+        information.pass.set_layer(self.layer);
+        shader.set_active(information, self.blend_mode);
+
+        information.pass.set_bind_group(0, texture);
+        information
+            .pass
+            .set_vertex_buffer(0, buffers.quad_buffer.slice(..));
+        information.pass.set_vertex_buffer(
+            1,
+            buffers
+                .instance_buffer
+                .slice(0..instance_bytes.len() as u64),
+        );
+        information
+            .pass
+            .set_index_buffer(buffers.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+
+        information
+            .pass
+            .draw_indexed(0..6, 0, 0..self.instances.len() as u32);
+
+        self.instances.clear();
+    }
+}
+
+impl<T: ShaderType + WriteInto> InstancedMaterial<T> {
+    /// Attempts to update the uniform data held within in the shader.
+    /// This will fail in the event that the shader has not loaded yet or
+    /// if the shader used to create the material never had any UniformData,
+    pub fn update_uniform_data(&self, data: &T, engine: &Engine) -> Result<(), UniformError> {
+        let options = match engine.resource_manager.get_pipeline(&self.pipeline_id) {
+            Some(shader) => shader,
+            None => return Err(UniformError::NotLoadedYet),
+        };
+
+        options.update_uniform_data(data, engine)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct InstanceBuffers {
+    // a single quad from (0, 0) to (1, 1) shared by every instance
+    quad_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+}
+
+impl InstanceBuffers {
+    fn new(device: &wgpu::Device) -> Self {
+        let quad = vertex::from_pixels(
+            Vec2 { x: 0.0, y: 0.0 },
+            Vec2 { x: 1.0, y: 1.0 },
+            Colour::WHITE.as_raw(),
+        );
+        // extra index keeps the buffer aligned to 4 bytes
+        let indicies: [u16; 8] = [0, 1, 2, 3, 0, 2, 0, 0];
+
+        let quad_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instanced Quad Buffer"),
+            contents: bytemuck::cast_slice(&quad),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instanced Index Buffer"),
+            contents: bytemuck::cast_slice(&indicies),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let instance_size = std::mem::size_of::<InstanceVertex>() as u64;
+
+        Self {
+            quad_buffer,
+            index_buffer,
+            instance_buffer: Self::make_instance_buffer(device, instance_size * 50),
+        }
+    }
+
+    fn make_instance_buffer(device: &wgpu::Device, size: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance_Buffer"),
+            size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+}
+
 /// A diffrent type of material used to draw WebGPU debug
 /// lines. These lines will allways be 1px wide and only one
 /// instance of this material should ever be made per programm.
@@ -729,6 +1203,7 @@ pub struct LineMaterial {
     vertex_buffer: Option<wgpu::Buffer>,
     vertex_count: u64,
     vertex_size: u64,
+    layer: i32,
 }
 
 impl LineMaterial {
@@ -741,9 +1216,20 @@ impl LineMaterial {
             vertex_buffer: None,
             vertex_count: 0,
             vertex_size,
+            layer: 0,
         }
     }
 
+    /// Changes the layer the lines are drawn on, see [MaterialBuilder::set_layer]
+    pub fn change_layer(&mut self, layer: i32) {
+        self.layer = layer
+    }
+
+    /// Gets the layer the lines are drawn on
+    pub fn get_layer(&self) -> i32 {
+        self.layer
+    }
+
     /// Queues a line from the two points.
     pub fn add_line(
         &mut self,
@@ -845,10 +1331,11 @@ impl LineMaterial {
 
         let buffer = self.vertex_buffer.as_ref().unwrap();
 
+        information.pass.set_layer(self.layer);
         information.pass.set_pipeline(pipeline);
         information
             .pass
-            .set_bind_group(0, information.camera_bindgroup, &[]);
+            .set_bind_group(0, information.camera_bindgroup);
         information
             .pass
             .set_vertex_buffer(0, buffer.slice(0..self.vertex_count));
@@ -894,6 +1381,12 @@ pub(crate) fn grow_buffer(
     *buffer = new_buffer;
 }
 
+#[derive(Debug, Default)]
+struct StagedShapes {
+    vertices: Vec<Vertex>,
+    indicies: Vec<u32>,
+}
+
 #[derive(Debug)]
 struct InnerBuffer {
     vertex_buffer: wgpu::Buffer,
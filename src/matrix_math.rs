@@ -6,7 +6,7 @@ pub fn pixels_to_screenspace(mut point: Vec2<f32>, screen_size: Vec2<u32>) -> Ve
     let width = screen_size.x as f32;
     let height = screen_size.y as f32;
     point.x = (2.0 * point.x / width) - 1.0;
-    point.y = ((2.0 * point.y / height) - 1.0) * -1.0;
+    point.y = -((2.0 * point.y / height) - 1.0);
 
     point
 }
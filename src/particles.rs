@@ -0,0 +1,590 @@
+//! Contains the [ParticleEmitter] which spawns, moves and draws particles
+//! ```rust,no_run
+//! # use bottomless_pit::colour::Colour;
+//! # use bottomless_pit::engine_handle::Engine;
+//! # use bottomless_pit::material::InstancedMaterial;
+//! # use bottomless_pit::particles::{Burst, ParticleEmitter};
+//! # use bottomless_pit::render::Renderer;
+//! # use bottomless_pit::vectors::Vec2;
+//! let mut sparks = ParticleEmitter::new(Vec2 { x: 200.0, y: 200.0 }, 1234)
+//!     .with_rate(50.0)
+//!     .with_lifetime(0.5, 1.5)
+//!     .with_velocity(Vec2 { x: -80.0, y: -200.0 }, Vec2 { x: 80.0, y: -120.0 })
+//!     .with_acceleration(Vec2 { x: 0.0, y: 300.0 }, Vec2 { x: 0.0, y: 300.0 })
+//!     .with_colour(Colour::YELLOW, Colour::from_rgba(255.0, 0.0, 0.0, 0.0))
+//!     .with_size(Vec2 { x: 6.0, y: 6.0 }, Vec2 { x: 1.0, y: 1.0 })
+//!     .with_burst(Burst::new(0.0, 30));
+//! # fn update(sparks: &mut ParticleEmitter, engine_handle: &Engine) {
+//! // every update
+//! sparks.update(engine_handle);
+//! # }
+//! # fn render<'o>(sparks: &ParticleEmitter, material: &'o mut InstancedMaterial, mut renderer: Renderer<'o, 'o>) {
+//! // and in render
+//! sparks.add_to_instanced(material, &renderer);
+//! material.draw(&mut renderer);
+//! # }
+//! ```
+//! Two emitters made with the same seed and settings that are updated with the same
+//! delta times will always make exactly the same particles.
+
+use crate::colour::Colour;
+use crate::engine_handle::Engine;
+use crate::material::{InstancedMaterial, Material};
+use crate::render::Renderer;
+use crate::vectors::Vec2;
+
+/// A single particle made by a [ParticleEmitter]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    /// The center of the particle
+    pub position: Vec2<f32>,
+    pub velocity: Vec2<f32>,
+    pub acceleration: Vec2<f32>,
+    /// Rotation in degrees
+    pub rotation: f32,
+    /// How fast the particle spins in degrees per second
+    pub angular_velocity: f32,
+    /// How long the particle has been alive in seconds
+    pub age: f32,
+    /// How long the particle lives for in seconds
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// How far through its life the particle is from 0.0 to 1.0
+    pub fn get_life_fraction(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+/// Spawns a group of particles all at once, either once or repeating
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Burst {
+    /// Seconds after the emitter starts that the burst happens
+    pub time: f32,
+    pub count: u32,
+    /// Seconds between each repeat of the burst, None only bursts once
+    pub interval: Option<f32>,
+}
+
+impl Burst {
+    /// A burst that happens once
+    pub fn new(time: f32, count: u32) -> Self {
+        Self {
+            time,
+            count,
+            interval: None,
+        }
+    }
+
+    /// Repeats the burst every interval seconds after its first time
+    pub fn repeating(self, interval: f32) -> Self {
+        Self {
+            interval: Some(interval),
+            ..self
+        }
+    }
+}
+
+/// Spawns particles over time and in bursts. Every setting that takes a minimum and
+/// maximum picks a random value between them for each particle.
+#[derive(Clone, Debug)]
+pub struct ParticleEmitter {
+    /// Where new particles are spawned
+    pub position: Vec2<f32>,
+    rate: f32,
+    max_particles: usize,
+    spawn_area: Vec2<f32>,
+    lifetime: (f32, f32),
+    velocity: (Vec2<f32>, Vec2<f32>),
+    acceleration: (Vec2<f32>, Vec2<f32>),
+    rotation: (f32, f32),
+    angular_velocity: (f32, f32),
+    colour: (Colour, Colour),
+    size: (Vec2<f32>, Vec2<f32>),
+    uv: Option<(Vec2<f32>, Vec2<f32>)>,
+    bursts: Vec<Burst>,
+    emitting: bool,
+    particles: Vec<Particle>,
+    // time that has passed without enough for a whole particle
+    rate_accumulator: f32,
+    elapsed: f32,
+    seed: u64,
+    rng: Rng,
+}
+
+impl ParticleEmitter {
+    /// Creates an emitter that spawns 10 particles a second that stay still for one second.
+    /// The seed decides all the random values so the same seed always gives the same particles.
+    pub fn new(position: Vec2<f32>, seed: u64) -> Self {
+        Self {
+            position,
+            rate: 10.0,
+            max_particles: 1000,
+            spawn_area: Vec2 { x: 0.0, y: 0.0 },
+            lifetime: (1.0, 1.0),
+            velocity: (Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 0.0, y: 0.0 }),
+            acceleration: (Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 0.0, y: 0.0 }),
+            rotation: (0.0, 0.0),
+            angular_velocity: (0.0, 0.0),
+            colour: (Colour::WHITE, Colour::WHITE),
+            size: (Vec2 { x: 4.0, y: 4.0 }, Vec2 { x: 4.0, y: 4.0 }),
+            uv: None,
+            bursts: Vec::new(),
+            emitting: true,
+            particles: Vec::new(),
+            rate_accumulator: 0.0,
+            elapsed: 0.0,
+            seed,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// How many particles are spawned per second, 0.0 only spawns bursts
+    pub fn with_rate(self, rate: f32) -> Self {
+        Self {
+            rate: rate.max(0.0),
+            ..self
+        }
+    }
+
+    /// The most particles that can be alive at once, new particles are not
+    /// spawned while at the limit
+    pub fn with_max_particles(self, max_particles: usize) -> Self {
+        Self {
+            max_particles,
+            ..self
+        }
+    }
+
+    /// Spawns particles anywhere inside of a rectangle of this size centered on the position
+    pub fn with_spawn_area(self, spawn_area: Vec2<f32>) -> Self {
+        Self { spawn_area, ..self }
+    }
+
+    /// How long each particle lives for in seconds
+    pub fn with_lifetime(self, min: f32, max: f32) -> Self {
+        Self {
+            lifetime: (min, max),
+            ..self
+        }
+    }
+
+    /// The starting velocity of each particle in pixels per second
+    pub fn with_velocity(self, min: Vec2<f32>, max: Vec2<f32>) -> Self {
+        Self {
+            velocity: (min, max),
+            ..self
+        }
+    }
+
+    /// The acceleration of each particle in pixels per second squared, use this for gravity
+    pub fn with_acceleration(self, min: Vec2<f32>, max: Vec2<f32>) -> Self {
+        Self {
+            acceleration: (min, max),
+            ..self
+        }
+    }
+
+    /// The starting rotation of each particle in degrees
+    pub fn with_rotation(self, min: f32, max: f32) -> Self {
+        Self {
+            rotation: (min, max),
+            ..self
+        }
+    }
+
+    /// How fast each particle spins in degrees per second
+    pub fn with_angular_velocity(self, min: f32, max: f32) -> Self {
+        Self {
+            angular_velocity: (min, max),
+            ..self
+        }
+    }
+
+    /// Particles fade from the start colour to the end colour over their life
+    pub fn with_colour(self, start: Colour, end: Colour) -> Self {
+        Self {
+            colour: (start, end),
+            ..self
+        }
+    }
+
+    /// Particles grow or shrink from the start size to the end size over their life
+    pub fn with_size(self, start: Vec2<f32>, end: Vec2<f32>) -> Self {
+        Self {
+            size: (start, end),
+            ..self
+        }
+    }
+
+    /// Draws each particle with part of the material's texture, in pixels
+    pub fn with_uv(self, uv_position: Vec2<f32>, uv_size: Vec2<f32>) -> Self {
+        Self {
+            uv: Some((uv_position, uv_size)),
+            ..self
+        }
+    }
+
+    /// Adds a burst of particles, these happen even when the rate is 0.0
+    pub fn with_burst(mut self, burst: Burst) -> Self {
+        self.bursts.push(burst);
+        self
+    }
+
+    /// Moves the emitter forward by [Engine::get_frame_delta_time]
+    pub fn update(&mut self, engine: &Engine) {
+        self.advance(engine.get_frame_delta_time());
+    }
+
+    /// Moves the emitter forward by an amount of seconds, use this inside of
+    /// [Game::fixed_update](crate::Game::fixed_update) with the fixed timestep
+    pub fn advance(&mut self, delta_time: f32) {
+        let step = Vec2 {
+            x: delta_time,
+            y: delta_time,
+        };
+        for particle in self.particles.iter_mut() {
+            particle.age += delta_time;
+            particle.velocity += particle.acceleration * step;
+            particle.position += particle.velocity * step;
+            particle.rotation += particle.angular_velocity * delta_time;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        if !self.emitting {
+            return;
+        }
+
+        let start = self.elapsed;
+        self.elapsed += delta_time;
+
+        let burst_count: u32 = self
+            .bursts
+            .iter()
+            .map(|burst| burst.count * burst_hits(burst, start, self.elapsed))
+            .sum();
+        self.emit(burst_count);
+
+        self.rate_accumulator += delta_time * self.rate;
+        let count = self.rate_accumulator.floor();
+        self.rate_accumulator -= count;
+        self.emit(count as u32);
+    }
+
+    /// Spawns particles straight away, even if the emitter is stopped
+    pub fn emit(&mut self, count: u32) {
+        for _ in 0..count {
+            if self.particles.len() >= self.max_particles {
+                return;
+            }
+
+            let offset = Vec2 {
+                x: self.rng.range(-0.5, 0.5) * self.spawn_area.x,
+                y: self.rng.range(-0.5, 0.5) * self.spawn_area.y,
+            };
+            let particle = Particle {
+                position: self.position + offset,
+                velocity: self.rng.range_vec2(self.velocity),
+                acceleration: self.rng.range_vec2(self.acceleration),
+                rotation: self.rng.range(self.rotation.0, self.rotation.1),
+                angular_velocity: self
+                    .rng
+                    .range(self.angular_velocity.0, self.angular_velocity.1),
+                age: 0.0,
+                lifetime: self.rng.range(self.lifetime.0, self.lifetime.1),
+            };
+
+            self.particles.push(particle);
+        }
+    }
+
+    /// Starts or stops spawning particles, particles that are alive keep moving
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Removes every particle and starts again from the seed, so the emitter
+    /// will make the same particles it did the first time
+    pub fn reset(&mut self) {
+        self.particles.clear();
+        self.rate_accumulator = 0.0;
+        self.elapsed = 0.0;
+        self.rng = Rng::new(self.seed);
+    }
+
+    /// Returns true when the emitter has stopped and all of its particles are gone
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.particles.is_empty()
+    }
+
+    pub fn get_particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Gets the colour of a particle based on how far through its life it is
+    pub fn get_particle_colour(&self, particle: &Particle) -> Colour {
+        let (start, end) = self.colour;
+        Colour::linear_interpolation(start, end, particle.get_life_fraction())
+    }
+
+    /// Gets the size of a particle based on how far through its life it is
+    pub fn get_particle_size(&self, particle: &Particle) -> Vec2<f32> {
+        let (start, end) = self.size;
+        let fraction = particle.get_life_fraction();
+        Vec2 {
+            x: start.x + (end.x - start.x) * fraction,
+            y: start.y + (end.y - start.y) * fraction,
+        }
+    }
+
+    /// Queues every particle on a material
+    pub fn add_to_material<T>(&self, material: &mut Material<T>, render: &Renderer) {
+        // add_rectangle_ex needs the texture to be loaded for the uv
+        let uv = self
+            .uv
+            .filter(|_| material.texture_size_from(render).is_some());
+
+        for particle in self.particles.iter() {
+            let size = self.get_particle_size(particle);
+            let position = particle.position - size / Vec2 { x: 2.0, y: 2.0 };
+            let colour = self.get_particle_colour(particle);
+
+            match uv {
+                Some((uv_position, uv_size)) => material.add_rectangle_ex(
+                    position,
+                    size,
+                    colour,
+                    particle.rotation,
+                    uv_position,
+                    uv_size,
+                    render,
+                ),
+                None => material.add_rectangle_with_rotation(
+                    position,
+                    size,
+                    colour,
+                    particle.rotation,
+                    render,
+                ),
+            }
+        }
+    }
+
+    /// Queues every particle on an instanced material, this is much faster when
+    /// there are lots of particles
+    pub fn add_to_instanced<T>(&self, material: &mut InstancedMaterial<T>, render: &Renderer) {
+        for particle in self.particles.iter() {
+            let size = self.get_particle_size(particle);
+            let position = particle.position - size / Vec2 { x: 2.0, y: 2.0 };
+            let colour = self.get_particle_colour(particle);
+
+            match self.uv {
+                Some((uv_position, uv_size)) => material.add_instance_ex(
+                    position,
+                    size,
+                    colour,
+                    particle.rotation,
+                    uv_position,
+                    uv_size,
+                    render,
+                ),
+                None if particle.rotation == 0.0 => material.add_instance(position, size, colour),
+                None => material.add_instance_ex(
+                    position,
+                    size,
+                    colour,
+                    particle.rotation,
+                    Vec2 { x: 0.0, y: 0.0 },
+                    material
+                        .texture_size_from(render)
+                        .unwrap_or(Vec2 { x: 1.0, y: 1.0 }),
+                    render,
+                ),
+            }
+        }
+    }
+}
+
+// how many times a burst happens from start up to but not including end
+fn burst_hits(burst: &Burst, start: f32, end: f32) -> u32 {
+    // how many times the burst has happened before a point in time
+    let happened_before = |time: f32| -> u32 {
+        if time <= burst.time {
+            return 0;
+        }
+
+        match burst.interval {
+            Some(interval) if interval > 0.0 => ((time - burst.time) / interval).ceil() as u32,
+            _ => 1,
+        }
+    };
+
+    happened_before(end).saturating_sub(happened_before(start))
+}
+
+// splitmix64, small and fast with good enough randomness for particles
+#[derive(Clone, Debug)]
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    // between 0.0 and 1.0
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    fn range_vec2(&mut self, (min, max): (Vec2<f32>, Vec2<f32>)) -> Vec2<f32> {
+        Vec2 {
+            x: self.range(min.x, max.x),
+            y: self.range(min.y, max.y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTAS: [f32; 6] = [0.016, 0.1, 0.05, 0.3, 0.016, 0.2];
+
+    fn sparks(seed: u64) -> ParticleEmitter {
+        ParticleEmitter::new(Vec2 { x: 10.0, y: 20.0 }, seed)
+            .with_rate(30.0)
+            .with_spawn_area(Vec2 { x: 8.0, y: 4.0 })
+            .with_lifetime(0.2, 0.6)
+            .with_velocity(Vec2 { x: -5.0, y: -10.0 }, Vec2 { x: 5.0, y: 0.0 })
+            .with_acceleration(Vec2 { x: 0.0, y: 2.0 }, Vec2 { x: 0.0, y: 8.0 })
+            .with_angular_velocity(-90.0, 90.0)
+            .with_burst(Burst::new(0.0, 4))
+    }
+
+    // an emitter that only spawns from bursts and keeps everything it spawns
+    fn bursts_only(burst: Burst) -> ParticleEmitter {
+        ParticleEmitter::new(Vec2 { x: 0.0, y: 0.0 }, 1)
+            .with_rate(0.0)
+            .with_lifetime(100.0, 100.0)
+            .with_burst(burst)
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_particles() {
+        let mut first = sparks(42);
+        let mut second = sparks(42);
+        let mut other_seed = sparks(43);
+
+        for delta in DELTAS {
+            first.advance(delta);
+            second.advance(delta);
+            other_seed.advance(delta);
+            assert_eq!(first.get_particles(), second.get_particles());
+        }
+
+        assert!(!first.get_particles().is_empty());
+        assert_ne!(first.get_particles(), other_seed.get_particles());
+    }
+
+    #[test]
+    fn reset_replays_the_same_particles() {
+        let mut emitter = sparks(7);
+        let first_run: Vec<Vec<Particle>> = DELTAS
+            .iter()
+            .map(|delta| {
+                emitter.advance(*delta);
+                emitter.get_particles().to_vec()
+            })
+            .collect();
+
+        emitter.reset();
+        assert!(emitter.get_particles().is_empty());
+
+        for (delta, expected) in DELTAS.iter().zip(first_run) {
+            emitter.advance(*delta);
+            assert_eq!(emitter.get_particles(), expected);
+        }
+    }
+
+    #[test]
+    fn burst_at_the_start_fires_once() {
+        let mut emitter = bursts_only(Burst::new(0.0, 5));
+        emitter.advance(0.0);
+        assert_eq!(emitter.get_particles().len(), 0);
+
+        emitter.advance(0.016);
+        assert_eq!(emitter.get_particles().len(), 5);
+
+        for _ in 0..100 {
+            emitter.advance(0.016);
+        }
+        assert_eq!(emitter.get_particles().len(), 5);
+    }
+
+    #[test]
+    fn repeating_burst_fires_across_frames() {
+        let burst = Burst::new(0.5, 2).repeating(1.0);
+
+        // a frame covers from its start up to but not including its end, so a frame
+        // ending right on a burst time leaves it for the next frame
+        let mut emitter = bursts_only(burst);
+        let mut counts = Vec::new();
+        for _ in 0..12 {
+            emitter.advance(0.25);
+            counts.push(emitter.get_particles().len());
+        }
+        assert_eq!(counts, [0, 0, 2, 2, 2, 2, 4, 4, 4, 4, 6, 6]);
+
+        // one long frame catches up on every repeat it skipped over
+        let mut emitter = bursts_only(burst);
+        emitter.advance(3.0);
+        assert_eq!(emitter.get_particles().len(), 6);
+
+        // frames that dont line up with the interval
+        let mut emitter = bursts_only(burst);
+        for _ in 0..9 {
+            emitter.advance(0.3);
+        }
+        assert_eq!(emitter.get_particles().len(), 6);
+    }
+
+    #[test]
+    fn max_particles_caps_spawning() {
+        let mut emitter = ParticleEmitter::new(Vec2 { x: 0.0, y: 0.0 }, 3)
+            .with_rate(100.0)
+            .with_lifetime(10.0, 10.0)
+            .with_max_particles(10)
+            .with_burst(Burst::new(0.0, 50));
+
+        emitter.advance(1.0);
+        assert_eq!(emitter.get_particles().len(), 10);
+
+        emitter.emit(5);
+        assert_eq!(emitter.get_particles().len(), 10);
+
+        // room is made as particles die
+        emitter.advance(10.0);
+        assert_eq!(emitter.get_particles().len(), 10);
+        assert!(emitter.get_particles().iter().all(|p| p.age < 10.0));
+    }
+}
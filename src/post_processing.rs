@@ -0,0 +1,860 @@
+//! Contains [PostEffect]s, full screen effects that are ran on everything drawn to the
+//! window in [Game::render](crate::Game::render) before it is shown. Effects are added to
+//! the engine with [Engine::add_post_effect] and run in the order they were added, each one
+//! getting the output of the last. The effects automatically follow the size of the window.
+//! ```rust,no_run
+//! # use bottomless_pit::engine_handle::EngineBuilder;
+//! # use bottomless_pit::post_processing::PostEffect;
+//! # use bottomless_pit::resource::LoadingOp;
+//! # use bottomless_pit::texture::Texture;
+//! let mut engine = EngineBuilder::new().build().unwrap();
+//!
+//! let lut = Texture::new(&mut engine, "warm_lut.png", LoadingOp::Blocking);
+//!
+//! engine.add_post_effect(PostEffect::bloom(0.7, 1.2, 8.0));
+//! engine.add_post_effect(PostEffect::colour_grade(lut));
+//! let vignette = engine.add_post_effect(PostEffect::vignette(0.8, 0.75, 0.45));
+//!
+//! // effects can be turned off without losing their place in the chain
+//! engine.set_post_effect_enabled(&vignette, false);
+//! ```
+//! Custom effects only need a fragment shader, the vertex shader and the screen texture
+//! are added for you. See [PostEffect::new] for everything that can be used.
+
+use std::error::Error;
+use std::fmt::Display;
+
+use encase::private::WriteInto;
+use encase::ShaderType;
+use web_time::Instant;
+
+use crate::context::{GraphicsContext, WgpuClump};
+use crate::layouts;
+use crate::render::{make_pipeline, BlendMode};
+use crate::resource::{ResourceId, ResourceManager};
+use crate::shader::UniformData;
+use crate::texture::Texture;
+use crate::vectors::Vec2;
+
+const PRELUDE: &str = include_str!("shaders/post_prelude.wgsl");
+
+/// A full screen effect like bloom or colour grading. Post effects are added
+/// to the engine with [Engine::add_post_effect](crate::engine_handle::Engine::add_post_effect)
+pub struct PostEffect {
+    source: String,
+    uniform_data: Option<Vec<u8>>,
+    texture: Option<ResourceId<Texture>>,
+    enabled: bool,
+    // only made once there is a graphics context
+    inner: Option<InnerEffect>,
+}
+
+impl PostEffect {
+    /// Creates a custom effect from the source of a WGSL fragment shader. The shader has
+    /// to have an `fs_main` entry point and the following is put before your code:
+    /// ```wgsl
+    /// struct VertexOutput {
+    ///     @builtin(position) clip_position: vec4<f32>,
+    ///     @location(0) tex_coords: vec2<f32>,
+    /// }
+    ///
+    /// struct PostGlobals {
+    ///     // size of the screen in pixels
+    ///     size: vec2<f32>,
+    ///     // seconds since the first post processed frame
+    ///     time: f32,
+    ///     // 1 when the screen stores srgb colours
+    ///     srgb: u32,
+    /// }
+    ///
+    /// // what has been drawn so far, including earlier effects
+    /// @group(0) @binding(0)
+    /// var screen_texture: texture_2d<f32>;
+    /// @group(0) @binding(1)
+    /// var screen_sampler: sampler;
+    ///
+    /// @group(1) @binding(0)
+    /// var<uniform> globals: PostGlobals;
+    /// ```
+    /// Uniform data and textures added with [PostEffect::with_uniform_data] and
+    /// [PostEffect::with_texture] are in group 2 using the same layout as
+    /// [ShaderOptions](crate::shader::ShaderOptions). The shader is compiled the first
+    /// frame the effect is used, if it has errors they are logged and the effect is disabled.
+    pub fn new<S: Into<String>>(source: S) -> Self {
+        Self {
+            source: source.into(),
+            uniform_data: None,
+            texture: None,
+            enabled: true,
+            inner: None,
+        }
+    }
+
+    /// Gives the effect a uniform buffer which can be changed later with
+    /// [Engine::update_post_effect_uniform](crate::engine_handle::Engine::update_post_effect_uniform)
+    pub fn with_uniform_data<T: ShaderType + WriteInto>(self, data: &UniformData<T>) -> Self {
+        Self {
+            uniform_data: Some(data.initial_data.clone()),
+            ..self
+        }
+    }
+
+    /// Gives the effect an extra texture, the effect is skipped untill the texture has loaded
+    pub fn with_texture(self, texture: ResourceId<Texture>) -> Self {
+        Self {
+            texture: Some(texture),
+            ..self
+        }
+    }
+
+    /// Makes everything brighter than the threshold glow. The threshold is the brightness
+    /// from 0.0 to 1.0 where things start to glow, and the radius is how far the glow
+    /// spreads in pixels.
+    pub fn bloom(threshold: f32, intensity: f32, radius: f32) -> Self {
+        Self::new(include_str!("shaders/post_bloom.wgsl"))
+            .with_raw_uniform(&[threshold, intensity, radius, 0.0])
+    }
+
+    /// Makes the screen look like an old CRT tv by darkening every other row of pixels
+    /// and bending the screen. A curvature of 0.0 leaves the screen flat.
+    pub fn crt(scanline_strength: f32, curvature: f32) -> Self {
+        Self::new(include_str!("shaders/post_crt.wgsl")).with_raw_uniform(&[
+            scanline_strength,
+            curvature,
+            0.0,
+            0.0,
+        ])
+    }
+
+    /// Darkens the edges of the screen. The radius is how far from the center the
+    /// darkening ends and softness is how far it takes to fade in, both are fractions of
+    /// the screen.
+    pub fn vignette(strength: f32, radius: f32, softness: f32) -> Self {
+        Self::new(include_str!("shaders/post_vignette.wgsl"))
+            .with_raw_uniform(&[strength, radius, softness, 0.0])
+    }
+
+    /// Changes every colour on the screen using a lookup table. The lookup table is a
+    /// texture made of N slices that are each N by N pixels placed side by side, so a
+    /// 16 slice table is 256 by 16 pixels. Across each slice red goes from 0 to 1,
+    /// down each slice green goes from 0 to 1 and blue goes from 0 to 1 across the slices.
+    pub fn colour_grade(lut: ResourceId<Texture>) -> Self {
+        Self::new(include_str!("shaders/post_colour_grade.wgsl")).with_texture(lut)
+    }
+
+    /// Checks if the effect will be ran
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn with_raw_uniform(self, data: &[f32]) -> Self {
+        Self {
+            uniform_data: Some(bytemuck::cast_slice(data).to_vec()),
+            ..self
+        }
+    }
+
+    // ready once the pipeline and any texture bind group exist
+    fn get_ready(&self) -> Option<&InnerEffect> {
+        self.inner
+            .as_ref()
+            .filter(|inner| self.enabled && (self.texture.is_none() || inner.bind_group.is_some()))
+    }
+
+    fn make_inner(
+        &mut self,
+        shared: &SharedResources,
+        wgpu: &WgpuClump,
+        resources: &ResourceManager,
+    ) {
+        // disabled effects are made once they are turned back on
+        if !self.enabled {
+            return;
+        }
+
+        let device = &wgpu.device;
+
+        if self.inner.is_none() {
+            let extra_layout = match (self.uniform_data.is_some(), self.texture.is_some()) {
+                (true, true) => Some(layouts::create_texture_uniform_layout(device)),
+                (true, false) => Some(layouts::create_uniform_layout(device)),
+                (false, true) => Some(layouts::create_texture_layout(device)),
+                (false, false) => None,
+            };
+
+            let pipeline =
+                match make_post_pipeline(device, shared, &self.source, extra_layout.as_ref()) {
+                    Ok(pipeline) => pipeline,
+                    Err(e) => {
+                        log::error!("post effect could not be made and has been disabled: {}", e);
+                        self.enabled = false;
+                        return;
+                    }
+                };
+
+            let uniform_buffer = self.uniform_data.as_ref().map(|data| {
+                let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Post Effect Uniform Buffer"),
+                    size: data.len() as u64,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                wgpu.queue.write_buffer(&buffer, 0, data);
+                buffer
+            });
+
+            self.inner = Some(InnerEffect {
+                pipeline,
+                extra_layout,
+                uniform_buffer,
+                bind_group: None,
+            });
+        }
+
+        let inner = self.inner.as_mut().unwrap();
+
+        if inner.bind_group.is_some() {
+            return;
+        }
+
+        let Some(layout) = &inner.extra_layout else {
+            return;
+        };
+
+        let texture = match self.texture {
+            Some(id) => match resources.get_texture(&id) {
+                Some(texture) => Some(texture),
+                // try again next frame
+                None => return,
+            },
+            None => None,
+        };
+
+        let mut entries = Vec::new();
+        if let Some(buffer) = &inner.uniform_buffer {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            });
+        }
+        if let Some(texture) = texture {
+            let binding = entries.len() as u32;
+            entries.push(wgpu::BindGroupEntry {
+                binding,
+                resource: wgpu::BindingResource::TextureView(&texture._view),
+            });
+            entries.push(wgpu::BindGroupEntry {
+                binding: binding + 1,
+                resource: wgpu::BindingResource::Sampler(&shared.sampler),
+            });
+        }
+
+        inner.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Effect Bind Group"),
+            layout,
+            entries: &entries,
+        }));
+    }
+}
+
+/// Updating a post effect can fail if the effect was removed from the engine,
+/// or if it was made without uniform data, or the new data is a different size
+#[derive(Debug)]
+pub enum PostEffectError {
+    DoesntExist,
+    DoesntHaveUniformBuffer,
+    WrongSize,
+}
+
+impl Display for PostEffectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DoesntExist => write!(f, "The post effect does not exist"),
+            Self::DoesntHaveUniformBuffer => {
+                write!(f, "The post effect does not have a uniform buffer")
+            }
+            Self::WrongSize => write!(
+                f,
+                "The uniform data is not the same size as the data the effect was made with"
+            ),
+        }
+    }
+}
+
+impl Error for PostEffectError {}
+
+struct InnerEffect {
+    pipeline: wgpu::RenderPipeline,
+    extra_layout: Option<wgpu::BindGroupLayout>,
+    uniform_buffer: Option<wgpu::Buffer>,
+    // group 2, None untill the texture is loaded
+    bind_group: Option<wgpu::BindGroup>,
+}
+
+/// The chain of post effects owned by the engine along with the textures
+/// they render between, this also scales up the virtual resolution
+#[derive(Default)]
+pub(crate) struct PostProcessing {
+    effects: Vec<(ResourceId<PostEffect>, PostEffect)>,
+    shared: Option<SharedResources>,
+    // when the first post processed frame was rendered
+    start: Option<Instant>,
+    virtual_resolution: Option<Vec2<u32>>,
+    upscale: Option<Upscale>,
+}
+
+impl PostProcessing {
+    pub(crate) fn new(virtual_resolution: Option<Vec2<u32>>) -> Self {
+        Self {
+            virtual_resolution,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn get_virtual_resolution(&self) -> Option<Vec2<u32>> {
+        self.virtual_resolution
+    }
+
+    pub(crate) fn add(&mut self, effect: PostEffect) -> ResourceId<PostEffect> {
+        let id = crate::resource::generate_id::<PostEffect>();
+        self.effects.push((id, effect));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: &ResourceId<PostEffect>) -> Option<PostEffect> {
+        let index = self.effects.iter().position(|(other, _)| other == id)?;
+        let (_, mut effect) = self.effects.remove(index);
+        effect.inner = None;
+        Some(effect)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    // the bind groups still point at the old texture, they get remade next frame
+    pub(crate) fn texture_changed(&mut self, id: &ResourceId<Texture>) {
+        let using_texture = self
+            .effects
+            .iter_mut()
+            .filter(|(_, effect)| effect.texture.as_ref() == Some(id))
+            .filter_map(|(_, effect)| effect.inner.as_mut());
+
+        for inner in using_texture {
+            inner.bind_group = None;
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, id: &ResourceId<PostEffect>) -> Option<&mut PostEffect> {
+        self.effects
+            .iter_mut()
+            .find(|(other, _)| other == id)
+            .map(|(_, effect)| effect)
+    }
+
+    pub(crate) fn set_enabled(
+        &mut self,
+        id: &ResourceId<PostEffect>,
+        enabled: bool,
+    ) -> Result<(), PostEffectError> {
+        let effect = self.get_mut(id).ok_or(PostEffectError::DoesntExist)?;
+        effect.enabled = enabled;
+        Ok(())
+    }
+
+    pub(crate) fn update_uniform(
+        &mut self,
+        id: &ResourceId<PostEffect>,
+        data: Vec<u8>,
+        wgpu: Option<&WgpuClump>,
+    ) -> Result<(), PostEffectError> {
+        let effect = self.get_mut(id).ok_or(PostEffectError::DoesntExist)?;
+        let current = effect
+            .uniform_data
+            .as_mut()
+            .ok_or(PostEffectError::DoesntHaveUniformBuffer)?;
+
+        if current.len() != data.len() {
+            return Err(PostEffectError::WrongSize);
+        }
+
+        let buffer = effect
+            .inner
+            .as_ref()
+            .and_then(|inner| inner.uniform_buffer.as_ref());
+        if let (Some(buffer), Some(wgpu)) = (buffer, wgpu) {
+            wgpu.queue.write_buffer(buffer, 0, &data);
+        }
+
+        *current = data;
+        Ok(())
+    }
+
+    /// Makes anything on the GPU that is missing and updates the globals,
+    /// called every frame before rendering
+    pub(crate) fn prepare(&mut self, context: &GraphicsContext, resources: &ResourceManager) {
+        if self.effects.is_empty() && self.virtual_resolution.is_none() {
+            return;
+        }
+
+        let wgpu = &context.wgpu;
+        let format = context.get_texture_format();
+        let size = Vec2 {
+            x: context.config.width,
+            y: context.config.height,
+        };
+        let shared = self
+            .shared
+            .get_or_insert_with(|| SharedResources::new(wgpu, format, size));
+
+        if let Some(virtual_size) = self.virtual_resolution {
+            self.upscale.get_or_insert_with(|| {
+                // the defualt camera has to draw in the virtual resolution
+                let size = [virtual_size.x as f32, virtual_size.y as f32];
+                wgpu.queue
+                    .write_buffer(&context.camera_buffer, 48, bytemuck::cast_slice(&size));
+
+                Upscale::new(&wgpu.device, shared, virtual_size)
+            });
+        }
+
+        for (_, effect) in self.effects.iter_mut() {
+            effect.make_inner(shared, wgpu, resources);
+        }
+
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let globals = PostGlobals {
+            size: [shared.size.x as f32, shared.size.y as f32],
+            time: start.elapsed().as_secs_f32(),
+            srgb: format.is_srgb() as u32,
+        };
+        wgpu.queue
+            .write_buffer(&shared.globals_buffer, 0, bytemuck::bytes_of(&globals));
+    }
+
+    pub(crate) fn resize(&mut self, new_size: Vec2<u32>, wgpu: &WgpuClump) {
+        if let Some(shared) = &mut self.shared {
+            if shared.size != new_size {
+                shared.targets = PostTarget::pair(
+                    &wgpu.device,
+                    &shared.texture_layout,
+                    &shared.sampler,
+                    shared.format,
+                    new_size,
+                );
+                shared.size = new_size;
+            }
+        }
+    }
+
+    /// The texture everything should be rendered to instead of the screen,
+    /// None when there are no effects to run
+    pub(crate) fn get_scene_target(&self) -> Option<(&wgpu::Texture, &wgpu::TextureView)> {
+        if let Some(upscale) = &self.upscale {
+            return Some((&upscale.target.texture, &upscale.target.view));
+        }
+
+        let shared = self.shared.as_ref()?;
+        self.effects
+            .iter()
+            .any(|(_, effect)| effect.get_ready().is_some())
+            .then(|| (&shared.targets[0].texture, &shared.targets[0].view))
+    }
+
+    /// Scales up the virtual resolution then runs every effect starting from
+    /// the scene target, with the last step drawing onto the output
+    pub(crate) fn run(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        output_size: Vec2<u32>,
+    ) {
+        let Some(shared) = &self.shared else {
+            return;
+        };
+
+        let ready: Vec<&InnerEffect> = self
+            .effects
+            .iter()
+            .filter_map(|(_, effect)| effect.get_ready())
+            .collect();
+
+        if let Some(upscale) = &self.upscale {
+            // the effects start from the first target like they would without upscaling
+            let (target, target_size) = match ready.is_empty() {
+                true => (output, output_size),
+                false => (&shared.targets[0].view, shared.size),
+            };
+
+            let (offset, scale) = letterbox(target_size, upscale.size);
+            let mut pass = begin_post_pass(encoder, target);
+            pass.set_viewport(
+                offset.x,
+                offset.y,
+                upscale.size.x as f32 * scale,
+                upscale.size.y as f32 * scale,
+                0.0,
+                1.0,
+            );
+            pass.set_pipeline(&upscale.pipeline);
+            pass.set_bind_group(0, &upscale.target.bind_group, &[]);
+            pass.set_bind_group(1, &shared.globals_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        for (i, effect) in ready.iter().enumerate() {
+            let input = &shared.targets[i % 2];
+            let target = if i == ready.len() - 1 {
+                output
+            } else {
+                &shared.targets[(i + 1) % 2].view
+            };
+
+            let mut pass = begin_post_pass(encoder, target);
+            pass.set_pipeline(&effect.pipeline);
+            pass.set_bind_group(0, &input.bind_group, &[]);
+            pass.set_bind_group(1, &shared.globals_bind_group, &[]);
+            if let Some(bind_group) = &effect.bind_group {
+                pass.set_bind_group(2, bind_group, &[]);
+            }
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Where the virtual resolution is drawn on the window, gives the top left corner and
+/// how many window pixels each virtual pixel takes up. The scale is always a whole
+/// number so every pixel is the same size, unless the window is smaller than the
+/// virtual resolution.
+pub(crate) fn letterbox(window_size: Vec2<u32>, virtual_size: Vec2<u32>) -> (Vec2<f32>, f32) {
+    let ratio = (window_size.x as f32 / virtual_size.x as f32)
+        .min(window_size.y as f32 / virtual_size.y as f32);
+    let scale = if ratio >= 1.0 { ratio.floor() } else { ratio };
+
+    let offset = Vec2 {
+        x: ((window_size.x as f32 - virtual_size.x as f32 * scale) / 2.0).floor(),
+        y: ((window_size.y as f32 - virtual_size.y as f32 * scale) / 2.0).floor(),
+    };
+
+    (offset, scale)
+}
+
+// the source comes from the user so it is checked inside of an error scope,
+// without it invalid wgsl would panic
+fn make_post_pipeline(
+    device: &wgpu::Device,
+    shared: &SharedResources,
+    source: &str,
+    extra_layout: Option<&wgpu::BindGroupLayout>,
+) -> Result<wgpu::RenderPipeline, wgpu::Error> {
+    let source = format!("{}{}", PRELUDE, source);
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Post Effect Module"),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+
+    let mut bind_group_layouts = vec![&shared.texture_layout, &shared.globals_layout];
+    bind_group_layouts.extend(extra_layout);
+
+    let pipeline = make_pipeline(
+        device,
+        wgpu::PrimitiveTopology::TriangleList,
+        &bind_group_layouts,
+        &[],
+        &module,
+        shared.format,
+        BlendMode::Replace,
+        Some("Post Effect Pipeline"),
+    );
+
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(e) => Err(e),
+        None => Ok(pipeline),
+    }
+}
+
+fn begin_post_pass<'p>(
+    encoder: &'p mut wgpu::CommandEncoder,
+    target: &'p wgpu::TextureView,
+) -> wgpu::RenderPass<'p> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Post Effect Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        timestamp_writes: None,
+        occlusion_query_set: None,
+        depth_stencil_attachment: None,
+    })
+}
+
+// draws the fixed size virtual resolution onto the window
+struct Upscale {
+    pipeline: wgpu::RenderPipeline,
+    target: PostTarget,
+    size: Vec2<u32>,
+}
+
+impl Upscale {
+    fn new(device: &wgpu::Device, shared: &SharedResources, size: Vec2<u32>) -> Self {
+        // nearest sampling keeps the pixels sharp
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Virtual Resolution Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let source = "
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(screen_texture, screen_sampler, in.tex_coords);
+}
+";
+
+        Self {
+            // built in so this cant fail
+            pipeline: make_post_pipeline(device, shared, source, None).unwrap(),
+            target: PostTarget::new(
+                device,
+                &shared.texture_layout,
+                &sampler,
+                shared.format,
+                size,
+            ),
+            size,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostGlobals {
+    size: [f32; 2],
+    time: f32,
+    srgb: u32,
+}
+
+// everything shared between all the effects
+struct SharedResources {
+    texture_layout: wgpu::BindGroupLayout,
+    globals_layout: wgpu::BindGroupLayout,
+    globals_buffer: wgpu::Buffer,
+    globals_bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
+    format: wgpu::TextureFormat,
+    size: Vec2<u32>,
+    // effects ping pong between these
+    targets: [PostTarget; 2],
+}
+
+impl SharedResources {
+    fn new(wgpu: &WgpuClump, format: wgpu::TextureFormat, size: Vec2<u32>) -> Self {
+        let device = &wgpu.device;
+        let texture_layout = layouts::create_texture_layout(device);
+        let globals_layout = layouts::create_uniform_layout(device);
+
+        let globals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Globals Buffer"),
+            size: std::mem::size_of::<PostGlobals>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let globals_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Globals Bind Group"),
+            layout: &globals_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: globals_buffer.as_entire_binding(),
+            }],
+        });
+
+        // effects sample exactly one pixel so clamping stops the edges wrapping around
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Effect Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let targets = PostTarget::pair(device, &texture_layout, &sampler, format, size);
+
+        Self {
+            texture_layout,
+            globals_layout,
+            globals_buffer,
+            globals_bind_group,
+            sampler,
+            format,
+            size,
+            targets,
+        }
+    }
+}
+
+struct PostTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+impl PostTarget {
+    fn pair(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        format: wgpu::TextureFormat,
+        size: Vec2<u32>,
+    ) -> [Self; 2] {
+        [
+            Self::new(device, layout, sampler, format, size),
+            Self::new(device, layout, sampler, format, size),
+        ]
+    }
+
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        format: wgpu::TextureFormat,
+        size: Vec2<u32>,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Post Effect Target"),
+            size: wgpu::Extent3d {
+                width: size.x.max(1),
+                height: size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            // COPY_SRC lets the frame be captured before the effects run
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Effect Target Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Self {
+            texture,
+            view,
+            bind_group,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_handle::{Engine, EngineBuilder};
+    use crate::render::RenderHandle;
+    use crate::resource::{LoadingOp, ReloadKind, ReloadedResource};
+    use crate::texture::TextureOptions;
+    use crate::Game;
+
+    #[test]
+    fn letterbox_scales_by_whole_numbers() {
+        let virtual_size = Vec2 { x: 320, y: 180 };
+
+        // fits exactly
+        let (offset, scale) = letterbox(Vec2 { x: 1280, y: 720 }, virtual_size);
+        assert_eq!(offset, Vec2 { x: 0.0, y: 0.0 });
+        assert_eq!(scale, 4.0);
+
+        // 3.33 times bigger only fits 3 times, the rest is split evenly on both sides
+        let (offset, scale) = letterbox(Vec2 { x: 1066, y: 800 }, virtual_size);
+        assert_eq!(scale, 3.0);
+        assert_eq!(offset, Vec2 { x: 53.0, y: 130.0 });
+    }
+
+    #[test]
+    fn letterbox_shrinks_smoothly_below_the_virtual_size() {
+        let (offset, scale) = letterbox(Vec2 { x: 160, y: 120 }, Vec2 { x: 320, y: 180 });
+        assert_eq!(scale, 0.5);
+        assert_eq!(offset, Vec2 { x: 0.0, y: 15.0 });
+    }
+
+    #[test]
+    fn letterbox_handles_a_minimised_window() {
+        let (offset, scale) = letterbox(Vec2 { x: 0, y: 0 }, Vec2 { x: 320, y: 180 });
+        assert_eq!(scale, 0.0);
+        assert_eq!(offset, Vec2 { x: 0.0, y: 0.0 });
+    }
+
+    struct Idle;
+
+    impl Game for Idle {
+        fn render<'o>(&'o mut self, _render_handle: RenderHandle<'o>) {}
+        fn update(&mut self, _engine_handle: &mut Engine) {}
+    }
+
+    #[test]
+    fn reloaded_textures_remake_the_effect_bind_group() {
+        let mut engine = EngineBuilder::new()
+            .with_resolution((16, 16))
+            .headless()
+            .build()
+            .unwrap();
+        let mut game = Idle;
+
+        let path = "examples/bplogo.png";
+        let lut = Texture::new(&mut engine, path, LoadingOp::Blocking);
+        let effect = engine.add_post_effect(PostEffect::colour_grade(lut));
+        engine.step(&mut game);
+
+        let has_bind_group = |engine: &mut Engine| {
+            let effect = engine.post_processing.get_mut(&effect).unwrap();
+            effect.inner.as_ref().unwrap().bind_group.is_some()
+        };
+        assert!(has_bind_group(&mut engine));
+
+        engine.handle_reload(ReloadedResource {
+            path: path.into(),
+            data: Ok(std::fs::read(path).unwrap()),
+            id: lut.get_id(),
+            kind: ReloadKind::Image(TextureOptions::default()),
+        });
+        assert!(!has_bind_group(&mut engine));
+
+        engine.step(&mut game);
+        assert!(has_bind_group(&mut engine));
+
+        // nothing to remake it with once the texture is gone
+        engine.unload(lut);
+        engine.step(&mut game);
+        assert!(!has_bind_group(&mut engine));
+    }
+}
@@ -16,15 +16,94 @@
 //! }
 //! ```
 
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc;
+
+use image::RgbaImage;
+
 use crate::colour::Colour;
 use crate::context::WgpuClump;
 use crate::engine_handle::Engine;
+use crate::material::{grow_buffer, Material};
+use crate::post_processing::PostProcessing;
 use crate::resource::{ResourceId, ResourceManager};
 use crate::shader::Shader;
-use crate::texture::UniformTexture;
+use crate::texture::{Texture, UniformTexture};
 use crate::vectors::Vec2;
+use crate::vertex::Vertex;
 use crate::{vec2, Game};
 
+/// How the colours being drawn are mixed with the colours already on the render target.
+/// This can be set on a [Shader] with [ShaderOptions](crate::shader::ShaderOptions::with_blend_mode)
+/// or per material with [MaterialBuilder](crate::material::MaterialBuilder::set_blend_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BlendMode {
+    /// Normal transparency using the alpha of the colour being drawn
+    #[default]
+    Alpha,
+    /// Like [BlendMode::Alpha] but for colours that have already been multiplied by their alpha
+    Premultiplied,
+    /// Adds the colour being drawn to what is already there, usefull for glows and fire
+    Additive,
+    /// Multiplies what is already there by the colour being drawn, usefull for shadows and lightmaps.
+    /// Like [BlendMode::Premultiplied] this expects colours already multiplied by their alpha,
+    /// so fully transparent pixels leave what is already there alone
+    Multiply,
+    /// The inverse of multiply, brightens what is already there without ever going past white.
+    /// This also expects colours already multiplied by their alpha
+    Screen,
+    /// Overwrites what is already there including its alpha
+    Replace,
+}
+
+impl BlendMode {
+    pub(crate) const ALL: [Self; 6] = [
+        Self::Alpha,
+        Self::Premultiplied,
+        Self::Additive,
+        Self::Multiply,
+        Self::Screen,
+        Self::Replace,
+    ];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl From<BlendMode> for wgpu::BlendState {
+    fn from(value: BlendMode) -> Self {
+        let component = |src_factor, dst_factor| wgpu::BlendComponent {
+            src_factor,
+            dst_factor,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        match value {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: component(wgpu::BlendFactor::SrcAlpha, wgpu::BlendFactor::One),
+                alpha: component(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+            },
+            BlendMode::Multiply => wgpu::BlendState {
+                color: component(wgpu::BlendFactor::Dst, wgpu::BlendFactor::OneMinusSrcAlpha),
+                alpha: component(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+            },
+            BlendMode::Screen => wgpu::BlendState {
+                color: component(wgpu::BlendFactor::OneMinusDst, wgpu::BlendFactor::One),
+                alpha: wgpu::BlendComponent::OVER,
+            },
+            BlendMode::Replace => wgpu::BlendState::REPLACE,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn make_pipeline(
     device: &wgpu::Device,
     topology: wgpu::PrimitiveTopology,
@@ -32,6 +111,7 @@ pub(crate) fn make_pipeline(
     vertex_buffers: &[wgpu::VertexBufferLayout],
     shader: &wgpu::ShaderModule,
     texture_format: wgpu::TextureFormat,
+    blend_mode: BlendMode,
     label: Option<&str>,
 ) -> wgpu::RenderPipeline {
     let layout_label = label.map(|label| format!("{}_layout", label));
@@ -58,8 +138,8 @@ pub(crate) fn make_pipeline(
             targets: &[Some(wgpu::ColorTargetState {
                 // tells wgpu what colour outputs it should set up.
                 format: texture_format,
-                blend: Some(wgpu::BlendState::ALPHA_BLENDING), // specifies that the blending should just replace old pixel data wiht new data,
-                write_mask: wgpu::ColorWrites::ALL,            // writes all colours
+                blend: Some(blend_mode.into()), // specifies how the new pixel data is mixed with the old pixel data
+                write_mask: wgpu::ColorWrites::ALL, // writes all colours
             })],
             compilation_options: wgpu::PipelineCompilationOptions::default(),
         }),
@@ -103,6 +183,12 @@ where
         return Ok(());
     }
 
+    if let Some(context) = &engine.context {
+        engine
+            .post_processing
+            .prepare(context, &engine.resource_manager);
+    }
+
     let render_handle = RenderHandle::from(engine);
 
     game.render(render_handle);
@@ -115,22 +201,39 @@ pub struct RenderHandle<'a> {
     // ME WHEN BC HACKS :3
     encoder: Option<wgpu::CommandEncoder>,
     surface: Option<wgpu::SurfaceTexture>,
+    // where to save a screenshot of this frame once it is finished
+    screenshot: Option<PathBuf>,
+    // only set when there are post effects to run, passes then draw to
+    // the defualt texture which the effects draw onto the output
+    post_processing: Option<&'a PostProcessing>,
+    output_texture: wgpu::Texture,
+    output_view: wgpu::TextureView,
     pub(crate) resources: &'a ResourceManager,
     defualt_id: ResourceId<Shader>,
+    defualt_texture: wgpu::Texture,
     defualt_view: wgpu::TextureView,
     defualt_view_size: Vec2<u32>,
     camera_bindgroup: &'a wgpu::BindGroup,
+    batch_buffers: &'a mut BatchBuffers,
     pub(crate) wgpu: &'a WgpuClump,
     format: wgpu::TextureFormat,
+    interpolation_alpha: f32,
 }
 
 impl<'a> RenderHandle<'a> {
+    /// Gets how far between the last and next [Game::fixed_update](crate::Game::fixed_update)
+    /// this frame is, see [Engine::get_interpolation_alpha]
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
     /// Creates a render pass that will render onto the windows surface.
     pub fn begin_pass<'p>(&mut self, clear_colour: Colour) -> Renderer<'_, 'p> {
-        let mut pass = match &mut self.encoder {
+        let pass = match &mut self.encoder {
             Some(encoder) => Self::create_pass(encoder, &self.defualt_view, clear_colour.into()),
             None => unreachable!(),
         };
+        let mut pass = LayeredPass::new(pass);
 
         let pipeline = &self
             .resources
@@ -139,11 +242,14 @@ impl<'a> RenderHandle<'a> {
             .pipeline;
 
         pass.set_pipeline(pipeline);
-        pass.set_bind_group(1, self.camera_bindgroup, &[]);
+        pass.set_bind_group(1, self.camera_bindgroup);
 
         Renderer {
             pass,
             size: self.defualt_view_size,
+            visible_area: screen_area(self.defualt_view_size),
+            batch: SpriteBatch::default(),
+            batch_buffers: &mut *self.batch_buffers,
             defualt_id: self.defualt_id,
             resources: self.resources,
             camera_bindgroup: self.camera_bindgroup,
@@ -161,7 +267,7 @@ impl<'a> RenderHandle<'a> {
     ) -> Renderer<'o, 'p> {
         let size = texture.get_size();
 
-        let mut pass = match &mut self.encoder {
+        let pass = match &mut self.encoder {
             Some(encoder) => Self::create_pass(
                 encoder,
                 texture.make_render_view(self.wgpu, self.format),
@@ -169,6 +275,7 @@ impl<'a> RenderHandle<'a> {
             ),
             None => unreachable!(),
         };
+        let mut pass = LayeredPass::new(pass);
 
         let pipeline = &self
             .resources
@@ -177,11 +284,14 @@ impl<'a> RenderHandle<'a> {
             .pipeline;
 
         pass.set_pipeline(pipeline);
-        pass.set_bind_group(1, self.camera_bindgroup, &[]);
+        pass.set_bind_group(1, self.camera_bindgroup);
 
         Renderer {
             pass,
             size,
+            visible_area: screen_area(size),
+            batch: SpriteBatch::default(),
+            batch_buffers: &mut *self.batch_buffers,
             defualt_id: self.defualt_id,
             resources: self.resources,
             camera_bindgroup: self.camera_bindgroup,
@@ -189,6 +299,51 @@ impl<'a> RenderHandle<'a> {
         }
     }
 
+    /// Reads back what has been rendered to the window so far this frame. Every pass
+    /// that has been finished before this call is included in the image, but
+    /// [PostEffects](crate::post_processing::PostEffect) have not been ran yet. Platforms
+    /// that do not allow copying from the window surface will return
+    /// [CaptureError::UnsupportedTexture], headless engines always support this. On wasm
+    /// this always returns [CaptureError::UnsupportedPlatform] as the browser cant wait on the GPU.
+    /// ```rust,no_run
+    /// # use bottomless_pit::colour::Colour;
+    /// # use bottomless_pit::render::RenderHandle;
+    /// # fn render(mut render_handle: RenderHandle) {
+    /// {
+    ///     let mut renderer = render_handle.begin_pass(Colour::BLACK);
+    ///     // draw the frame
+    /// }
+    /// let image = render_handle.capture().unwrap();
+    /// image.save("frame.png").unwrap();
+    /// # }
+    /// ```
+    pub fn capture(&mut self) -> Result<RgbaImage, CaptureError> {
+        self.submit_passes();
+        read_texture(self.wgpu, &self.defualt_texture)
+    }
+
+    /// Reads back the contents of a [UniformTexture]. This is most usefull after
+    /// the texture has been drawn to with [RenderHandle::begin_texture_pass].
+    pub fn capture_texture(&mut self, texture: &UniformTexture) -> Result<RgbaImage, CaptureError> {
+        let inner_texture = texture.get_texture().ok_or(CaptureError::NotCreatedYet)?;
+        self.submit_passes();
+        read_texture(self.wgpu, inner_texture)
+    }
+
+    // submits all the work done so far and starts a new encoder so
+    // more passes can still be made afterwards
+    fn submit_passes(&mut self) {
+        let new_encoder =
+            self.wgpu
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("render encoder"),
+                });
+
+        let finished = self.encoder.replace(new_encoder).unwrap();
+        self.wgpu.queue.submit(std::iter::once(finished.finish()));
+    }
+
     fn create_pass<'p>(
         encoder: &'p mut wgpu::CommandEncoder,
         view: &'p wgpu::TextureView,
@@ -215,7 +370,10 @@ impl<'a> RenderHandle<'a> {
 
 impl<'a> From<&'a mut Engine> for RenderHandle<'a> {
     fn from(value: &'a mut Engine) -> Self {
-        let context = value.context.as_ref().unwrap();
+        let screenshot = value.take_screenshot_request();
+        let interpolation_alpha = value.get_interpolation_alpha();
+        let defualt_id = value.defualt_pipe_id();
+        let context = value.context.as_mut().unwrap();
 
         let encoder = context
             .wgpu
@@ -224,35 +382,73 @@ impl<'a> From<&'a mut Engine> for RenderHandle<'a> {
                 label: Some("render encoder"),
             });
 
-        let texture = context.get_surface_texture().unwrap();
+        let (output_texture, surface) = context.get_current_frame().unwrap();
+        let format = context.get_texture_format();
+        let output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let scene_target = value.post_processing.get_scene_target();
+        let post_processing = scene_target.is_some().then_some(&value.post_processing);
+        let (defualt_texture, defualt_view) = match scene_target {
+            Some((texture, view)) => (texture.clone(), view.clone()),
+            None => (output_texture.clone(), output_view.clone()),
+        };
+
+        let batch_buffers = &mut context.batch_buffers;
+        batch_buffers.start_frame();
 
-        let defualt_view_size = texture.texture.size();
+        let defualt_view_size = defualt_texture.size();
         let defualt_view_size = vec2!(defualt_view_size.width, defualt_view_size.height);
-        let defualt_view = texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
 
         Self {
             encoder: Some(encoder),
-            surface: Some(texture),
-            resources: value.get_resources(),
-            defualt_id: value.defualt_pipe_id(),
+            surface,
+            screenshot,
+            post_processing,
+            output_texture,
+            output_view,
+            resources: &value.resource_manager,
+            defualt_id,
+            defualt_texture,
             defualt_view,
             defualt_view_size,
             camera_bindgroup: &context.camera_bind_group,
+            batch_buffers,
             wgpu: &context.wgpu,
-            format: context.get_texture_format(),
+            format,
+            interpolation_alpha,
         }
     }
 }
 
 impl Drop for RenderHandle<'_> {
     fn drop(&mut self) {
-        self.wgpu
-            .queue
-            .submit(std::iter::once(self.encoder.take().unwrap().finish()));
+        let mut encoder = self.encoder.take().unwrap();
+        if let Some(post_processing) = self.post_processing {
+            let size = self.output_texture.size();
+            post_processing.run(
+                &mut encoder,
+                &self.output_view,
+                vec2!(size.width, size.height),
+            );
+        }
+
+        self.wgpu.queue.submit(std::iter::once(encoder.finish()));
 
-        self.surface.take().unwrap().present();
+        // has to happen before presenting as the surface texture is gone afterwards
+        if let Some(path) = self.screenshot.take() {
+            match read_texture(self.wgpu, &self.output_texture) {
+                Ok(image) => match image.save(&path) {
+                    Ok(()) => log::info!("screenshot saved to: {:?}", path),
+                    Err(e) => log::error!("could not save screenshot to {:?}: {}", path, e),
+                },
+                Err(e) => log::error!("could not take screenshot: {}", e),
+            }
+        }
+
+        // headless engines have nothing to present
+        if let Some(surface) = self.surface.take() {
+            surface.present();
+        }
     }
 }
 
@@ -262,18 +458,100 @@ pub struct Renderer<'o, 'p>
 where
     'o: 'p,
 {
-    pub(crate) pass: wgpu::RenderPass<'p>,
+    pub(crate) pass: LayeredPass<'p>,
     pub(crate) size: Vec2<u32>,
     pub(crate) resources: &'o ResourceManager,
     pub(crate) defualt_id: ResourceId<Shader>,
     pub(crate) camera_bindgroup: &'o wgpu::BindGroup,
     pub(crate) wgpu: &'o WgpuClump,
+    // the top left and bottom right of the area the active camera can see
+    pub(crate) visible_area: (Vec2<f32>, Vec2<f32>),
+    pub(crate) batch: SpriteBatch,
+    pub(crate) batch_buffers: &'o mut BatchBuffers,
 }
 
 impl<'p, 'o> Renderer<'p, 'o> {
     /// Resets the camera to the defualt camera.
     pub fn reset_camera(&mut self) {
-        self.pass.set_bind_group(1, self.camera_bindgroup, &[]);
+        // anything batched so far was meant for the previous camera
+        self.flush_batch();
+        self.pass.set_bind_group(1, self.camera_bindgroup);
+        self.visible_area = screen_area(self.size);
+    }
+
+    /// Gives the top left and bottom right corners of the area that can be seen with
+    /// the active [Camera](crate::camera::Camera). When the camera is rotated this is
+    /// the smallest rectangle that fits around everything that can be seen.
+    pub fn get_visible_area(&self) -> (Vec2<f32>, Vec2<f32>) {
+        self.visible_area
+    }
+
+    /// Hands everything queued by batched [Materials](crate::material::MaterialBuilder::set_batched)
+    /// to the pass. Shapes sharing a [layer](crate::material::MaterialBuilder::set_layer), shader,
+    /// blend mode and texture are drawn together so each pipeline and texture is only bound once.
+    /// Nothing is actually drawn untill the Renderer is dropped, at which point everything in
+    /// the pass is drawn from the lowest layer to the highest. Inside of a layer things are drawn
+    /// in the order they were given to the Renderer, and the batch is given to the Renderer when
+    /// the camera changes and when the Renderer is dropped. This means batched shapes end up on
+    /// top of anything else on their layer, so this is only needed when something that isnt
+    /// batched has to be drawn on top of batched shapes on the same layer.
+    pub fn flush_batch(&mut self) {
+        if self.batch.batches.is_empty() {
+            return;
+        }
+
+        let mut batches = std::mem::take(&mut self.batch.batches);
+        // pipelines are the most expensive to switch so they are grouped first
+        batches.sort_by_key(|batch| {
+            (
+                batch.layer,
+                batch.pipeline_id.get_id(),
+                batch.blend_mode,
+                batch.texture_id.get_id(),
+            )
+        });
+
+        let vertices: Vec<Vertex> = batches
+            .iter()
+            .flat_map(|batch| batch.vertices.iter().copied())
+            .collect();
+        let indicies: Vec<u32> = batches
+            .iter()
+            .flat_map(|batch| batch.indicies.iter().copied())
+            .collect();
+
+        let buffers = &mut *self.batch_buffers;
+        let (vertex_range, index_range) = buffers.write(self.wgpu, &vertices, &indicies);
+
+        self.pass
+            .set_vertex_buffer(0, buffers.vertex_buffer.slice(vertex_range));
+        self.pass.set_index_buffer(
+            buffers.index_buffer.slice(index_range),
+            wgpu::IndexFormat::Uint32,
+        );
+
+        let resources = self.resources;
+        let mut first_index = 0;
+        let mut base_vertex = 0;
+
+        for batch in batches.iter() {
+            let index_range = first_index..first_index + batch.indicies.len() as u32;
+            let batch_vertex = base_vertex;
+            first_index = index_range.end;
+            base_vertex += batch.vertices.len() as i32;
+
+            let Some(shader) = resources.get_pipeline(&batch.pipeline_id) else {
+                continue;
+            };
+            let Some(texture) = resources.get_texture(&batch.texture_id) else {
+                continue;
+            };
+
+            self.pass.set_layer(batch.layer);
+            shader.set_active(self, batch.blend_mode);
+            self.pass.set_bind_group(0, &texture.bind_group);
+            self.pass.draw_indexed(index_range, batch_vertex, 0..1);
+        }
     }
 
     /// Gives the size of the current render surface
@@ -281,3 +559,529 @@ impl<'p, 'o> Renderer<'p, 'o> {
         self.size
     }
 }
+
+impl<'o, 'p> Drop for Renderer<'o, 'p>
+where
+    'o: 'p,
+{
+    fn drop(&mut self) {
+        self.flush_batch();
+        self.pass.finish();
+    }
+}
+
+// shapes from batched materials waiting to be drawn, grouped by
+// the state needed to draw them
+#[derive(Default)]
+pub(crate) struct SpriteBatch {
+    batches: Vec<Batch>,
+}
+
+impl SpriteBatch {
+    // the indicies are relative to the first of the given vertices
+    pub(crate) fn add(
+        &mut self,
+        layer: i32,
+        pipeline_id: ResourceId<Shader>,
+        blend_mode: Option<BlendMode>,
+        texture_id: ResourceId<Texture>,
+        vertices: &[Vertex],
+        indicies: &[u32],
+    ) {
+        let position = self.batches.iter().position(|batch| {
+            batch.layer == layer
+                && batch.pipeline_id == pipeline_id
+                && batch.blend_mode == blend_mode
+                && batch.texture_id == texture_id
+        });
+
+        let batch = match position {
+            Some(i) => &mut self.batches[i],
+            None => {
+                self.batches.push(Batch {
+                    layer,
+                    pipeline_id,
+                    blend_mode,
+                    texture_id,
+                    vertices: Vec::new(),
+                    indicies: Vec::new(),
+                });
+                self.batches.last_mut().unwrap()
+            }
+        };
+
+        let offset = batch.vertices.len() as u32;
+        batch.vertices.extend_from_slice(vertices);
+        batch
+            .indicies
+            .extend(indicies.iter().map(|index| index + offset));
+    }
+}
+
+// everything drawn with a Renderer is recorded here along with the state it needs,
+// then sorted by layer and drawn onto the real render pass when the Renderer is
+// dropped. this lets things be drawn in any order and still end up on their layer
+pub(crate) struct LayeredPass<'p> {
+    pass: wgpu::RenderPass<'p>,
+    layer: i32,
+    state: DrawState,
+    draws: Vec<Draw>,
+}
+
+impl<'p> LayeredPass<'p> {
+    fn new(pass: wgpu::RenderPass<'p>) -> Self {
+        Self {
+            pass,
+            layer: 0,
+            state: DrawState::default(),
+            draws: Vec::new(),
+        }
+    }
+
+    // the layer that draws are put on untill it is changed again
+    pub(crate) fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub(crate) fn set_pipeline(&mut self, pipeline: &wgpu::RenderPipeline) {
+        self.state.pipeline = Some(pipeline.clone());
+    }
+
+    pub(crate) fn set_bind_group(&mut self, index: u32, bind_group: &wgpu::BindGroup) {
+        self.state.bind_groups[index as usize] = Some(bind_group.clone());
+    }
+
+    pub(crate) fn set_vertex_buffer(&mut self, slot: u32, buffer_slice: wgpu::BufferSlice) {
+        self.state.vertex_buffers[slot as usize] = Some(BufferRange::from(buffer_slice));
+    }
+
+    pub(crate) fn set_index_buffer(
+        &mut self,
+        buffer_slice: wgpu::BufferSlice,
+        index_format: wgpu::IndexFormat,
+    ) {
+        self.state.index_buffer = Some((BufferRange::from(buffer_slice), index_format));
+    }
+
+    pub(crate) fn draw_indexed(
+        &mut self,
+        indicies: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    ) {
+        self.push(DrawKind::Indexed {
+            indicies,
+            base_vertex,
+            instances,
+        });
+    }
+
+    pub(crate) fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.push(DrawKind::Vertices {
+            vertices,
+            instances,
+        });
+    }
+
+    fn push(&mut self, kind: DrawKind) {
+        self.draws.push(Draw {
+            layer: self.layer,
+            state: self.state.clone(),
+            kind,
+        });
+    }
+
+    // the sort is stable so draws on the same layer keep the order they were made in
+    fn finish(&mut self) {
+        let mut draws = std::mem::take(&mut self.draws);
+        draws.sort_by_key(|draw| draw.layer);
+
+        // only what changes between draws is set again
+        let mut bound = DrawState::default();
+        for draw in draws {
+            let state = draw.state;
+            if state.pipeline != bound.pipeline {
+                if let Some(pipeline) = &state.pipeline {
+                    self.pass.set_pipeline(pipeline);
+                }
+            }
+
+            for (index, bind_group) in state.bind_groups.iter().enumerate() {
+                if *bind_group != bound.bind_groups[index] {
+                    if let Some(bind_group) = bind_group {
+                        self.pass.set_bind_group(index as u32, bind_group, &[]);
+                    }
+                }
+            }
+
+            for (slot, buffer) in state.vertex_buffers.iter().enumerate() {
+                if *buffer != bound.vertex_buffers[slot] {
+                    if let Some(buffer) = buffer {
+                        self.pass.set_vertex_buffer(slot as u32, buffer.slice());
+                    }
+                }
+            }
+
+            if state.index_buffer != bound.index_buffer {
+                if let Some((buffer, format)) = &state.index_buffer {
+                    self.pass.set_index_buffer(buffer.slice(), *format);
+                }
+            }
+
+            match draw.kind {
+                DrawKind::Indexed {
+                    indicies,
+                    base_vertex,
+                    instances,
+                } => self.pass.draw_indexed(indicies, base_vertex, instances),
+                DrawKind::Vertices {
+                    vertices,
+                    instances,
+                } => self.pass.draw(vertices, instances),
+            }
+
+            bound = state;
+        }
+    }
+}
+
+// everything that has to be set on the pass before a draw
+#[derive(Clone, Default)]
+struct DrawState {
+    pipeline: Option<wgpu::RenderPipeline>,
+    // the texture, the camera and then the shader's uniforms
+    bind_groups: [Option<wgpu::BindGroup>; 3],
+    vertex_buffers: [Option<BufferRange>; 2],
+    index_buffer: Option<(BufferRange, wgpu::IndexFormat)>,
+}
+
+#[derive(Clone, PartialEq)]
+struct BufferRange {
+    buffer: wgpu::Buffer,
+    range: Range<u64>,
+}
+
+impl BufferRange {
+    fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(self.range.clone())
+    }
+}
+
+impl From<wgpu::BufferSlice<'_>> for BufferRange {
+    fn from(value: wgpu::BufferSlice<'_>) -> Self {
+        let start = value.offset();
+        Self {
+            buffer: value.buffer().clone(),
+            range: start..start + value.size().get(),
+        }
+    }
+}
+
+struct Draw {
+    layer: i32,
+    state: DrawState,
+    kind: DrawKind,
+}
+
+enum DrawKind {
+    Indexed {
+        indicies: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    },
+    Vertices {
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    },
+}
+
+// the GPU side of the sprite batch, kept around between frames so the buffers are only
+// made again when they need to grow. every flush in a frame writes after the last one
+// as draws that have been recorded but not submitted yet still read from the buffers
+pub(crate) struct BatchBuffers {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    // bytes used so far this frame
+    vertex_count: u64,
+    index_count: u64,
+}
+
+impl BatchBuffers {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let vertex_size = std::mem::size_of::<Vertex>() as u64;
+        let index_size = std::mem::size_of::<u32>() as u64;
+        let (vertex_buffer, index_buffer) =
+            Material::<()>::create_buffers(device, vertex_size, 400, index_size, 600);
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            vertex_count: 0,
+            index_count: 0,
+        }
+    }
+
+    pub(crate) fn start_frame(&mut self) {
+        self.vertex_count = 0;
+        self.index_count = 0;
+    }
+
+    // returns the byte ranges the vertices and indicies were written to
+    fn write(
+        &mut self,
+        wgpu: &WgpuClump,
+        vertices: &[Vertex],
+        indicies: &[u32],
+    ) -> (Range<u64>, Range<u64>) {
+        let vertices: &[u8] = bytemuck::cast_slice(vertices);
+        let indicies: &[u8] = bytemuck::cast_slice(indicies);
+
+        let vertex_end = self.vertex_count + vertices.len() as u64;
+        if vertex_end > self.vertex_buffer.size() {
+            grow_buffer(
+                &mut self.vertex_buffer,
+                wgpu,
+                vertex_end,
+                wgpu::BufferUsages::VERTEX,
+            );
+        }
+
+        let index_end = self.index_count + indicies.len() as u64;
+        if index_end > self.index_buffer.size() {
+            grow_buffer(
+                &mut self.index_buffer,
+                wgpu,
+                index_end,
+                wgpu::BufferUsages::INDEX,
+            );
+        }
+
+        wgpu.queue
+            .write_buffer(&self.vertex_buffer, self.vertex_count, vertices);
+        wgpu.queue
+            .write_buffer(&self.index_buffer, self.index_count, indicies);
+
+        let ranges = (self.vertex_count..vertex_end, self.index_count..index_end);
+        self.vertex_count = vertex_end;
+        self.index_count = index_end;
+
+        ranges
+    }
+}
+
+struct Batch {
+    layer: i32,
+    pipeline_id: ResourceId<Shader>,
+    blend_mode: Option<BlendMode>,
+    texture_id: ResourceId<Texture>,
+    vertices: Vec<Vertex>,
+    indicies: Vec<u32>,
+}
+
+// what the defualt camera can see
+fn screen_area(size: Vec2<u32>) -> (Vec2<f32>, Vec2<f32>) {
+    (
+        Vec2 { x: 0.0, y: 0.0 },
+        Vec2 {
+            x: size.x as f32,
+            y: size.y as f32,
+        },
+    )
+}
+
+/// Copies a texture back from the GPU into an [RgbaImage]. This blocks untill
+/// the GPU has finished all submitted work.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_texture(
+    wgpu: &WgpuClump,
+    texture: &wgpu::Texture,
+) -> Result<RgbaImage, CaptureError> {
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        return Err(CaptureError::UnsupportedTexture);
+    }
+
+    let is_bgra = match texture.format() {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        format => return Err(CaptureError::UnsupportedFormat(format)),
+    };
+
+    let width = texture.width();
+    let height = texture.height();
+    let unpadded_row = width * 4;
+    // wgpu requires each row in the buffer to be aligned to 256 bytes
+    let padded_row = unpadded_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = wgpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: (padded_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = wgpu
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+
+    wgpu.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+
+    wgpu.device.poll(wgpu::PollType::Wait)?;
+    receiver
+        .recv()
+        .map_err(|_| CaptureError::MapFailed(None))?
+        .map_err(|e| CaptureError::MapFailed(Some(e)))?;
+
+    let mut pixels = Vec::with_capacity((unpadded_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if is_bgra {
+        pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2));
+    }
+
+    Ok(RgbaImage::from_raw(width, height, pixels).expect("buffer is the size of the image"))
+}
+
+// blocking on the GPU would freeze the browser and mapping only finishes
+// once control goes back to the event loop, so there is no way to do this
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn read_texture(
+    _wgpu: &WgpuClump,
+    _texture: &wgpu::Texture,
+) -> Result<RgbaImage, CaptureError> {
+    Err(CaptureError::UnsupportedPlatform)
+}
+
+/// Errors that can occur when reading a frame or texture back from the GPU
+#[derive(Debug)]
+pub enum CaptureError {
+    /// The texture cannot be copied from. This happens when the platform
+    /// does not allow reading from the window surface.
+    UnsupportedTexture,
+    /// Only 8 bit RGBA and BGRA textures can be captured
+    UnsupportedFormat(wgpu::TextureFormat),
+    /// The [UniformTexture] has not been created on the GPU yet. Render
+    /// to it at least once before capturing it.
+    NotCreatedYet,
+    /// Waiting for the GPU to finish failed
+    PollError(wgpu::PollError),
+    /// The copied data could not be mapped back to the CPU
+    MapFailed(Option<wgpu::BufferAsyncError>),
+    /// Reading back from the GPU is not supported on wasm
+    UnsupportedPlatform,
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedTexture => write!(f, "The texture does not support being copied from"),
+            Self::UnsupportedFormat(format) => {
+                write!(f, "Cannot capture textures with the format {:?}", format)
+            }
+            Self::NotCreatedYet => write!(f, "The texture has not been created yet"),
+            Self::PollError(e) => write!(f, "{}", e),
+            Self::MapFailed(Some(e)) => write!(f, "{}", e),
+            Self::MapFailed(None) => write!(f, "The capture buffer was never mapped"),
+            Self::UnsupportedPlatform => write!(f, "Capturing is not supported on this platform"),
+        }
+    }
+}
+
+impl Error for CaptureError {}
+
+impl From<wgpu::PollError> for CaptureError {
+    fn from(value: wgpu::PollError) -> Self {
+        Self::PollError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU64;
+
+    fn id<T>(number: u64) -> ResourceId<T> {
+        ResourceId::from_number(NonZeroU64::new(number).unwrap())
+    }
+
+    fn quad(x: f32) -> [Vertex; 4] {
+        [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+            .map(|[u, v]| Vertex::from_2d([x + u, v], [u, v], [1.0; 4]))
+    }
+
+    const QUAD_INDICIES: [u32; 6] = [0, 1, 2, 2, 3, 0];
+
+    #[test]
+    fn matching_shapes_share_a_batch() {
+        let mut batch = SpriteBatch::default();
+        batch.add(0, id(1), None, id(2), &quad(0.0), &QUAD_INDICIES);
+        batch.add(0, id(1), None, id(2), &quad(1.0), &QUAD_INDICIES);
+        batch.add(0, id(1), None, id(2), &quad(2.0), &QUAD_INDICIES);
+
+        assert_eq!(batch.batches.len(), 1);
+        let only = &batch.batches[0];
+        assert_eq!(only.vertices.len(), 12);
+        assert_eq!(only.vertices[4].position, [1.0, 0.0]);
+        assert_eq!(
+            only.indicies,
+            [0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4, 8, 9, 10, 10, 11, 8]
+        );
+    }
+
+    #[test]
+    fn diffrent_state_gets_its_own_batch() {
+        let mut batch = SpriteBatch::default();
+        batch.add(0, id(1), None, id(2), &quad(0.0), &QUAD_INDICIES);
+        batch.add(1, id(1), None, id(2), &quad(1.0), &QUAD_INDICIES);
+        batch.add(0, id(3), None, id(2), &quad(2.0), &QUAD_INDICIES);
+        let additive = Some(BlendMode::Additive);
+        batch.add(0, id(1), additive, id(2), &quad(3.0), &QUAD_INDICIES);
+        batch.add(0, id(1), None, id(4), &quad(4.0), &QUAD_INDICIES);
+        // goes back into the first batch after the others
+        batch.add(0, id(1), None, id(2), &quad(5.0), &QUAD_INDICIES);
+
+        assert_eq!(batch.batches.len(), 5);
+        let layers: Vec<i32> = batch.batches.iter().map(|b| b.layer).collect();
+        assert_eq!(layers, [0, 1, 0, 0, 0]);
+        assert_eq!(batch.batches[3].blend_mode, additive);
+        assert_eq!(batch.batches[4].texture_id, id(4));
+
+        // each batch counts its indicies from its own first vertex
+        for other in &batch.batches[1..] {
+            assert_eq!(other.indicies, QUAD_INDICIES);
+        }
+        let first = &batch.batches[0];
+        assert_eq!(first.vertices.len(), 8);
+        assert_eq!(first.vertices[4].position, [5.0, 0.0]);
+        assert_eq!(&first.indicies[6..], [4, 5, 6, 6, 7, 4]);
+    }
+}
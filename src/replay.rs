@@ -0,0 +1,554 @@
+//! Contains [InputRecording] which stores the input the game saw on every frame
+//! so it can be played back later. While a recording is playing the engine ignores
+//! real input and [Game::update](crate::Game::update) sees exactly what was recorded,
+//! including the frame delta time. This is usefull for reproducing bugs and for
+//! automated playthrough tests.
+//! ```rust,no_run
+//! # use bottomless_pit::engine_handle::Engine;
+//! # use bottomless_pit::input::Key;
+//! # use bottomless_pit::replay::InputRecording;
+//! # fn update(engine_handle: &mut Engine) {
+//! if engine_handle.is_key_pressed(Key::F5) {
+//!     engine_handle.start_recording_input();
+//! }
+//!
+//! if engine_handle.is_key_pressed(Key::F6) {
+//!     let recording = engine_handle.stop_recording_input().unwrap();
+//!     recording.save("bug_report.bpinput").unwrap();
+//! }
+//!
+//! if engine_handle.is_key_pressed(Key::F7) {
+//!     let recording = InputRecording::load("bug_report.bpinput").unwrap();
+//!     engine_handle.play_input_recording(recording);
+//! }
+//! # }
+//! ```
+//! Only input is recorded, anything else the game depends on like random numbers
+//! has to be made deterministic by the game itself.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::gamepad::{
+    GamepadAxis, GamepadButton, GamepadEvent, GamepadHandle, GamepadId, GamepadState,
+};
+use crate::input::{InputHandle, Touch, TouchPhase, INPUT_MAP_SIZE};
+use crate::vectors::Vec2;
+
+const MAGIC: &[u8; 4] = b"BPIR";
+const VERSION: u8 = 2;
+
+/// Every piece of input the game could see during one frame
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InputFrame {
+    pub(crate) delta_time: f32,
+    pub(crate) previous_keyboard_state: [bool; INPUT_MAP_SIZE],
+    pub(crate) current_keyboard_state: [bool; INPUT_MAP_SIZE],
+    pub(crate) previous_modifier_state: u32,
+    pub(crate) modifier_state: u32,
+    pub(crate) previous_mouse_state: [bool; 6],
+    pub(crate) current_mouse_state: [bool; 6],
+    pub(crate) current_text: Option<String>,
+    pub(crate) mouse_position: Vec2<f32>,
+    pub(crate) mouse_delta: Vec2<f32>,
+    pub(crate) wheel_delta: Vec2<f32>,
+    pub(crate) touches: Vec<Touch>,
+    pub(crate) gamepads: Vec<(GamepadId, GamepadState)>,
+    pub(crate) gamepad_events: Vec<GamepadEvent>,
+}
+
+/// The input from a series of frames, made with
+/// [Engine::start_recording_input](crate::engine_handle::Engine::start_recording_input)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputRecording {
+    pub(crate) frames: Vec<InputFrame>,
+}
+
+impl InputRecording {
+    /// The amount of frames that were recorded
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Checks if no frames were recorded
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Writes the recording to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordingError> {
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Reads a recording that was made with [InputRecording::save]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RecordingError> {
+        let data = std::fs::read(path)?;
+        Self::from_bytes(&data)
+    }
+
+    /// Converts the recording into its file format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::new());
+        writer.0.extend_from_slice(MAGIC);
+        writer.u8(VERSION);
+        writer.u32(self.frames.len() as u32);
+
+        for frame in self.frames.iter() {
+            writer.f32(frame.delta_time);
+            writer.bools(&frame.previous_keyboard_state);
+            writer.bools(&frame.current_keyboard_state);
+            writer.u32(frame.previous_modifier_state);
+            writer.u32(frame.modifier_state);
+            writer.bools(&frame.previous_mouse_state);
+            writer.bools(&frame.current_mouse_state);
+            match &frame.current_text {
+                Some(text) => {
+                    writer.u8(1);
+                    writer.u32(text.len() as u32);
+                    writer.0.extend_from_slice(text.as_bytes());
+                }
+                None => writer.u8(0),
+            }
+            writer.vec2(frame.mouse_position);
+            writer.vec2(frame.mouse_delta);
+            writer.vec2(frame.wheel_delta);
+
+            writer.u32(frame.touches.len() as u32);
+            for touch in frame.touches.iter() {
+                writer.u64(touch.id);
+                writer.vec2(touch.position);
+                writer.vec2(touch.start_position);
+                writer.u8(touch.phase as u8);
+                writer.u8(touch.started as u8);
+            }
+
+            writer.u32(frame.gamepads.len() as u32);
+            for (id, state) in frame.gamepads.iter() {
+                writer.u64(id.0 as u64);
+                writer.bools(&state.previous_buttons);
+                writer.bools(&state.current_buttons);
+                state.previous_axes.iter().for_each(|a| writer.f32(*a));
+                state.axes.iter().for_each(|a| writer.f32(*a));
+            }
+
+            writer.u32(frame.gamepad_events.len() as u32);
+            for event in frame.gamepad_events.iter() {
+                match *event {
+                    GamepadEvent::Connected(id) => {
+                        writer.u8(0);
+                        writer.u64(id.0 as u64);
+                    }
+                    GamepadEvent::Disconnected(id) => {
+                        writer.u8(1);
+                        writer.u64(id.0 as u64);
+                    }
+                    GamepadEvent::ButtonPressed(id, button) => {
+                        writer.u8(2);
+                        writer.u64(id.0 as u64);
+                        writer.u8(button as u8);
+                    }
+                    GamepadEvent::ButtonReleased(id, button) => {
+                        writer.u8(3);
+                        writer.u64(id.0 as u64);
+                        writer.u8(button as u8);
+                    }
+                    GamepadEvent::AxisChanged(id, axis, value) => {
+                        writer.u8(4);
+                        writer.u64(id.0 as u64);
+                        writer.u8(axis as u8);
+                        writer.f32(value);
+                    }
+                }
+            }
+        }
+
+        writer.0
+    }
+
+    /// Reads a recording from bytes made with [InputRecording::to_bytes]
+    pub fn from_bytes(data: &[u8]) -> Result<Self, RecordingError> {
+        let mut reader = Reader(data);
+        if reader.take(4)? != MAGIC {
+            return Err(RecordingError::InvalidFile);
+        }
+
+        if reader.u8()? != VERSION {
+            return Err(RecordingError::UnsupportedVersion);
+        }
+
+        let frame_count = reader.u32()?;
+        let mut frames = Vec::new();
+        for _ in 0..frame_count {
+            let delta_time = reader.f32()?;
+            let mut previous_keyboard_state = [false; INPUT_MAP_SIZE];
+            reader.bools(&mut previous_keyboard_state)?;
+            let mut current_keyboard_state = [false; INPUT_MAP_SIZE];
+            reader.bools(&mut current_keyboard_state)?;
+            let previous_modifier_state = reader.u32()?;
+            let modifier_state = reader.u32()?;
+            let mut previous_mouse_state = [false; 6];
+            reader.bools(&mut previous_mouse_state)?;
+            let mut current_mouse_state = [false; 6];
+            reader.bools(&mut current_mouse_state)?;
+
+            let current_text = match reader.u8()? {
+                0 => None,
+                _ => {
+                    let len = reader.u32()? as usize;
+                    let text = String::from_utf8(reader.take(len)?.to_vec())
+                        .map_err(|_| RecordingError::InvalidFile)?;
+                    Some(text)
+                }
+            };
+
+            let mouse_position = reader.vec2()?;
+            let mouse_delta = reader.vec2()?;
+            let wheel_delta = reader.vec2()?;
+
+            let mut touches = Vec::new();
+            for _ in 0..reader.u32()? {
+                touches.push(Touch {
+                    id: reader.u64()?,
+                    position: reader.vec2()?,
+                    start_position: reader.vec2()?,
+                    phase: touch_phase_from_u8(reader.u8()?)?,
+                    started: reader.u8()? != 0,
+                });
+            }
+
+            let mut gamepads = Vec::new();
+            for _ in 0..reader.u32()? {
+                let id = GamepadId(reader.u64()? as usize);
+                let mut state = GamepadState::default();
+                reader.bools(&mut state.previous_buttons)?;
+                reader.bools(&mut state.current_buttons)?;
+                for axis in state.previous_axes.iter_mut() {
+                    *axis = reader.f32()?;
+                }
+                for axis in state.axes.iter_mut() {
+                    *axis = reader.f32()?;
+                }
+                gamepads.push((id, state));
+            }
+
+            let mut gamepad_events = Vec::new();
+            for _ in 0..reader.u32()? {
+                let tag = reader.u8()?;
+                let id = GamepadId(reader.u64()? as usize);
+                let event = match tag {
+                    0 => GamepadEvent::Connected(id),
+                    1 => GamepadEvent::Disconnected(id),
+                    2 => GamepadEvent::ButtonPressed(id, button_from_u8(reader.u8()?)?),
+                    3 => GamepadEvent::ButtonReleased(id, button_from_u8(reader.u8()?)?),
+                    4 => {
+                        let axis = axis_from_u8(reader.u8()?)?;
+                        GamepadEvent::AxisChanged(id, axis, reader.f32()?)
+                    }
+                    _ => return Err(RecordingError::InvalidFile),
+                };
+                gamepad_events.push(event);
+            }
+
+            frames.push(InputFrame {
+                delta_time,
+                previous_keyboard_state,
+                current_keyboard_state,
+                previous_modifier_state,
+                modifier_state,
+                previous_mouse_state,
+                current_mouse_state,
+                current_text,
+                mouse_position,
+                mouse_delta,
+                wheel_delta,
+                touches,
+                gamepads,
+                gamepad_events,
+            });
+        }
+
+        Ok(Self { frames })
+    }
+}
+
+fn touch_phase_from_u8(value: u8) -> Result<TouchPhase, RecordingError> {
+    let phase = match value {
+        0 => TouchPhase::Started,
+        1 => TouchPhase::Moved,
+        2 => TouchPhase::Stationary,
+        3 => TouchPhase::Ended,
+        4 => TouchPhase::Cancelled,
+        _ => return Err(RecordingError::InvalidFile),
+    };
+
+    Ok(phase)
+}
+
+fn button_from_u8(value: u8) -> Result<GamepadButton, RecordingError> {
+    GamepadButton::ALL
+        .get(value as usize)
+        .copied()
+        .ok_or(RecordingError::InvalidFile)
+}
+
+fn axis_from_u8(value: u8) -> Result<GamepadAxis, RecordingError> {
+    GamepadAxis::ALL
+        .get(value as usize)
+        .copied()
+        .ok_or(RecordingError::InvalidFile)
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn vec2(&mut self, value: Vec2<f32>) {
+        self.f32(value.x);
+        self.f32(value.y);
+    }
+
+    // packs the bools into bits
+    fn bools(&mut self, values: &[bool]) {
+        for chunk in values.chunks(8) {
+            let byte = chunk
+                .iter()
+                .enumerate()
+                .fold(0_u8, |byte, (i, b)| byte | ((*b as u8) << i));
+            self.0.push(byte);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, amount: usize) -> Result<&[u8], RecordingError> {
+        if self.0.len() < amount {
+            return Err(RecordingError::InvalidFile);
+        }
+
+        let (taken, rest) = self.0.split_at(amount);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, RecordingError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, RecordingError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, RecordingError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, RecordingError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn vec2(&mut self) -> Result<Vec2<f32>, RecordingError> {
+        Ok(Vec2 {
+            x: self.f32()?,
+            y: self.f32()?,
+        })
+    }
+
+    fn bools(&mut self, out: &mut [bool]) -> Result<(), RecordingError> {
+        let bytes = self.take(out.len().div_ceil(8))?;
+        for (i, value) in out.iter_mut().enumerate() {
+            *value = bytes[i / 8] & (1 << (i % 8)) != 0;
+        }
+
+        Ok(())
+    }
+}
+
+/// What the engine is doing with input recordings
+pub(crate) enum ReplayState {
+    Idle,
+    Recording(InputRecording),
+    Replaying {
+        recording: InputRecording,
+        next_frame: usize,
+        // the real input keeps being tracked while the replay is going
+        // so nothing is stuck or missing once it is put back
+        live_input: Box<InputHandle>,
+        live_gamepads: Box<GamepadHandle>,
+    },
+}
+
+/// Errors that can occur when saving or loading an [InputRecording]
+#[derive(Debug)]
+pub enum RecordingError {
+    IoError(std::io::Error),
+    /// The data is not an input recording or has been cut off
+    InvalidFile,
+    /// The recording was made by a diffrent version of the engine
+    UnsupportedVersion,
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "{}", e),
+            Self::InvalidFile => write!(f, "The file is not a valid input recording"),
+            Self::UnsupportedVersion => {
+                write!(f, "The input recording was made by a diffrent version")
+            }
+        }
+    }
+}
+
+impl Error for RecordingError {}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputHandle;
+
+    fn test_recording() -> InputRecording {
+        let mut first = InputHandle::new().snapshot(1.0 / 60.0);
+        first.current_keyboard_state[3] = true;
+        first.previous_mouse_state[0] = true;
+        first.modifier_state = 0b100;
+        first.current_text = Some("hé".to_string());
+        first.mouse_position = Vec2 { x: 10.5, y: -2.0 };
+        first.wheel_delta = Vec2 { x: 0.0, y: 1.0 };
+        first.touches.push(Touch {
+            id: 9,
+            position: Vec2 { x: 1.0, y: 2.0 },
+            start_position: Vec2 { x: 0.0, y: 0.0 },
+            phase: TouchPhase::Ended,
+            started: true,
+        });
+
+        let mut state = GamepadState::default();
+        state.current_buttons[GamepadButton::South as usize] = true;
+        state.axes[GamepadAxis::RightTrigger as usize] = 0.75;
+        let second = InputFrame {
+            gamepads: vec![(GamepadId(2), state)],
+            gamepad_events: vec![
+                GamepadEvent::Connected(GamepadId(2)),
+                GamepadEvent::Disconnected(GamepadId(3)),
+                GamepadEvent::ButtonPressed(GamepadId(2), GamepadButton::DPadRight),
+                GamepadEvent::ButtonReleased(GamepadId(2), GamepadButton::West),
+                GamepadEvent::AxisChanged(GamepadId(2), GamepadAxis::LeftStickY, -0.5),
+            ],
+            ..InputHandle::new().snapshot(0.5)
+        };
+
+        InputRecording {
+            frames: vec![first, second],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = test_recording();
+        let bytes = recording.to_bytes();
+        assert!(bytes.starts_with(MAGIC));
+
+        let loaded = InputRecording::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.len(), 2);
+    }
+
+    #[test]
+    fn empty_round_trip() {
+        let bytes = InputRecording::default().to_bytes();
+        assert!(InputRecording::from_bytes(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn truncated_data_is_invalid() {
+        let bytes = test_recording().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                matches!(
+                    InputRecording::from_bytes(&bytes[..len]),
+                    Err(RecordingError::InvalidFile)
+                ),
+                "cut off at {} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_magic_and_version() {
+        let mut bytes = test_recording().to_bytes();
+        bytes[4] = VERSION + 1;
+        assert!(matches!(
+            InputRecording::from_bytes(&bytes),
+            Err(RecordingError::UnsupportedVersion)
+        ));
+
+        bytes[0] = b'X';
+        assert!(matches!(
+            InputRecording::from_bytes(&bytes),
+            Err(RecordingError::InvalidFile)
+        ));
+    }
+
+    #[test]
+    fn corrupt_values_are_invalid() {
+        let recording = InputRecording {
+            frames: vec![InputFrame {
+                gamepad_events: vec![GamepadEvent::ButtonPressed(
+                    GamepadId(0),
+                    GamepadButton::South,
+                )],
+                ..InputHandle::new().snapshot(0.0)
+            }],
+        };
+        let bytes = recording.to_bytes();
+        // the button is the very last byte and the event tag is before the id
+        let mut bad_button = bytes.clone();
+        *bad_button.last_mut().unwrap() = 200;
+        assert!(matches!(
+            InputRecording::from_bytes(&bad_button),
+            Err(RecordingError::InvalidFile)
+        ));
+
+        let mut bad_tag = bytes.clone();
+        let tag = bytes.len() - 1 - 8 - 1;
+        bad_tag[tag] = 9;
+        assert!(matches!(
+            InputRecording::from_bytes(&bad_tag),
+            Err(RecordingError::InvalidFile)
+        ));
+
+        // a huge frame count has nothing behind it
+        let mut bad_count = bytes;
+        bad_count[5..9].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            InputRecording::from_bytes(&bad_count),
+            Err(RecordingError::InvalidFile)
+        ));
+    }
+}
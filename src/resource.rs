@@ -40,24 +40,42 @@
 //! ```
 //! Because of this stalling behavior it is recomended you do all your loading of assests in as large of chunks as possible.
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicU64;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, Weak};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant, SystemTime};
 
-use winit::event_loop::EventLoopProxy;
-
-use crate::engine_handle::BpEvent;
+use crate::archive::AssetArchive;
+use crate::engine_handle::{BpEvent, BpProxy, Engine};
 use crate::shader::{IntermediateOptions, Shader};
+use crate::sound::{Music, Sound};
+use crate::sprite::SpriteSheet;
 use crate::text::Font;
-use crate::texture::{SamplerType, Texture};
+use crate::texture::{Texture, TextureOptions};
+use crate::tilemap::Tilemap;
+use crate::vectors::Vec2;
 
 #[cfg(not(target_arch = "wasm32"))]
 use futures::executor::ThreadPool;
+#[cfg(target_arch = "wasm32")]
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+
+#[cfg(target_arch = "wasm32")]
+async fn web_read<P: AsRef<Path>>(path: P, vfs: &Vfs) -> Result<Vec<u8>, ReadError> {
+    match vfs.read(path.as_ref()).await {
+        Some(data) => Ok(data),
+        None => fetch(path).await,
+    }
+}
 
 #[cfg(target_arch = "wasm32")]
-async fn web_read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, ReadError> {
+async fn fetch<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, ReadError> {
     use js_sys::Uint8Array;
     use wasm_bindgen::JsCast;
     use wasm_bindgen_futures::JsFuture;
@@ -88,8 +106,150 @@ async fn web_read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, ReadError> {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub(crate) async fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, ReadError> {
-    Ok(std::fs::read(path)?)
+pub(crate) fn read<P: AsRef<Path>>(path: P, vfs: &Vfs) -> Result<Vec<u8>, ReadError> {
+    match vfs.read(path.as_ref()) {
+        Some(data) => Ok(data),
+        None => Ok(std::fs::read(path)?),
+    }
+}
+
+// reads a resource along with the other files it needs
+#[cfg(not(target_arch = "wasm32"))]
+fn read_resource(
+    path: &Path,
+    resource_type: &ResourceType,
+    vfs: &Vfs,
+) -> Result<(Vec<u8>, ExtraFiles), ReadError> {
+    let data = read(path, vfs)?;
+    let mut extra_files = ExtraFiles::new();
+    for file in extra_file_paths(&data, path, resource_type) {
+        let extra = read(&file, vfs)
+            .inspect_err(|e| log::error!("could not read {:?} for {:?}: {}", file, path, e))?;
+        extra_files.insert(file, extra);
+    }
+
+    Ok((data, extra_files))
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn web_read_resource(
+    path: &Path,
+    resource_type: &ResourceType,
+    vfs: &Vfs,
+) -> Result<(Vec<u8>, ExtraFiles), ReadError> {
+    let data = web_read(path, vfs).await?;
+    let mut extra_files = ExtraFiles::new();
+    for file in extra_file_paths(&data, path, resource_type) {
+        let extra = web_read(&file, vfs)
+            .await
+            .inspect_err(|e| log::error!("could not read {:?} for {:?}: {}", file, path, e))?;
+        extra_files.insert(file, extra);
+    }
+
+    Ok((data, extra_files))
+}
+
+fn extra_file_paths(data: &[u8], path: &Path, resource_type: &ResourceType) -> Vec<PathBuf> {
+    match resource_type {
+        ResourceType::Tilemap => Tilemap::external_tilesets(data, path),
+        _ => Vec::new(),
+    }
+}
+
+// other files a resource needs by their path, like tilesets saved outside of a tilemap
+pub(crate) type ExtraFiles = HashMap<PathBuf, Vec<u8>>;
+
+// the archives mounted on the engine, shared with anything loading resources
+// so they are checked before the file system
+#[derive(Clone, Default)]
+pub(crate) struct Vfs {
+    archives: Arc<Mutex<Vec<MountedArchive>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type MountedArchive = Arc<AssetArchive>;
+
+// on wasm archives can still be downloading when they are mounted, so
+// reads wait for them to finish
+#[cfg(target_arch = "wasm32")]
+type MountedArchive = Shared<LocalBoxFuture<'static, Option<Arc<AssetArchive>>>>;
+
+impl Vfs {
+    pub fn mount(&self, archive: AssetArchive) {
+        let archive = Arc::new(archive);
+        #[cfg(target_arch = "wasm32")]
+        let archive = futures::future::ready(Some(archive)).boxed_local().shared();
+        self.archives.lock().unwrap().push(archive);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn mount_file(&self, path: &Path) {
+        match AssetArchive::load(path) {
+            Ok(archive) => {
+                self.mount(archive);
+                log::info!("archive at: {:?} mounted succesfully", path);
+            }
+            Err(e) => log::error!("could not mount archive: {:?}, becuase: {}", path, e),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn mount_file(&self, path: &Path) {
+        let path = path.to_owned();
+        let archive = async move {
+            let archive = match fetch(&path).await {
+                Ok(data) => AssetArchive::from_bytes(data).map_err(|e| e.to_string()),
+                Err(e) => Err(format!("{:?}", e)),
+            };
+
+            match archive {
+                Ok(archive) => {
+                    log::info!("archive at: {:?} mounted succesfully", path);
+                    Some(Arc::new(archive))
+                }
+                Err(e) => {
+                    log::error!("could not mount archive: {:?}, becuase: {}", path, e);
+                    None
+                }
+            }
+        }
+        .boxed_local()
+        .shared();
+
+        // starts the download now instead of when the first resource is read
+        wasm_bindgen_futures::spawn_local(archive.clone().map(|_| ()));
+        self.archives.lock().unwrap().push(archive);
+    }
+
+    pub fn unmount_all(&self) {
+        self.archives.lock().unwrap().clear();
+    }
+
+    // the last archive mounted is checked first so patches can be mounted over the base game
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        let archives = self.archives.lock().unwrap();
+        archives
+            .iter()
+            .rev()
+            .find_map(|archive| archive.get_file(path))
+            .map(|data| data.to_vec())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        let archives = self.archives.lock().unwrap().clone();
+        for archive in archives.into_iter().rev() {
+            let data = archive
+                .await
+                .and_then(|archive| archive.get_file(path).map(|data| data.to_vec()));
+            if data.is_some() {
+                return data;
+            }
+        }
+
+        None
+    }
 }
 
 pub(crate) enum ReadError {
@@ -112,6 +272,18 @@ impl Debug for ReadError {
     }
 }
 
+impl Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "{}", e),
+            #[cfg(target_arch = "wasm32")]
+            Self::ResponseError(code, text) => write!(f, "{} {}", code, text),
+            #[cfg(target_arch = "wasm32")]
+            Self::WindowError => write!(f, "could not get the browser window"),
+        }
+    }
+}
+
 impl From<std::io::Error> for ReadError {
     fn from(value: std::io::Error) -> Self {
         Self::IoError(value)
@@ -121,17 +293,29 @@ impl From<std::io::Error> for ReadError {
 pub(crate) struct Loader {
     blocked_loading: usize,
     background_loading: usize,
+    statuses: HashMap<NonZeroU64, LoadStatus>,
+    progress: LoadingProgress,
     #[cfg(not(target_arch = "wasm32"))]
     pool: ThreadPool,
+    vfs: Vfs,
+    // only exists when hot reloading is turned on
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<Watcher>,
 }
 
 impl Loader {
-    pub fn new() -> Self {
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn new(hot_reloading: bool) -> Self {
         Self {
             background_loading: 0,
             blocked_loading: 0,
+            statuses: HashMap::new(),
+            progress: LoadingProgress::default(),
             #[cfg(not(target_arch = "wasm32"))]
             pool: ThreadPool::new().unwrap(),
+            vfs: Vfs::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher: hot_reloading.then(Watcher::new),
         }
     }
 
@@ -142,16 +326,88 @@ impl Loader {
         }
     }
 
+    pub fn get_vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
     pub fn get_loading_resources(&self) -> usize {
         self.background_loading + self.blocked_loading
     }
 
+    pub fn get_status(&self, id: NonZeroU64) -> Option<&LoadStatus> {
+        self.statuses.get(&id)
+    }
+
+    pub fn get_progress(&self) -> LoadingProgress {
+        self.progress
+    }
+
+    // stops tracking an unloaded resource so anything still loading for it is thrown away
+    pub fn forget(&mut self, id: NonZeroU64) {
+        if self.statuses.remove(&id) == Some(LoadStatus::Pending) {
+            self.progress.total -= 1;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(watcher) = &mut self.watcher {
+            watcher.files.retain(|file| file.id != id);
+        }
+    }
+
+    // for resources that are already in memory, they skip reading and go straight to
+    // the engine so they are ready by the next frame like a blocking load
+    pub fn load_from_memory(
+        &mut self,
+        data: Vec<u8>,
+        id: NonZeroU64,
+        resource_type: ResourceType,
+        proxy: BpProxy,
+    ) {
+        self.start_loading(id);
+        let resource = Resource::from_result(
+            Ok((data, ExtraFiles::new())),
+            PathBuf::new(),
+            id,
+            resource_type,
+            LoadingOp::Blocking,
+        );
+        self.blocked_loading += 1;
+        proxy.send_event(BpEvent::ResourceLoaded(resource)).unwrap();
+    }
+
+    // must be called before the loading counters go up
+    fn start_loading(&mut self, id: NonZeroU64) {
+        // a new round of loading starts once everything before it is done
+        if self.get_loading_resources() == 0 {
+            self.progress = LoadingProgress::default();
+        }
+
+        self.progress.total += 1;
+        self.statuses.insert(id, LoadStatus::Pending);
+    }
+
+    pub fn finish_loading(&mut self, id: NonZeroU64, result: Result<usize, LoadError>) {
+        self.progress.finished += 1;
+        let status = match result {
+            Ok(bytes) => {
+                self.progress.bytes_loaded += bytes as u64;
+                LoadStatus::Loaded
+            }
+            Err(e) => {
+                self.progress.failed += 1;
+                LoadStatus::Failed(e)
+            }
+        };
+
+        self.statuses.insert(id, status);
+    }
+
     #[cfg(target_arch = "wasm32")]
     pub fn is_blocked(&self) -> bool {
         self.blocked_loading > 0
     }
 
-    pub fn load(&mut self, ip_resource: InProgressResource, proxy: EventLoopProxy<BpEvent>) {
+    pub fn load(&mut self, ip_resource: InProgressResource, proxy: BpProxy) {
         match ip_resource.loading_op {
             LoadingOp::Background => self.background_load(ip_resource, proxy),
             LoadingOp::Blocking => self.blocking_load(ip_resource, proxy),
@@ -162,15 +418,10 @@ impl Loader {
     // becuase this is all happening on the main thread stuff will be read in before
     // render() is called
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn blocking_load(
-        &mut self,
-        ip_resource: InProgressResource,
-        proxy: EventLoopProxy<BpEvent>,
-    ) {
-        let data: Result<Vec<u8>, ReadError> = match std::fs::read(&ip_resource.path) {
-            Ok(d) => Ok(d),
-            Err(e) => Err(e.into()),
-        };
+    pub fn blocking_load(&mut self, ip_resource: InProgressResource, proxy: BpProxy) {
+        self.watch(&ip_resource);
+        self.start_loading(ip_resource.id);
+        let data = read_resource(&ip_resource.path, &ip_resource.resource_type, &self.vfs);
 
         let resource = Resource::from_result(
             data,
@@ -185,15 +436,14 @@ impl Loader {
 
     // request but flip flag :3
     #[cfg(target_arch = "wasm32")]
-    pub fn blocking_load(
-        &mut self,
-        ip_resource: InProgressResource,
-        proxy: EventLoopProxy<BpEvent>,
-    ) {
+    pub fn blocking_load(&mut self, ip_resource: InProgressResource, proxy: BpProxy) {
         use wasm_bindgen_futures::spawn_local;
+        self.start_loading(ip_resource.id);
         self.blocked_loading += 1;
+        let vfs = self.vfs.clone();
         spawn_local(async move {
-            let result = web_read(&ip_resource.path).await;
+            let result =
+                web_read_resource(&ip_resource.path, &ip_resource.resource_type, &vfs).await;
             let resource = Resource::from_result(
                 result,
                 ip_resource.path,
@@ -206,16 +456,15 @@ impl Loader {
     }
 
     // threadpool / aysnc
-    pub fn background_load(
-        &mut self,
-        ip_resource: InProgressResource,
-        proxy: EventLoopProxy<BpEvent>,
-    ) {
+    pub fn background_load(&mut self, ip_resource: InProgressResource, proxy: BpProxy) {
+        self.start_loading(ip_resource.id);
         self.background_loading += 1;
         #[cfg(not(target_arch = "wasm32"))]
         {
+            self.watch(&ip_resource);
+            let vfs = self.vfs.clone();
             self.pool.spawn_ok(async move {
-                let result = read(&ip_resource.path).await;
+                let result = read_resource(&ip_resource.path, &ip_resource.resource_type, &vfs);
                 let resource = Resource::from_result(
                     result,
                     ip_resource.path,
@@ -230,8 +479,10 @@ impl Loader {
         #[cfg(target_arch = "wasm32")]
         {
             use wasm_bindgen_futures::spawn_local;
+            let vfs = self.vfs.clone();
             spawn_local(async move {
-                let result = web_read(&ip_resource.path).await;
+                let result =
+                    web_read_resource(&ip_resource.path, &ip_resource.resource_type, &vfs).await;
                 let resource = Resource::from_result(
                     result,
                     ip_resource.path,
@@ -243,12 +494,138 @@ impl Loader {
             });
         }
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn watch(&mut self, ip_resource: &InProgressResource) {
+        let kind = match ip_resource.resource_type {
+            ResourceType::Image(options) => ReloadKind::Image(options),
+            ResourceType::Shader(_) => ReloadKind::Shader,
+            ResourceType::Font => ReloadKind::Font,
+            _ => return,
+        };
+
+        if let Some(watcher) = &mut self.watcher {
+            watcher.add(&ip_resource.path, ip_resource.id, kind);
+        }
+    }
+
+    // rereads any watched files that changed on the threadpool, these dont count
+    // as loading resources so the game keeps running with the old version untill
+    // the new one is ready
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn check_for_changes(&mut self, proxy: &BpProxy) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+
+        for file in watcher.changed_files() {
+            let proxy = proxy.clone();
+            self.pool.spawn_ok(async move {
+                // the watched file is on disk even if an archive has the same path
+                let data = std::fs::read(&file.path).map_err(ReadError::from);
+                let reloaded = ReloadedResource {
+                    path: file.path,
+                    data,
+                    id: file.id,
+                    kind: file.kind,
+                };
+                proxy
+                    .send_event(BpEvent::ResourceReloaded(reloaded))
+                    .unwrap();
+            });
+        }
+    }
+}
+
+// how often the modified times of watched files are checked
+#[cfg(not(target_arch = "wasm32"))]
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+// polls the modified time of every file that was loaded while hot reloading
+// is on, doesnt need any platform specific file watching
+#[cfg(not(target_arch = "wasm32"))]
+struct Watcher {
+    files: Vec<WatchedFile>,
+    last_check: Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Watcher {
+    fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            last_check: Instant::now(),
+        }
+    }
+
+    fn add(&mut self, path: &Path, id: NonZeroU64, kind: ReloadKind) {
+        let modified = modified_time(path);
+        self.files.push(WatchedFile {
+            path: path.to_owned(),
+            id,
+            kind,
+            modified,
+        });
+    }
+
+    fn changed_files(&mut self) -> Vec<WatchedFile> {
+        if self.last_check.elapsed() < WATCH_INTERVAL {
+            return Vec::new();
+        }
+        self.last_check = Instant::now();
+
+        let mut changed = Vec::new();
+        for file in self.files.iter_mut() {
+            let modified = modified_time(&file.path);
+            // files that are gone or half written by an editor are picked up on the next change
+            if modified.is_some() && modified != file.modified {
+                file.modified = modified;
+                changed.push(file.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+struct WatchedFile {
+    path: PathBuf,
+    id: NonZeroU64,
+    kind: ReloadKind,
+    modified: Option<SystemTime>,
+}
+
+/// The resources that can be hot reloaded, shaders dont need their options as
+/// the reload reuses the ones from the shader already in the [ResourceManager]
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ReloadKind {
+    Image(TextureOptions),
+    Shader,
+    Font,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub(crate) struct ReloadedResource {
+    pub(crate) path: PathBuf,
+    pub(crate) data: Result<Vec<u8>, ReadError>,
+    pub(crate) id: NonZeroU64,
+    pub(crate) kind: ReloadKind,
 }
 
 #[derive(Debug)]
 pub(crate) struct Resource {
     pub(crate) path: PathBuf,
     pub(crate) data: Vec<u8>,
+    pub(crate) extra_files: ExtraFiles,
     pub(crate) id: NonZeroU64,
     pub(crate) resource_type: ResourceType,
     pub(crate) loading_op: LoadingOp,
@@ -257,7 +634,7 @@ pub(crate) struct Resource {
 #[derive(Debug)]
 pub(crate) struct ResourceError {
     pub(crate) error: ReadError,
-    _path: PathBuf,
+    pub(crate) path: PathBuf,
     pub(crate) id: NonZeroU64,
     pub(crate) resource_type: ResourceType,
     pub(crate) loading_op: LoadingOp,
@@ -265,23 +642,24 @@ pub(crate) struct ResourceError {
 
 impl Resource {
     pub fn from_result(
-        result: Result<Vec<u8>, ReadError>,
+        result: Result<(Vec<u8>, ExtraFiles), ReadError>,
         path: PathBuf,
         id: NonZeroU64,
         resource_type: ResourceType,
         loading_op: LoadingOp,
     ) -> Result<Self, ResourceError> {
         match result {
-            Ok(data) => Ok(Self {
+            Ok((data, extra_files)) => Ok(Self {
                 path,
                 data,
+                extra_files,
                 id,
                 resource_type,
                 loading_op,
             }),
             Err(e) => Err(ResourceError {
                 error: e,
-                _path: path,
+                path,
                 id,
                 resource_type,
                 loading_op,
@@ -316,10 +694,16 @@ impl InProgressResource {
 
 #[derive(Debug)]
 pub(crate) enum ResourceType {
-    Image(SamplerType, SamplerType),
+    Image(TextureOptions),
+    // rgba pixels that dont need decoding
+    RawImage(Vec2<u32>, TextureOptions),
     Shader(IntermediateOptions),
     Bytes,
     Font,
+    Sound,
+    Music,
+    SpriteSheet,
+    Tilemap,
 }
 
 impl PartialEq for ResourceType {
@@ -327,15 +711,123 @@ impl PartialEq for ResourceType {
         match (self, other) {
             (Self::Bytes, Self::Bytes) => true,
             (Self::Font, Self::Font) => true,
+            (Self::Sound, Self::Sound) => true,
+            (Self::Music, Self::Music) => true,
+            (Self::SpriteSheet, Self::SpriteSheet) => true,
+            (Self::Tilemap, Self::Tilemap) => true,
             (Self::Shader(option_1), Self::Shader(option_2)) => {
                 option_1.check_has() == option_2.check_has()
             }
-            (Self::Image(s1, s2), Self::Image(s3, s4)) => s1 == s3 && s2 == s4,
+            (Self::Image(options_1), Self::Image(options_2)) => options_1 == options_2,
+            (Self::RawImage(size_1, options_1), Self::RawImage(size_2, options_2)) => {
+                size_1 == size_2 && options_1 == options_2
+            }
             _ => false,
         }
     }
 }
 
+/// Where a resource is in the loading process, see
+/// [Engine::get_load_status](crate::engine_handle::Engine::get_load_status)
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadStatus {
+    /// The resource is still being read
+    Pending,
+    /// The resource is ready to use
+    Loaded,
+    /// The resource couldnt be loaded and a defualt replacement is being used instead
+    Failed(LoadError),
+}
+
+/// Why a resource failed to load
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// The file couldnt be read from the file system or downloaded
+    ReadError(String),
+    /// The file was read but isnt valid for the type of resource, like a
+    /// corrupt png or a sound file in a format that isnt supported
+    InvalidData(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadError(e) => write!(f, "could not read the file: {}", e),
+            Self::InvalidData(e) => write!(f, "the file is not valid: {}", e),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+/// How far along the current round of loading is, see
+/// [Engine::get_loading_progress](crate::engine_handle::Engine::get_loading_progress).
+/// A new round starts when a resource is loaded while nothing else is loading, so
+/// everything loaded for one loading screen is counted together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadingProgress {
+    /// Resources that have loaded or failed
+    pub finished: usize,
+    /// Resources that failed, these are counted in `finished` too
+    pub failed: usize,
+    /// Every resource in this round of loading
+    pub total: usize,
+    /// The size of all the files that have been read
+    pub bytes_loaded: u64,
+}
+
+impl LoadingProgress {
+    /// How much of the loading is done from 0.0 to 1.0, is 1.0 when nothing is loading
+    pub fn get_fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.finished as f32 / self.total as f32
+        }
+    }
+
+    /// Checks if everything in this round has loaded or failed
+    pub fn is_done(&self) -> bool {
+        self.finished == self.total
+    }
+}
+
+/// Given to [Game::on_resource_loaded](crate::Game::on_resource_loaded) once a resource
+/// has loaded or failed to load
+#[derive(Debug, Clone)]
+pub struct LoadEvent {
+    id: NonZeroU64,
+    path: PathBuf,
+    error: Option<LoadError>,
+}
+
+impl LoadEvent {
+    pub(crate) fn new(id: NonZeroU64, path: PathBuf, error: Option<LoadError>) -> Self {
+        Self { id, path, error }
+    }
+
+    /// Checks if the event is for this resource
+    pub fn is_resource<T>(&self, id: ResourceId<T>) -> bool {
+        self.id == id.get_id()
+    }
+
+    /// The path the resource was loaded from, this is empty for resources made
+    /// from memory like [Texture::from_bytes]
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Why the resource failed, `None` when it loaded succesfully
+    pub fn get_error(&self) -> Option<&LoadError> {
+        self.error.as_ref()
+    }
+
+    /// Checks if the resource loaded without any errors
+    pub fn is_loaded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
 /// This enum is used to control how resources are loaded.
 /// Background loading will load the resource on another thread
 /// and the resource will be ready when its ready. Blocking Loads
@@ -404,6 +896,10 @@ pub(crate) struct ResourceManager {
     bindgroup_resources: ResourceMap<Texture>,
     pipeline_resource: ResourceMap<Shader>,
     fonts: ResourceMap<Font>,
+    sounds: ResourceMap<Sound>,
+    music: ResourceMap<Music>,
+    sprite_sheets: ResourceMap<SpriteSheet>,
+    tilemaps: ResourceMap<Tilemap>,
 }
 
 impl ResourceManager {
@@ -413,6 +909,10 @@ impl ResourceManager {
             bindgroup_resources: HashMap::new(),
             pipeline_resource: HashMap::new(),
             fonts: HashMap::new(),
+            sounds: HashMap::new(),
+            music: HashMap::new(),
+            sprite_sheets: HashMap::new(),
+            tilemaps: HashMap::new(),
         }
     }
 
@@ -428,8 +928,25 @@ impl ResourceManager {
         self.pipeline_resource.insert(key, data);
     }
 
-    pub fn insert_font(&mut self, key: ResourceId<Font>, data: Font) {
-        self.fonts.insert(key, data);
+    // gives back the font it replaced so its faces can be removed
+    pub fn insert_font(&mut self, key: ResourceId<Font>, data: Font) -> Option<Font> {
+        self.fonts.insert(key, data)
+    }
+
+    pub fn insert_sound(&mut self, key: ResourceId<Sound>, data: Sound) {
+        self.sounds.insert(key, data);
+    }
+
+    pub fn insert_music(&mut self, key: ResourceId<Music>, data: Music) {
+        self.music.insert(key, data);
+    }
+
+    pub fn insert_sprite_sheet(&mut self, key: ResourceId<SpriteSheet>, data: SpriteSheet) {
+        self.sprite_sheets.insert(key, data);
+    }
+
+    pub fn insert_tilemap(&mut self, key: ResourceId<Tilemap>, data: Tilemap) {
+        self.tilemaps.insert(key, data);
     }
 
     pub fn get_byte_resource(&self, key: &ResourceId<Vec<u8>>) -> Option<&Vec<u8>> {
@@ -448,7 +965,318 @@ impl ResourceManager {
         self.fonts.get(key)
     }
 
+    pub fn get_sound(&self, key: &ResourceId<Sound>) -> Option<&Sound> {
+        self.sounds.get(key)
+    }
+
+    pub fn get_music(&self, key: &ResourceId<Music>) -> Option<&Music> {
+        self.music.get(key)
+    }
+
+    pub fn get_sprite_sheet(&self, key: &ResourceId<SpriteSheet>) -> Option<&SpriteSheet> {
+        self.sprite_sheets.get(key)
+    }
+
+    pub fn get_tilemap(&self, key: &ResourceId<Tilemap>) -> Option<&Tilemap> {
+        self.tilemaps.get(key)
+    }
+
     pub fn get_mut_shader(&mut self, key: &ResourceId<Shader>) -> Option<&mut Shader> {
         self.pipeline_resource.get_mut(key)
     }
+
+    pub fn remove_bytes(&mut self, key: &ResourceId<Vec<u8>>) -> Option<Vec<u8>> {
+        self.btye_resources.remove(key)
+    }
+
+    pub fn remove_texture(&mut self, key: &ResourceId<Texture>) -> Option<Texture> {
+        self.bindgroup_resources.remove(key)
+    }
+
+    pub fn remove_pipeline(&mut self, key: &ResourceId<Shader>) -> Option<Shader> {
+        self.pipeline_resource.remove(key)
+    }
+
+    pub fn remove_font(&mut self, key: &ResourceId<Font>) -> Option<Font> {
+        self.fonts.remove(key)
+    }
+
+    pub fn remove_sound(&mut self, key: &ResourceId<Sound>) -> Option<Sound> {
+        self.sounds.remove(key)
+    }
+
+    pub fn remove_music(&mut self, key: &ResourceId<Music>) -> Option<Music> {
+        self.music.remove(key)
+    }
+
+    pub fn remove_sprite_sheet(&mut self, key: &ResourceId<SpriteSheet>) -> Option<SpriteSheet> {
+        self.sprite_sheets.remove(key)
+    }
+
+    pub fn remove_tilemap(&mut self, key: &ResourceId<Tilemap>) -> Option<Tilemap> {
+        self.tilemaps.remove(key)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Every type the engine loads and keeps track of with a [ResourceId], these can be
+/// unloaded with [Engine::unload]
+pub trait EngineResource: sealed::Sealed + Sized {
+    #[doc(hidden)]
+    fn remove(engine: &mut Engine, id: ResourceId<Self>);
+}
+
+macro_rules! engine_resource {
+    ($resource:ty, $remove:ident) => {
+        impl sealed::Sealed for $resource {}
+
+        impl EngineResource for $resource {
+            fn remove(engine: &mut Engine, id: ResourceId<Self>) {
+                engine.resource_manager.$remove(&id);
+            }
+        }
+    };
+}
+
+engine_resource!(Vec<u8>, remove_bytes);
+engine_resource!(Shader, remove_pipeline);
+engine_resource!(Sound, remove_sound);
+engine_resource!(Music, remove_music);
+engine_resource!(SpriteSheet, remove_sprite_sheet);
+engine_resource!(Tilemap, remove_tilemap);
+
+impl sealed::Sealed for Texture {}
+
+impl EngineResource for Texture {
+    fn remove(engine: &mut Engine, id: ResourceId<Self>) {
+        engine.resource_manager.remove_texture(&id);
+        engine.post_processing.texture_changed(&id);
+    }
+}
+
+impl sealed::Sealed for Font {}
+
+impl EngineResource for Font {
+    fn remove(engine: &mut Engine, id: ResourceId<Self>) {
+        let font = engine.resource_manager.remove_font(&id);
+        if let (Some(font), Some(context)) = (font, engine.context.as_mut()) {
+            context.text_renderer.unload_font(&font);
+        }
+    }
+}
+
+// sent when the last clone of a handle is dropped, the function knows the type
+// of resource so the engine can unload it
+pub(crate) type DroppedHandle = (NonZeroU64, fn(&mut Engine, NonZeroU64));
+
+// the handles that are alive for each resource so making another handle for the
+// same resource shares its count
+pub(crate) type HandleCounts = HashMap<NonZeroU64, Weak<HandleInner>>;
+
+/// A reference counted [ResourceId] made with [Engine::create_handle]. The resource is
+/// unloaded at the start of the next frame after the last handle for it is dropped,
+/// calling [Engine::create_handle] again for the same resource shares the same count.
+/// ```rust,no_run
+/// # use bottomless_pit::engine_handle::Engine;
+/// # use bottomless_pit::material::{Material, MaterialBuilder};
+/// # use bottomless_pit::resource::{LoadingOp, ResourceHandle};
+/// # use bottomless_pit::texture::Texture;
+/// struct Level {
+///     tiles: ResourceHandle<Texture>,
+///     material: Material,
+/// }
+///
+/// fn load_level(engine: &mut Engine) -> Level {
+///     let texture = Texture::new(engine, "tiles.png", LoadingOp::Blocking);
+///     let tiles = engine.create_handle(texture);
+///     let material = MaterialBuilder::new()
+///         .add_texture(tiles.get_resource_id())
+///         .build(engine);
+///
+///     // the texture is freed when the level is dropped
+///     Level { tiles, material }
+/// }
+/// ```
+pub struct ResourceHandle<T> {
+    inner: Arc<HandleInner>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: EngineResource> ResourceHandle<T> {
+    pub(crate) fn new(
+        id: ResourceId<T>,
+        sender: &Sender<DroppedHandle>,
+        counts: &mut HandleCounts,
+    ) -> Self {
+        let existing = counts.get(&id.get_id()).and_then(Weak::upgrade);
+        let inner = existing.unwrap_or_else(|| {
+            let inner = Arc::new(HandleInner {
+                id: id.get_id(),
+                unload: |engine, id| engine.unload(ResourceId::<T>::from_number(id)),
+                sender: sender.clone(),
+            });
+            counts.insert(id.get_id(), Arc::downgrade(&inner));
+            inner
+        });
+
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> ResourceHandle<T> {
+    /// The id of the resource this handle keeps alive
+    pub fn get_resource_id(&self) -> ResourceId<T> {
+        ResourceId::from_number(self.inner.id)
+    }
+}
+
+impl<T> Clone for ResourceHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ResourceHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Handle").field(&self.inner.id).finish()
+    }
+}
+
+pub(crate) struct HandleInner {
+    id: NonZeroU64,
+    unload: fn(&mut Engine, NonZeroU64),
+    sender: Sender<DroppedHandle>,
+}
+
+impl Drop for HandleInner {
+    fn drop(&mut self) {
+        // the engine is already gone so there is nothing to unload
+        let _ = self.sender.send((self.id, self.unload));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // what load() does for the counters without reading anything
+    fn start(loader: &mut Loader, loading_op: LoadingOp) -> NonZeroU64 {
+        let id = generate_id::<Vec<u8>>().get_id();
+        loader.start_loading(id);
+        match loading_op {
+            LoadingOp::Background => loader.background_loading += 1,
+            LoadingOp::Blocking => loader.blocked_loading += 1,
+        }
+        id
+    }
+
+    // what the engine does when a resource arrives
+    fn finish(
+        loader: &mut Loader,
+        id: NonZeroU64,
+        loading_op: LoadingOp,
+        result: Result<usize, LoadError>,
+    ) {
+        loader.remove_item_loading(loading_op);
+        if loader.get_status(id).is_some() {
+            loader.finish_loading(id, result);
+        }
+    }
+
+    #[test]
+    fn pending_resources_end_up_loaded_or_failed() {
+        let mut loader = Loader::new(false);
+        let loaded = start(&mut loader, LoadingOp::Blocking);
+        let failed = start(&mut loader, LoadingOp::Background);
+
+        assert_eq!(loader.get_status(loaded), Some(&LoadStatus::Pending));
+        assert_eq!(loader.get_status(failed), Some(&LoadStatus::Pending));
+        assert_eq!(loader.get_loading_resources(), 2);
+
+        let error = LoadError::InvalidData("not a png".into());
+        finish(&mut loader, loaded, LoadingOp::Blocking, Ok(10));
+        finish(
+            &mut loader,
+            failed,
+            LoadingOp::Background,
+            Err(error.clone()),
+        );
+
+        assert_eq!(loader.get_status(loaded), Some(&LoadStatus::Loaded));
+        assert_eq!(loader.get_status(failed), Some(&LoadStatus::Failed(error)));
+        assert_eq!(loader.get_loading_resources(), 0);
+        assert_eq!(
+            loader.get_progress(),
+            LoadingProgress {
+                finished: 2,
+                failed: 1,
+                total: 2,
+                bytes_loaded: 10,
+            }
+        );
+        assert_eq!(loader.get_progress().get_fraction(), 1.0);
+    }
+
+    #[test]
+    fn progress_resets_once_a_round_is_finished() {
+        let mut loader = Loader::new(false);
+        let first = start(&mut loader, LoadingOp::Background);
+        let second = start(&mut loader, LoadingOp::Background);
+        finish(&mut loader, first, LoadingOp::Background, Ok(4));
+        assert_eq!(loader.get_progress().get_fraction(), 0.5);
+
+        // still part of the first round as the second is loading
+        let third = start(&mut loader, LoadingOp::Blocking);
+        assert_eq!(loader.get_progress().total, 3);
+        finish(&mut loader, second, LoadingOp::Background, Ok(4));
+        finish(&mut loader, third, LoadingOp::Blocking, Ok(4));
+        assert_eq!(loader.get_progress().finished, 3);
+
+        let next_round = start(&mut loader, LoadingOp::Background);
+        assert_eq!(
+            loader.get_progress(),
+            LoadingProgress {
+                finished: 0,
+                failed: 0,
+                total: 1,
+                bytes_loaded: 0,
+            }
+        );
+        finish(&mut loader, next_round, LoadingOp::Background, Ok(4));
+        assert_eq!(loader.get_progress().bytes_loaded, 4);
+    }
+
+    #[test]
+    fn forgetting_a_pending_resource_takes_it_out_of_the_round() {
+        let mut loader = Loader::new(false);
+        let kept = start(&mut loader, LoadingOp::Background);
+        let forgotten = start(&mut loader, LoadingOp::Background);
+
+        loader.forget(forgotten);
+        assert_eq!(loader.get_status(forgotten), None);
+        assert_eq!(loader.get_progress().total, 1);
+
+        // it still arrives later but is thrown away
+        finish(&mut loader, forgotten, LoadingOp::Background, Ok(8));
+        assert_eq!(loader.get_status(forgotten), None);
+        assert_eq!(loader.get_progress().finished, 0);
+
+        finish(&mut loader, kept, LoadingOp::Background, Ok(2));
+        assert_eq!(loader.get_progress().get_fraction(), 1.0);
+        assert_eq!(loader.get_loading_resources(), 0);
+
+        // forgetting something that already loaded leaves the round alone
+        loader.forget(kept);
+        assert_eq!(loader.get_progress().total, 1);
+        assert_eq!(loader.get_progress().finished, 1);
+    }
 }
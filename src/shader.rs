@@ -6,6 +6,7 @@ use std::fmt::Display;
 use std::marker::PhantomData;
 use std::path::Path;
 use std::string::FromUtf8Error;
+use std::sync::OnceLock;
 
 use encase::private::WriteInto;
 use encase::ShaderType;
@@ -14,11 +15,11 @@ use wgpu::{include_wgsl, TextureFormat};
 
 use crate::context::{GraphicsContext, WgpuClump};
 use crate::engine_handle::Engine;
-use crate::render::Renderer;
+use crate::render::{BlendMode, Renderer};
 use crate::resource::{self, InProgressResource, LoadingOp, ResourceId, ResourceType};
 use crate::texture::{SamplerType, UniformTexture};
 use crate::vectors::Vec2;
-use crate::vertex::Vertex;
+use crate::vertex::{InstanceVertex, Vertex};
 use crate::{layouts, render};
 
 /// An internal representation of an WGSL Shader. Under the hood this creates
@@ -28,6 +29,8 @@ use crate::{layouts, render};
 pub struct Shader {
     pub(crate) pipeline: wgpu::RenderPipeline,
     options: FinalShaderOptions,
+    // lets materials draw with a diffrent blend mode than the shader was made with
+    variants: Option<PipelineVariants>,
 }
 
 impl Shader {
@@ -66,65 +69,128 @@ impl Shader {
                 source: wgpu::ShaderSource::Wgsl(string.into()),
             });
 
-        let optional_layout = options.make_layout(&context.wgpu.device);
-        let pipeline = if let Some(layout) = optional_layout {
-            render::make_pipeline(
-                &context.wgpu.device,
-                wgpu::PrimitiveTopology::TriangleList,
-                &[
-                    &layouts::create_texture_layout(&context.wgpu.device),
-                    &layouts::create_camera_layout(&context.wgpu.device),
-                    &layout,
-                ],
-                &[Vertex::desc()],
-                &shader,
-                context.get_texture_format(),
-                Some("User Shader Pipeline"),
-            )
-        } else {
-            render::make_pipeline(
-                &context.wgpu.device,
-                wgpu::PrimitiveTopology::TriangleList,
-                &[
-                    &layouts::create_texture_layout(&context.wgpu.device),
-                    &layouts::create_camera_layout(&context.wgpu.device),
-                ],
-                &[Vertex::desc()],
-                &shader,
-                context.get_texture_format(),
-                Some("User Shader Pipeline"),
-            )
+        let mut layouts = vec![
+            layouts::create_texture_layout(&context.wgpu.device),
+            layouts::create_camera_layout(&context.wgpu.device),
+        ];
+        layouts.extend(options.make_layout(&context.wgpu.device));
+
+        let variants = PipelineVariants {
+            module: shader,
+            layouts,
+            texture_format: context.get_texture_format(),
+            instanced: options.instanced,
+            label: "User Shader Pipeline",
+            pipelines: Default::default(),
         };
+        let pipeline = variants.make_pipeline(&context.wgpu.device, options.blend_mode);
 
-        Ok(Self { pipeline, options })
+        Ok(Self {
+            pipeline,
+            options,
+            variants: Some(variants),
+        })
+    }
+
+    // swaps in a new module while keeping the uniforms and layouts, if the wgsl
+    // doesnt compile the old pipeline is kept
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn reload(
+        &mut self,
+        data: &[u8],
+        wgpu: &WgpuClump,
+    ) -> Result<(), ShaderReloadError> {
+        let Some(old_variants) = &self.variants else {
+            return Err(ShaderReloadError::NotReloadable);
+        };
+
+        let string = String::from_utf8(data.to_vec())?;
+
+        // without the error scope invalid wgsl would panic
+        wgpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = wgpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("User Shader Module"),
+                source: wgpu::ShaderSource::Wgsl(string.into()),
+            });
+
+        let variants = PipelineVariants {
+            module: shader,
+            layouts: old_variants.layouts.clone(),
+            texture_format: old_variants.texture_format,
+            instanced: old_variants.instanced,
+            label: old_variants.label,
+            pipelines: Default::default(),
+        };
+        let pipeline = variants.make_pipeline(&wgpu.device, self.options.blend_mode);
+
+        if let Some(e) = pollster::block_on(wgpu.device.pop_error_scope()) {
+            return Err(ShaderReloadError::CompilationError(e));
+        }
+
+        self.pipeline = pipeline;
+        self.variants = Some(variants);
+
+        Ok(())
     }
 
     pub(crate) fn from_pipeline(pipeline: wgpu::RenderPipeline) -> Self {
         Self {
             pipeline,
             options: FinalShaderOptions::EMPTY,
+            variants: None,
         }
     }
 
     pub(crate) fn defualt(wgpu: &WgpuClump, texture_format: wgpu::TextureFormat) -> Self {
         let shader_descriptor = include_wgsl!("shaders/shader.wgsl");
         let shader = wgpu.device.create_shader_module(shader_descriptor);
-        let pipeline = render::make_pipeline(
-            &wgpu.device,
-            wgpu::PrimitiveTopology::TriangleList,
-            &[
-                &layouts::create_texture_layout(&wgpu.device),
-                &layouts::create_camera_layout(&wgpu.device),
+
+        let variants = PipelineVariants {
+            module: shader,
+            layouts: vec![
+                layouts::create_texture_layout(&wgpu.device),
+                layouts::create_camera_layout(&wgpu.device),
             ],
-            &[Vertex::desc()],
-            &shader,
             texture_format,
-            Some("Defualt Shader From Error"),
-        );
+            instanced: false,
+            label: "Defualt Shader From Error",
+            pipelines: Default::default(),
+        };
+        let pipeline = variants.make_pipeline(&wgpu.device, BlendMode::Alpha);
 
         Self {
             pipeline,
             options: FinalShaderOptions::EMPTY,
+            variants: Some(variants),
+        }
+    }
+
+    pub(crate) fn defualt_instanced(wgpu: &WgpuClump, texture_format: wgpu::TextureFormat) -> Self {
+        let shader_descriptor = include_wgsl!("shaders/instanced_shader.wgsl");
+        let shader = wgpu.device.create_shader_module(shader_descriptor);
+
+        let variants = PipelineVariants {
+            module: shader,
+            layouts: vec![
+                layouts::create_texture_layout(&wgpu.device),
+                layouts::create_camera_layout(&wgpu.device),
+            ],
+            texture_format,
+            instanced: true,
+            label: "Defualt Instanced Shader",
+            pipelines: Default::default(),
+        };
+        let pipeline = variants.make_pipeline(&wgpu.device, BlendMode::Alpha);
+
+        Self {
+            pipeline,
+            options: FinalShaderOptions {
+                instanced: true,
+                ..FinalShaderOptions::EMPTY
+            },
+            variants: Some(variants),
         }
     }
 
@@ -145,21 +211,78 @@ impl Shader {
         self.options.update_uniform_texture(texture, wgpu, format)
     }
 
-    pub(crate) fn set_active<'o>(&'o self, renderer: &mut Renderer<'o, '_>) {
-        renderer.pass.set_pipeline(&self.pipeline);
+    /// The blend mode the shader was created with
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.options.blend_mode
+    }
+
+    /// Whether the shader was made for an [InstancedMaterial](crate::material::InstancedMaterial)
+    pub fn is_instanced(&self) -> bool {
+        self.options.instanced
+    }
+
+    // uses the pipeline for the blend mode if one is given, making it
+    // the first time it is needed
+    pub(crate) fn set_active<'o>(
+        &'o self,
+        renderer: &mut Renderer<'o, '_>,
+        blend_mode: Option<BlendMode>,
+    ) {
+        let device = &renderer.wgpu.device;
+        let pipeline = match (blend_mode, &self.variants) {
+            (Some(mode), Some(variants)) if mode != self.options.blend_mode => {
+                let pipeline = &variants.pipelines[mode.index()];
+                pipeline.get_or_init(|| variants.make_pipeline(device, mode))
+            }
+            _ => &self.pipeline,
+        };
+
+        renderer.pass.set_pipeline(pipeline);
 
         if let Some(bind_group) = &self.options.bind_group {
-            renderer.pass.set_bind_group(2, bind_group, &[]);
+            renderer.pass.set_bind_group(2, bind_group);
         }
     }
 }
 
+#[derive(Debug)]
+struct PipelineVariants {
+    module: wgpu::ShaderModule,
+    layouts: Vec<wgpu::BindGroupLayout>,
+    texture_format: wgpu::TextureFormat,
+    instanced: bool,
+    label: &'static str,
+    pipelines: [OnceLock<wgpu::RenderPipeline>; BlendMode::ALL.len()],
+}
+
+impl PipelineVariants {
+    fn make_pipeline(&self, device: &wgpu::Device, blend_mode: BlendMode) -> wgpu::RenderPipeline {
+        let layouts: Vec<&wgpu::BindGroupLayout> = self.layouts.iter().collect();
+        let vertex_buffers = if self.instanced {
+            vec![Vertex::desc(), InstanceVertex::desc()]
+        } else {
+            vec![Vertex::desc()]
+        };
+
+        render::make_pipeline(
+            device,
+            wgpu::PrimitiveTopology::TriangleList,
+            &layouts,
+            &vertex_buffers,
+            &self.module,
+            self.texture_format,
+            blend_mode,
+            Some(self.label),
+        )
+    }
+}
+
 /// `UniformData` contains the byte data of any struct that implements
 /// [ShaderType](https://docs.rs/encase/latest/encase/trait.ShaderType.html) which
 /// can be derived. This data needs to be added to [ShaderOptions].
 #[derive(Debug)]
 pub struct UniformData<T> {
-    initial_data: Vec<u8>,
+    pub(crate) initial_data: Vec<u8>,
     _marker: PhantomData<T>,
 }
 
@@ -182,6 +305,8 @@ impl<T: ShaderType + WriteInto> UniformData<T> {
 pub struct ShaderOptions<T> {
     uniform_data: Option<Vec<u8>>,
     uniform_texture: Option<(SamplerType, SamplerType, Vec2<u32>)>,
+    blend_mode: BlendMode,
+    instanced: bool,
     _marker: PhantomData<T>,
 }
 
@@ -192,6 +317,8 @@ impl<T> ShaderOptions<T> {
     pub const EMPTY: Self = Self {
         uniform_data: None,
         uniform_texture: None,
+        blend_mode: BlendMode::Alpha,
+        instanced: false,
         _marker: PhantomData,
     };
 
@@ -213,6 +340,8 @@ impl<T> ShaderOptions<T> {
         Self {
             uniform_data: Some(starting_buffer),
             uniform_texture: None,
+            blend_mode: BlendMode::Alpha,
+            instanced: false,
             _marker: PhantomData,
         }
     }
@@ -231,6 +360,8 @@ impl<T> ShaderOptions<T> {
         Self {
             uniform_data: None,
             uniform_texture: Some((mag, min, size)),
+            blend_mode: BlendMode::Alpha,
+            instanced: false,
             _marker: PhantomData,
         }
     }
@@ -252,15 +383,50 @@ impl<T> ShaderOptions<T> {
         Self {
             uniform_data: Some(starting_buffer),
             uniform_texture: Some((mag, min, size)),
+            blend_mode: BlendMode::Alpha,
+            instanced: false,
             _marker: PhantomData,
         }
     }
+
+    /// Sets how the shader blends with what has already been drawn, by defualt
+    /// this is [BlendMode::Alpha]
+    pub fn with_blend_mode(self, blend_mode: BlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    /// Makes the shader for use with an [InstancedMaterial](crate::material::InstancedMaterial).
+    /// Along with the normal vertex input the vertex shader gets the data of each instance:
+    /// ```wgsl
+    /// struct InstanceInput {
+    ///     @location(3) position: vec2<f32>,
+    ///     @location(4) size: vec2<f32>,
+    ///     // in degrees
+    ///     @location(5) rotation: f32,
+    ///     // already divided by the size of the texture
+    ///     @location(6) uv_position: vec2<f32>,
+    ///     @location(7) uv_size: vec2<f32>,
+    ///     @location(8) colour: vec4<f32>,
+    /// }
+    ///
+    /// @vertex
+    /// fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput
+    /// ```
+    /// The vertices are a single quad going from (0, 0) to (1, 1).
+    pub fn with_instancing(self) -> Self {
+        Self {
+            instanced: true,
+            ..self
+        }
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct IntermediateOptions {
     uniform_data: Option<Vec<u8>>,
     uniform_texture: Option<(SamplerType, SamplerType, Vec2<u32>)>,
+    blend_mode: BlendMode,
+    instanced: bool,
 }
 
 impl IntermediateOptions {
@@ -274,6 +440,8 @@ impl<T> From<ShaderOptions<T>> for IntermediateOptions {
         Self {
             uniform_data: value.uniform_data,
             uniform_texture: value.uniform_texture,
+            blend_mode: value.blend_mode,
+            instanced: value.instanced,
         }
     }
 }
@@ -283,6 +451,8 @@ pub(crate) struct FinalShaderOptions {
     uniform_data: Option<wgpu::Buffer>,
     uniform_texture: Option<(wgpu::TextureView, wgpu::Sampler)>,
     bind_group: Option<wgpu::BindGroup>,
+    blend_mode: BlendMode,
+    instanced: bool,
 }
 
 impl FinalShaderOptions {
@@ -290,6 +460,8 @@ impl FinalShaderOptions {
         uniform_data: None,
         uniform_texture: None,
         bind_group: None,
+        blend_mode: BlendMode::Alpha,
+        instanced: false,
     };
 
     pub(crate) fn from_intermediate(
@@ -363,6 +535,8 @@ impl FinalShaderOptions {
             uniform_data: buffer,
             uniform_texture,
             bind_group,
+            blend_mode: options.blend_mode,
+            instanced: options.instanced,
         }
     }
 
@@ -451,6 +625,38 @@ impl Display for UniformError {
 
 impl Error for UniformError {}
 
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub(crate) enum ShaderReloadError {
+    NotReloadable,
+    NotUtf8(FromUtf8Error),
+    CompilationError(wgpu::Error),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Display for ShaderReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotReloadable => write!(
+                f,
+                "The shader is built into the engine and cannot be reloaded"
+            ),
+            Self::NotUtf8(e) => write!(f, "The shader is not valid UTF-8: {}", e),
+            Self::CompilationError(e) => write!(f, "The shader failed to compile: {}", e),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Error for ShaderReloadError {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<FromUtf8Error> for ShaderReloadError {
+    fn from(value: FromUtf8Error) -> Self {
+        Self::NotUtf8(value)
+    }
+}
+
 fn make_layout_internal(
     device: &wgpu::Device,
     has_buffer: bool,
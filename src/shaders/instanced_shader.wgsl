@@ -0,0 +1,65 @@
+struct EngineUniforms {
+    camera: mat3x3<f32>,
+    screen_size: vec2<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> engine: EngineUniforms;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) colour: vec4<f32>
+}
+
+struct InstanceInput {
+    @location(3) position: vec2<f32>,
+    @location(4) size: vec2<f32>,
+    @location(5) rotation: f32,
+    @location(6) uv_position: vec2<f32>,
+    @location(7) uv_size: vec2<f32>,
+    @location(8) colour: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) colour: vec4<f32>,
+}
+
+// vertex shader
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coords = instance.uv_position + model.tex_coords * instance.uv_size;
+
+    // the quad goes from 0 to 1 so it is rotated around its center
+    let angle = radians(instance.rotation);
+    let local = (model.position - vec2(0.5, 0.5)) * instance.size;
+    let rotated = vec2(
+        local.x * cos(angle) - local.y * sin(angle),
+        local.x * sin(angle) + local.y * cos(angle),
+    );
+    let world = rotated + instance.position + instance.size * 0.5;
+
+    var final_pos = engine.camera * vec3(world, 1.0);
+    final_pos = final_pos / final_pos.z;
+    final_pos.x = 2.0 * final_pos.x / engine.screen_size.x - 1.0;
+    final_pos.y = ((2.0 * final_pos.y / engine.screen_size.y) - 1.0) * -1.0;
+    out.clip_position = vec4(final_pos.xy, 0.0, 1.0);
+    out.colour = model.colour * instance.colour;
+    return out;
+}
+
+
+// Fragment shader
+
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0) @binding(1)
+var s_diffuse: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.colour;
+}
@@ -0,0 +1,35 @@
+struct Bloom {
+    threshold: f32,
+    intensity: f32,
+    radius: f32,
+    _padding: f32,
+}
+
+@group(2) @binding(0)
+var<uniform> bloom: Bloom;
+
+// only the parts brighter than the threshold glow
+fn bright_part(uv: vec2<f32>) -> vec3<f32> {
+    let colour = textureSampleLevel(screen_texture, screen_sampler, uv, 0.0).rgb;
+    let luminance = dot(colour, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return colour * max(luminance - bloom.threshold, 0.0) / max(luminance, 0.0001);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSampleLevel(screen_texture, screen_sampler, in.tex_coords, 0.0);
+    let step = bloom.radius / 4.0 / globals.size;
+
+    var glow = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var x = -4; x <= 4; x++) {
+        for (var y = -4; y <= 4; y++) {
+            let offset = vec2<f32>(f32(x), f32(y));
+            let weight = exp(-dot(offset, offset) / 8.0);
+            glow += bright_part(in.tex_coords + offset * step) * weight;
+            total += weight;
+        }
+    }
+
+    return vec4<f32>(base.rgb + glow / total * bloom.intensity, base.a);
+}
@@ -0,0 +1,36 @@
+// the lut is a strip of N slices each N by N pixels, red goes across
+// each slice, green goes down and blue picks the slice
+@group(2) @binding(0)
+var lut_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var lut_sampler: sampler;
+
+fn linear_to_srgb(colour: vec3<f32>) -> vec3<f32> {
+    let low = colour * 12.92;
+    let high = 1.055 * pow(colour, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, colour <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSampleLevel(screen_texture, screen_sampler, in.tex_coords, 0.0);
+
+    // the lut is made in srgb so it has to be looked up with srgb colours
+    var rgb = clamp(colour.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    if globals.srgb == 1u {
+        rgb = linear_to_srgb(rgb);
+    }
+
+    let slices = f32(textureDimensions(lut_texture).y);
+    let blue = rgb.b * (slices - 1.0);
+    let first_slice = floor(blue);
+    let second_slice = min(first_slice + 1.0, slices - 1.0);
+
+    let x = (rgb.r * (slices - 1.0) + 0.5) / (slices * slices);
+    let y = (rgb.g * (slices - 1.0) + 0.5) / slices;
+
+    let first = textureSampleLevel(lut_texture, lut_sampler, vec2<f32>(x + first_slice / slices, y), 0.0);
+    let second = textureSampleLevel(lut_texture, lut_sampler, vec2<f32>(x + second_slice / slices, y), 0.0);
+
+    return vec4<f32>(mix(first.rgb, second.rgb, blue - first_slice), colour.a);
+}
@@ -0,0 +1,26 @@
+struct Crt {
+    scanline_strength: f32,
+    curvature: f32,
+    _padding: vec2<f32>,
+}
+
+@group(2) @binding(0)
+var<uniform> crt: Crt;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // bends the screen outwards like the glass of an old tv
+    let centered = in.tex_coords - 0.5;
+    let uv = 0.5 + centered * (1.0 + crt.curvature * dot(centered, centered));
+
+    if uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let colour = textureSampleLevel(screen_texture, screen_sampler, uv, 0.0);
+    // every other row of pixels is darker
+    let row = floor(uv.y * globals.size.y);
+    let scanline = 1.0 - crt.scanline_strength * (row % 2.0);
+
+    return vec4<f32>(colour.rgb * scanline, colour.a);
+}
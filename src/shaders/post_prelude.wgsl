@@ -0,0 +1,35 @@
+// added to the start of every post effect
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+struct PostGlobals {
+    // size of the screen in pixels
+    size: vec2<f32>,
+    // seconds since the first post processed frame
+    time: f32,
+    // 1 when the screen stores srgb colours, textureSample always gives linear colours
+    srgb: u32,
+}
+
+@group(0) @binding(0)
+var screen_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var screen_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> globals: PostGlobals;
+
+// one triangle big enough to cover the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.tex_coords = uv;
+    return out;
+}
+
@@ -0,0 +1,18 @@
+struct Vignette {
+    strength: f32,
+    radius: f32,
+    softness: f32,
+    _padding: f32,
+}
+
+@group(2) @binding(0)
+var<uniform> vignette: Vignette;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSampleLevel(screen_texture, screen_sampler, in.tex_coords, 0.0);
+    let distance = distance(in.tex_coords, vec2<f32>(0.5));
+    let light = smoothstep(vignette.radius, vignette.radius - vignette.softness, distance);
+
+    return vec4<f32>(colour.rgb * mix(1.0, light, vignette.strength), colour.a);
+}
@@ -0,0 +1,968 @@
+//! Contains everything needed to play sound effects and music.
+//!
+//! Sound effects are loaded as a [Sound] which is fully decoded in memory and can
+//! be played as many times as you like at once. Music is loaded as [Music] which
+//! stays compressed and is decoded bit by bit as it plays, only one track of music
+//! can play at a time. Both WAV and OGG Vorbis files are supported and both are loaded
+//! like any other resource.
+//!
+//! Every time a sound is played it is given its own [Channel] which can be used
+//! to change the volume, pan or to stop the sound.
+//! ```rust,no_run
+//! # use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+//! # use bottomless_pit::resource::LoadingOp;
+//! # use bottomless_pit::sound::{Music, Sound};
+//! let mut engine = EngineBuilder::new().build().unwrap();
+//! let jump = Sound::new(&mut engine, "jump.wav", LoadingOp::Blocking);
+//! let theme = Music::new(&mut engine, "theme.ogg", LoadingOp::Blocking);
+//! // later inside of Game::update
+//! engine.play_music(&theme, true).unwrap();
+//! let channel = engine.play_sound(&jump).unwrap();
+//! engine.set_channel_pan(channel, -0.5);
+//! ```
+//! Where the sound ends up is decided by the [AudioBackend]. With the `cpal` feature
+//! sound is played through the default output device, without it or when running
+//! headless the [NullBackend] is used. [WavWriterBackend] can be used to record
+//! everything the game plays into a file.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use lewton::inside_ogg::OggStreamReader;
+
+use crate::engine_handle::Engine;
+use crate::resource::{self, InProgressResource, LoadingOp, ResourceId, ResourceType};
+
+// amount of frames decoded at once when streaming wav music
+const WAV_CHUNK_FRAMES: usize = 4096;
+
+/// A sound effect that has been fully decoded into memory
+#[derive(Clone)]
+pub struct Sound {
+    // interleaved stereo samples
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+}
+
+impl Sound {
+    /// Attempts to load a WAV or OGG Vorbis file as a sound effect
+    pub fn new<P>(engine: &mut Engine, path: P, loading_op: LoadingOp) -> ResourceId<Sound>
+    where
+        P: AsRef<Path>,
+    {
+        let typed_id = resource::generate_id::<Sound>();
+        let id = typed_id.get_id();
+        let path = path.as_ref();
+        let ip_resource = InProgressResource::new(path, id, ResourceType::Sound, loading_op);
+
+        engine.loader.load(ip_resource, engine.get_proxy());
+
+        typed_id
+    }
+
+    pub(crate) fn from_resource_data(data: Vec<u8>) -> Result<Self, SoundError> {
+        let mut decoder = Decoder::new(data.into())?;
+        let mut samples = Vec::new();
+        while decoder.decode_chunk(&mut samples)? {}
+
+        Ok(Self {
+            samples: samples.into(),
+            sample_rate: decoder.sample_rate(),
+        })
+    }
+
+    /// The silent sound used when a sound fails to load
+    pub(crate) fn defualt() -> Self {
+        Self {
+            samples: Arc::new([]),
+            sample_rate: 44100,
+        }
+    }
+
+    /// Gets the length of the sound in seconds
+    pub fn get_duration(&self) -> f32 {
+        (self.samples.len() / 2) as f32 / self.sample_rate as f32
+    }
+}
+
+/// A music track which is decoded while it plays instead of all at once.
+/// Use this for long files like background music.
+#[derive(Clone)]
+pub struct Music {
+    // the still encoded file
+    data: Arc<[u8]>,
+}
+
+impl Music {
+    /// Attempts to load a WAV or OGG Vorbis file as a music track
+    pub fn new<P>(engine: &mut Engine, path: P, loading_op: LoadingOp) -> ResourceId<Music>
+    where
+        P: AsRef<Path>,
+    {
+        let typed_id = resource::generate_id::<Music>();
+        let id = typed_id.get_id();
+        let path = path.as_ref();
+        let ip_resource = InProgressResource::new(path, id, ResourceType::Music, loading_op);
+
+        engine.loader.load(ip_resource, engine.get_proxy());
+
+        typed_id
+    }
+
+    pub(crate) fn from_resource_data(data: Vec<u8>) -> Result<Self, SoundError> {
+        let data: Arc<[u8]> = data.into();
+        // makes sure the file can actually be played before accepting it
+        Decoder::new(data.clone())?;
+        Ok(Self { data })
+    }
+
+    /// The silent track used when music fails to load
+    pub(crate) fn defualt() -> Self {
+        Self { data: Arc::new([]) }
+    }
+}
+
+/// Identifies a single playing sound. Channels are never reused so a
+/// stale channel will simply be ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Channel(u64);
+
+/// The mixer combines every playing sound and the current music track into
+/// a single stereo stream. [AudioBackends](AudioBackend) pull samples out
+/// of it with [Mixer::mix].
+#[derive(Clone)]
+pub struct Mixer {
+    state: Arc<Mutex<MixerState>>,
+}
+
+impl Mixer {
+    fn new(sample_rate: u32) -> Self {
+        let state = MixerState {
+            sample_rate,
+            master_volume: 1.0,
+            voices: Vec::new(),
+            music: None,
+            music_volume: 1.0,
+            next_channel: 0,
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Fills `out` with interleaved stereo samples, advancing every playing
+    /// sound. Any sounds that finish are removed.
+    pub fn mix(&self, out: &mut [f32]) {
+        out.fill(0.0);
+        let mut state = self.state.lock().unwrap();
+        let output_rate = state.sample_rate as f64;
+
+        state
+            .voices
+            .retain_mut(|voice| voice.mix_into(out, output_rate));
+
+        let music_volume = state.music_volume;
+        let music_finished = state
+            .music
+            .as_mut()
+            .map(|music| !music.mix_into(out, output_rate, music_volume))
+            .unwrap_or(false);
+
+        if music_finished {
+            state.music = None;
+        }
+
+        let master_volume = state.master_volume;
+        out.iter_mut()
+            .for_each(|sample| *sample = (*sample * master_volume).clamp(-1.0, 1.0));
+    }
+
+    /// The sample rate the mixer is outputting at
+    pub fn get_sample_rate(&self) -> u32 {
+        self.state.lock().unwrap().sample_rate
+    }
+
+    fn play(&self, sound: &Sound, looping: bool) -> Channel {
+        let mut state = self.state.lock().unwrap();
+        let channel = Channel(state.next_channel);
+        state.next_channel += 1;
+
+        state.voices.push(Voice {
+            channel,
+            samples: sound.samples.clone(),
+            sample_rate: sound.sample_rate,
+            position: 0.0,
+            looping,
+            volume: 1.0,
+            pan: 0.0,
+        });
+
+        channel
+    }
+
+    fn with_voice<F: FnOnce(&mut Voice)>(&self, channel: Channel, f: F) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(voice) = state.voices.iter_mut().find(|v| v.channel == channel) {
+            f(voice);
+        }
+    }
+}
+
+struct MixerState {
+    sample_rate: u32,
+    master_volume: f32,
+    voices: Vec<Voice>,
+    music: Option<MusicVoice>,
+    music_volume: f32,
+    next_channel: u64,
+}
+
+struct Voice {
+    channel: Channel,
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+    // in frames, fractional for resampling
+    position: f64,
+    looping: bool,
+    volume: f32,
+    pan: f32,
+}
+
+impl Voice {
+    // returns false once the sound has finished
+    fn mix_into(&mut self, out: &mut [f32], output_rate: f64) -> bool {
+        let frames = self.samples.len() / 2;
+        if frames == 0 {
+            return false;
+        }
+
+        let step = self.sample_rate as f64 / output_rate;
+        let (left_gain, right_gain) = pan_gains(self.volume, self.pan);
+
+        for out_frame in out.chunks_exact_mut(2) {
+            if self.position >= frames as f64 {
+                if !self.looping {
+                    return false;
+                }
+                self.position %= frames as f64;
+            }
+
+            let index = self.position as usize;
+            let next = if index + 1 < frames {
+                index + 1
+            } else if self.looping {
+                0
+            } else {
+                index
+            };
+            let t = (self.position - index as f64) as f32;
+
+            let left = lerp(self.samples[index * 2], self.samples[next * 2], t);
+            let right = lerp(self.samples[index * 2 + 1], self.samples[next * 2 + 1], t);
+            out_frame[0] += left * left_gain;
+            out_frame[1] += right * right_gain;
+
+            self.position += step;
+        }
+
+        true
+    }
+}
+
+struct MusicVoice {
+    data: Arc<[u8]>,
+    decoder: Decoder,
+    // decoded stereo samples that have not been played yet
+    buffer: Vec<f32>,
+    // in frames from the start of the buffer
+    position: f64,
+    looping: bool,
+}
+
+impl MusicVoice {
+    // returns false once the track has finished
+    fn mix_into(&mut self, out: &mut [f32], output_rate: f64, volume: f32) -> bool {
+        let step = self.decoder.sample_rate() as f64 / output_rate;
+
+        for out_frame in out.chunks_exact_mut(2) {
+            // always keep the next frame around so it can be interpolated with
+            while (self.position as usize + 1) * 2 >= self.buffer.len() {
+                if !self.decode_more() {
+                    return false;
+                }
+            }
+
+            let index = self.position as usize;
+            let t = (self.position - index as f64) as f32;
+
+            let left = lerp(self.buffer[index * 2], self.buffer[index * 2 + 2], t);
+            let right = lerp(self.buffer[index * 2 + 1], self.buffer[index * 2 + 3], t);
+            out_frame[0] += left * volume;
+            out_frame[1] += right * volume;
+
+            self.position += step;
+        }
+
+        true
+    }
+
+    fn decode_more(&mut self) -> bool {
+        // throw away what has been played so the buffer does not grow forever
+        let played = (self.position as usize).min(self.buffer.len() / 2);
+        self.buffer.drain(..played * 2);
+        self.position -= played as f64;
+
+        let decoded = self.buffer.len();
+        match self.decoder.decode_chunk(&mut self.buffer) {
+            Ok(true) => {}
+            Ok(false) if self.looping => {
+                match Decoder::new(self.data.clone()) {
+                    Ok(decoder) => self.decoder = decoder,
+                    Err(e) => {
+                        log::error!("could not restart music: {}", e);
+                        return false;
+                    }
+                }
+
+                if let Err(e) = self.decoder.decode_chunk(&mut self.buffer) {
+                    log::error!("could not decode music: {}", e);
+                    return false;
+                }
+            }
+            Ok(false) => return false,
+            Err(e) => {
+                log::error!("could not decode music: {}", e);
+                return false;
+            }
+        }
+
+        // a track with no samples in it would otherwise restart forever
+        self.buffer.len() > decoded
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn pan_gains(volume: f32, pan: f32) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    let left = (1.0 - pan).min(1.0);
+    let right = (1.0 + pan).min(1.0);
+    (volume * left, volume * right)
+}
+
+enum Decoder {
+    Wav(hound::WavReader<Cursor<Arc<[u8]>>>),
+    // the ogg reader is huge compared to the wav one
+    Ogg(Box<OggStreamReader<Cursor<Arc<[u8]>>>>),
+}
+
+impl Decoder {
+    fn new(data: Arc<[u8]>) -> Result<Self, SoundError> {
+        if data.starts_with(b"RIFF") {
+            Ok(Self::Wav(hound::WavReader::new(Cursor::new(data))?))
+        } else if data.starts_with(b"OggS") {
+            Ok(Self::Ogg(Box::new(OggStreamReader::new(Cursor::new(
+                data,
+            ))?)))
+        } else {
+            Err(SoundError::UnsupportedFormat)
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Wav(reader) => reader.spec().sample_rate,
+            Self::Ogg(reader) => reader.ident_hdr.audio_sample_rate,
+        }
+    }
+
+    // pushes the next chunk of the file onto `out` as interleaved stereo
+    // returns false when the end of the file has been reached
+    fn decode_chunk(&mut self, out: &mut Vec<f32>) -> Result<bool, SoundError> {
+        match self {
+            Self::Wav(reader) => {
+                let spec = reader.spec();
+                let channels = spec.channels as usize;
+                let wanted = WAV_CHUNK_FRAMES * channels;
+                let samples: Vec<f32> = match spec.sample_format {
+                    hound::SampleFormat::Float => reader
+                        .samples::<f32>()
+                        .take(wanted)
+                        .collect::<Result<_, _>>()?,
+                    hound::SampleFormat::Int => {
+                        let scale = 1.0 / (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                        reader
+                            .samples::<i32>()
+                            .take(wanted)
+                            .map(|s| s.map(|s| s as f32 * scale))
+                            .collect::<Result<_, _>>()?
+                    }
+                };
+
+                push_stereo(&samples, channels, out);
+                Ok(!samples.is_empty())
+            }
+            Self::Ogg(reader) => {
+                let channels = reader.ident_hdr.audio_channels as usize;
+                // packets can be empty so keep going untill there is something
+                loop {
+                    match reader.read_dec_packet_itl()? {
+                        Some(packet) if packet.is_empty() => continue,
+                        Some(packet) => {
+                            let samples = packet
+                                .into_iter()
+                                .map(|s| s as f32 / 32768.0)
+                                .collect::<Vec<f32>>();
+                            push_stereo(&samples, channels, out);
+                            return Ok(true);
+                        }
+                        None => return Ok(false),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn push_stereo(samples: &[f32], channels: usize, out: &mut Vec<f32>) {
+    match channels {
+        0 => {}
+        1 => samples
+            .iter()
+            .for_each(|s| out.extend_from_slice(&[*s, *s])),
+        _ => samples
+            .chunks_exact(channels)
+            .for_each(|frame| out.extend_from_slice(&frame[..2])),
+    }
+}
+
+/// Something that can take the output of the [Mixer] and send it somewhere,
+/// usually the speakers. Custom backends can be set with
+/// [EngineBuilder::with_audio_backend](crate::engine_handle::EngineBuilder::with_audio_backend).
+pub trait AudioBackend {
+    /// The sample rate the mixer should output at
+    fn sample_rate(&self) -> u32;
+    /// Called once when the engine is created. Backends that output on their own
+    /// thread should keep the mixer around and call [Mixer::mix] when they need
+    /// more samples.
+    fn start(&mut self, mixer: Mixer);
+    /// Called every frame with the time since the last frame in seconds. Backends
+    /// that are not driven by an audio device should mix enough samples to cover
+    /// this time.
+    fn update(&mut self, _delta: f32) {}
+}
+
+// turns frame times into amounts of audio frames keeping track
+// of the fractional part so no time gets lost
+struct FrameClock {
+    sample_rate: u32,
+    leftover: f64,
+}
+
+impl FrameClock {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            leftover: 0.0,
+        }
+    }
+
+    fn frames_for(&mut self, delta: f32) -> usize {
+        let frames = delta as f64 * self.sample_rate as f64 + self.leftover;
+        self.leftover = frames.fract();
+        frames as usize
+    }
+}
+
+/// Plays sound nowhere. Sounds still progress and finish as frames pass, which
+/// makes this usefull for headless engines and machines without a sound card.
+pub struct NullBackend {
+    mixer: Option<Mixer>,
+    clock: FrameClock,
+    scratch: Vec<f32>,
+}
+
+impl NullBackend {
+    /// Creates a null backend mixing at 44100hz
+    pub fn new() -> Self {
+        Self {
+            mixer: None,
+            clock: FrameClock::new(44100),
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn sample_rate(&self) -> u32 {
+        self.clock.sample_rate
+    }
+
+    fn start(&mut self, mixer: Mixer) {
+        self.mixer = Some(mixer);
+    }
+
+    fn update(&mut self, delta: f32) {
+        let frames = self.clock.frames_for(delta);
+        if let Some(mixer) = &self.mixer {
+            self.scratch.resize(frames * 2, 0.0);
+            mixer.mix(&mut self.scratch);
+        }
+    }
+}
+
+/// Writes everything that is played into a 16 bit stereo WAV file. The file
+/// is finished when the engine is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct WavWriterBackend {
+    mixer: Option<Mixer>,
+    clock: FrameClock,
+    scratch: Vec<f32>,
+    writer: hound::WavWriter<std::io::BufWriter<std::fs::File>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WavWriterBackend {
+    /// Creates the file at `path` which will be written to at the given sample rate
+    pub fn new<P: AsRef<Path>>(path: P, sample_rate: u32) -> Result<Self, SoundError> {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let writer = hound::WavWriter::create(path, spec)?;
+
+        Ok(Self {
+            mixer: None,
+            clock: FrameClock::new(sample_rate),
+            scratch: Vec::new(),
+            writer,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AudioBackend for WavWriterBackend {
+    fn sample_rate(&self) -> u32 {
+        self.clock.sample_rate
+    }
+
+    fn start(&mut self, mixer: Mixer) {
+        self.mixer = Some(mixer);
+    }
+
+    fn update(&mut self, delta: f32) {
+        let frames = self.clock.frames_for(delta);
+        let Some(mixer) = &self.mixer else {
+            return;
+        };
+
+        self.scratch.resize(frames * 2, 0.0);
+        mixer.mix(&mut self.scratch);
+
+        for sample in self.scratch.iter() {
+            if let Err(e) = self.writer.write_sample((sample * i16::MAX as f32) as i16) {
+                log::error!("could not write audio: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Plays sound through the systems default output device
+#[cfg(feature = "cpal")]
+pub struct CpalBackend {
+    device: cpal::Device,
+    config: cpal::SupportedStreamConfig,
+    // the stream stops playing when dropped
+    stream: Option<cpal::Stream>,
+}
+
+#[cfg(feature = "cpal")]
+impl CpalBackend {
+    /// Finds the default output device, fails if there is none
+    pub fn new() -> Result<Self, SoundError> {
+        use cpal::traits::{DeviceTrait, HostTrait};
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or(SoundError::NoOutputDevice)?;
+        let config = device.default_output_config()?;
+
+        Ok(Self {
+            device,
+            config,
+            stream: None,
+        })
+    }
+
+    fn build_stream<T>(&self, mixer: Mixer) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        use cpal::traits::DeviceTrait;
+
+        let channels = self.config.channels() as usize;
+        let mut scratch = Vec::new();
+
+        self.device.build_output_stream(
+            &self.config.config(),
+            move |data: &mut [T], _| {
+                let frames = data.len() / channels;
+                scratch.resize(frames * 2, 0.0);
+                mixer.mix(&mut scratch);
+
+                let output = data.chunks_exact_mut(channels).zip(scratch.chunks_exact(2));
+                for (frame, stereo) in output {
+                    if channels == 1 {
+                        frame[0] = T::from_sample((stereo[0] + stereo[1]) / 2.0);
+                        continue;
+                    }
+
+                    frame[0] = T::from_sample(stereo[0]);
+                    frame[1] = T::from_sample(stereo[1]);
+                    frame[2..].fill(T::EQUILIBRIUM);
+                }
+            },
+            |e| log::error!("audio stream error: {}", e),
+            None,
+        )
+    }
+}
+
+#[cfg(feature = "cpal")]
+impl AudioBackend for CpalBackend {
+    fn sample_rate(&self) -> u32 {
+        self.config.sample_rate().0
+    }
+
+    fn start(&mut self, mixer: Mixer) {
+        use cpal::traits::StreamTrait;
+
+        let stream = match self.config.sample_format() {
+            cpal::SampleFormat::F32 => self.build_stream::<f32>(mixer),
+            cpal::SampleFormat::I16 => self.build_stream::<i16>(mixer),
+            cpal::SampleFormat::U16 => self.build_stream::<u16>(mixer),
+            format => {
+                log::error!("unsupported audio sample format: {}", format);
+                return;
+            }
+        };
+
+        match stream.map_err(|e| e.to_string()).and_then(|s| {
+            s.play().map_err(|e| e.to_string())?;
+            Ok(s)
+        }) {
+            Ok(stream) => self.stream = Some(stream),
+            Err(e) => log::error!("could not start audio stream: {}", e),
+        }
+    }
+}
+
+/// Owns the backend and mixer for the engine
+pub(crate) struct AudioHandle {
+    backend: Box<dyn AudioBackend>,
+    mixer: Mixer,
+}
+
+impl AudioHandle {
+    pub(crate) fn new(mut backend: Box<dyn AudioBackend>) -> Self {
+        let mixer = Mixer::new(backend.sample_rate());
+        backend.start(mixer.clone());
+
+        Self { backend, mixer }
+    }
+
+    /// Picks the best backend available on this platform
+    pub(crate) fn defualt_backend(headless: bool) -> Box<dyn AudioBackend> {
+        #[cfg(feature = "cpal")]
+        if !headless {
+            match CpalBackend::new() {
+                Ok(backend) => return Box::new(backend),
+                Err(e) => log::warn!("{}, no sound will be played", e),
+            }
+        }
+
+        let _ = headless;
+        Box::new(NullBackend::new())
+    }
+
+    pub(crate) fn update(&mut self, delta: f32) {
+        self.backend.update(delta);
+    }
+
+    pub(crate) fn play(&self, sound: &Sound, looping: bool) -> Channel {
+        self.mixer.play(sound, looping)
+    }
+
+    pub(crate) fn set_volume(&self, channel: Channel, volume: f32) {
+        self.mixer
+            .with_voice(channel, |v| v.volume = volume.max(0.0));
+    }
+
+    pub(crate) fn set_pan(&self, channel: Channel, pan: f32) {
+        self.mixer
+            .with_voice(channel, |v| v.pan = pan.clamp(-1.0, 1.0));
+    }
+
+    pub(crate) fn stop(&self, channel: Channel) {
+        let mut state = self.mixer.state.lock().unwrap();
+        state.voices.retain(|v| v.channel != channel);
+    }
+
+    pub(crate) fn stop_all(&self) {
+        self.mixer.state.lock().unwrap().voices.clear();
+    }
+
+    pub(crate) fn is_playing(&self, channel: Channel) -> bool {
+        let state = self.mixer.state.lock().unwrap();
+        state.voices.iter().any(|v| v.channel == channel)
+    }
+
+    pub(crate) fn play_music(&self, music: &Music, looping: bool) -> Result<(), SoundError> {
+        let mut state = self.mixer.state.lock().unwrap();
+        // a track that failed to load is silent
+        if music.data.is_empty() {
+            state.music = None;
+            return Ok(());
+        }
+
+        state.music = Some(MusicVoice {
+            data: music.data.clone(),
+            decoder: Decoder::new(music.data.clone())?,
+            buffer: Vec::new(),
+            position: 0.0,
+            looping,
+        });
+
+        Ok(())
+    }
+
+    pub(crate) fn stop_music(&self) {
+        self.mixer.state.lock().unwrap().music = None;
+    }
+
+    pub(crate) fn is_music_playing(&self) -> bool {
+        self.mixer.state.lock().unwrap().music.is_some()
+    }
+
+    pub(crate) fn set_music_volume(&self, volume: f32) {
+        self.mixer.state.lock().unwrap().music_volume = volume.max(0.0);
+    }
+
+    pub(crate) fn set_master_volume(&self, volume: f32) {
+        self.mixer.state.lock().unwrap().master_volume = volume.max(0.0);
+    }
+
+    pub(crate) fn get_master_volume(&self) -> f32 {
+        self.mixer.state.lock().unwrap().master_volume
+    }
+}
+
+/// Errors that can occur when loading or playing sound
+#[derive(Debug)]
+pub enum SoundError {
+    /// The sound or music has not finished loading yet
+    NotLoadedYet,
+    /// The file is not a WAV or OGG Vorbis file
+    UnsupportedFormat,
+    /// The WAV file could not be read or written
+    WavError(hound::Error),
+    /// The OGG Vorbis file could not be read
+    OggError(lewton::VorbisError),
+    /// There is no audio device to play sound on
+    #[cfg(feature = "cpal")]
+    NoOutputDevice,
+    /// The audio device could not tell us how to play sound on it
+    #[cfg(feature = "cpal")]
+    DeviceConfigError(cpal::DefaultStreamConfigError),
+}
+
+impl Display for SoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotLoadedYet => write!(f, "The sound has not loaded yet please try again later"),
+            Self::UnsupportedFormat => write!(f, "Only WAV and OGG Vorbis files are supported"),
+            Self::WavError(e) => write!(f, "{}", e),
+            Self::OggError(e) => write!(f, "{}", e),
+            #[cfg(feature = "cpal")]
+            Self::NoOutputDevice => write!(f, "There is no audio output device"),
+            #[cfg(feature = "cpal")]
+            Self::DeviceConfigError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for SoundError {}
+
+impl From<hound::Error> for SoundError {
+    fn from(value: hound::Error) -> Self {
+        Self::WavError(value)
+    }
+}
+
+impl From<lewton::VorbisError> for SoundError {
+    fn from(value: lewton::VorbisError) -> Self {
+        Self::OggError(value)
+    }
+}
+
+#[cfg(feature = "cpal")]
+impl From<cpal::DefaultStreamConfigError> for SoundError {
+    fn from(value: cpal::DefaultStreamConfigError) -> Self {
+        Self::DeviceConfigError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_sound(samples: &[f32]) -> Sound {
+        Sound {
+            samples: samples.into(),
+            sample_rate: 100,
+        }
+    }
+
+    fn wav_bytes(frames: &[i16]) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut data, spec).unwrap();
+        for frame in frames {
+            writer.write_sample(*frame).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        data.into_inner()
+    }
+
+    #[test]
+    fn mixer_plays_sound_at_the_same_rate() {
+        let mixer = Mixer::new(100);
+        let channel = mixer.play(&test_sound(&[0.5, -0.5, 0.25, -0.25]), false);
+
+        let mut out = [1.0; 4];
+        mixer.mix(&mut out);
+        assert_eq!(out, [0.5, -0.5, 0.25, -0.25]);
+
+        // the sound is removed once it has no more frames
+        mixer.mix(&mut out);
+        assert_eq!(out, [0.0; 4]);
+        assert!(mixer.state.lock().unwrap().voices.is_empty());
+        assert_ne!(channel, mixer.play(&test_sound(&[]), false));
+    }
+
+    #[test]
+    fn mixer_applies_pan_and_master_volume() {
+        let mixer = Mixer::new(100);
+        let channel = mixer.play(&test_sound(&[0.5, 0.5]), true);
+        mixer.with_voice(channel, |voice| voice.pan = 1.0);
+        mixer.state.lock().unwrap().master_volume = 0.5;
+
+        let mut out = [0.0; 4];
+        mixer.mix(&mut out);
+        assert_eq!(out, [0.0, 0.25, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn mixer_resamples_between_frames() {
+        let mixer = Mixer::new(200);
+        mixer.play(&test_sound(&[0.0, 0.0, 1.0, 1.0]), false);
+
+        let mut out = [0.0; 6];
+        mixer.mix(&mut out);
+        assert_eq!(out, [0.0, 0.0, 0.5, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn null_backend_mixes_the_frame_delta() {
+        let mut audio = AudioHandle::new(Box::new(NullBackend::new()));
+        let sound = Sound {
+            samples: vec![0.0; 22050 * 2].into(),
+            sample_rate: 44100,
+        };
+        let channel = audio.play(&sound, false);
+
+        audio.update(0.25);
+        assert!(audio.is_playing(channel));
+        audio.update(0.5);
+        assert!(!audio.is_playing(channel));
+    }
+
+    #[test]
+    fn wav_writer_records_mixed_samples() {
+        let path = std::env::temp_dir().join(format!("bp_wav_writer_{}.wav", std::process::id()));
+        let mut backend = WavWriterBackend::new(&path, 100).unwrap();
+        let mixer = Mixer::new(100);
+        backend.start(mixer.clone());
+        mixer.play(&test_sound(&[0.5, -0.5, 0.25, -0.25]), false);
+
+        // 25 frames, everything after the sound is silence
+        backend.update(0.25);
+        drop(backend);
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let samples = reader
+            .samples::<i16>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(samples.len(), 50);
+        assert_eq!(samples[..4], [16383, -16383, 8191, -8191]);
+        assert!(samples[4..].iter().all(|sample| *sample == 0));
+    }
+
+    #[test]
+    fn sound_decodes_mono_wav_to_stereo() {
+        let sound = Sound::from_resource_data(wav_bytes(&[16384, -16384])).unwrap();
+        assert_eq!(&*sound.samples, &[0.5, 0.5, -0.5, -0.5]);
+        assert_eq!(sound.get_duration(), 0.02);
+    }
+
+    #[test]
+    fn empty_looping_music_stops() {
+        let audio = AudioHandle::new(Box::new(NullBackend::new()));
+        let music = Music::from_resource_data(wav_bytes(&[])).unwrap();
+        audio.play_music(&music, true).unwrap();
+
+        let mut out = [0.0; 8];
+        audio.mixer.mix(&mut out);
+        assert!(!audio.is_music_playing());
+    }
+
+    #[test]
+    fn looping_music_wraps_around() {
+        let audio = AudioHandle::new(Box::new(NullBackend::new()));
+        let music = Music::from_resource_data(wav_bytes(&[16384, -16384])).unwrap();
+        audio.play_music(&music, true).unwrap();
+        audio.mixer.state.lock().unwrap().sample_rate = 100;
+
+        let mut out = [0.0; 8];
+        audio.mixer.mix(&mut out);
+        assert_eq!(out, [0.5, 0.5, -0.5, -0.5, 0.5, 0.5, -0.5, -0.5]);
+        assert!(audio.is_music_playing());
+    }
+}
@@ -0,0 +1,564 @@
+//! Contains the [SpriteSheet] which gives names to areas of a texture so sprites
+//! can be drawn without working out UV coordinates by hand.
+//! ```rust,no_run
+//! # use bottomless_pit::colour::Colour;
+//! # use bottomless_pit::engine_handle::EngineBuilder;
+//! # use bottomless_pit::material::{Material, MaterialBuilder};
+//! # use bottomless_pit::render::Renderer;
+//! # use bottomless_pit::resource::{LoadingOp, ResourceId};
+//! # use bottomless_pit::sprite::{SpriteGrid, SpriteSheet};
+//! # use bottomless_pit::texture::Texture;
+//! # use bottomless_pit::vectors::Vec2;
+//! let mut engine = EngineBuilder::new().build().unwrap();
+//! let texture = Texture::new(&mut engine, "player.png", LoadingOp::Blocking);
+//! // exported from Aseprite or TexturePacker
+//! let sheet = SpriteSheet::new(&mut engine, "player.json", LoadingOp::Blocking);
+//! // or made from a grid of 32x32 frames with 4 columns and 2 rows
+//! let grid = SpriteGrid::new(Vec2 { x: 32, y: 32 }, 4, 2)
+//!     .with_row_names(&["player_idle", "player_run"]);
+//! let grid_sheet = SpriteSheet::from_grid(&mut engine, grid);
+//!
+//! let material: Material = MaterialBuilder::new().add_texture(texture).build(&mut engine);
+//! # fn render(material: &mut Material, sheet: ResourceId<SpriteSheet>, render: &Renderer) {
+//! // later inside of Game::render
+//! let position = Vec2 { x: 100.0, y: 100.0 };
+//! let size = Vec2 { x: 64.0, y: 64.0 };
+//! material.add_sprite(&sheet, "player_idle_0", position, size, Colour::WHITE, render);
+//! # }
+//! ```
+//! The sprite sheet only knows where the frames are, the texture they come from has
+//! to be loaded seperatly and used by the [Material](crate::material::Material) drawing them.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::engine_handle::Engine;
+use crate::resource::{self, InProgressResource, LoadingOp, ResourceId, ResourceType};
+use crate::vectors::Vec2;
+
+// removed from frame names so "idle_0.png" can be drawn as "idle_0"
+const IMAGE_EXTENSIONS: [&str; 7] = [".png", ".jpg", ".jpeg", ".bmp", ".gif", ".aseprite", ".ase"];
+
+/// An area of a texture with a name
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteFrame {
+    pub name: String,
+    /// The top left corner of the frame in pixels
+    pub position: Vec2<f32>,
+    /// The size of the frame in pixels
+    pub size: Vec2<f32>,
+    /// How long the frame should be shown for in seconds, only Aseprite
+    /// exports this
+    pub duration: Option<f32>,
+}
+
+/// A named range of frames from Aseprite, these are turned into animations with
+/// [Animation::from_tag](crate::animation::Animation::from_tag)
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameTag {
+    pub name: String,
+    /// The index of the first frame in the tag
+    pub from: usize,
+    /// The index of the last frame in the tag, this frame is part of the tag
+    pub to: usize,
+    pub direction: TagDirection,
+}
+
+/// The order Aseprite plays the frames of a [FrameTag] in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TagDirection {
+    Forward,
+    Reverse,
+    /// Forward then backwards
+    PingPong,
+    /// Backwards then forward
+    PingPongReverse,
+}
+
+/// A set of named frames on a texture. Use [SpriteSheet::new] to load an
+/// Aseprite or TexturePacker JSON file or [SpriteSheet::from_grid] for
+/// sheets where every frame is the same size.
+#[derive(Clone, Debug, Default)]
+pub struct SpriteSheet {
+    // in the order they appear in the file, the hash layout is sorted by name
+    frames: Vec<SpriteFrame>,
+    names: HashMap<String, usize>,
+    tags: Vec<FrameTag>,
+}
+
+impl SpriteSheet {
+    /// Attempts to load a JSON file exported by Aseprite or TexturePacker. Both the
+    /// hash and array layouts are supported. Any image extension like `.png` is removed
+    /// from the frame names.
+    pub fn new<P>(engine: &mut Engine, path: P, loading_op: LoadingOp) -> ResourceId<SpriteSheet>
+    where
+        P: AsRef<Path>,
+    {
+        let typed_id = resource::generate_id::<SpriteSheet>();
+        let id = typed_id.get_id();
+        let path = path.as_ref();
+        let ip_resource = InProgressResource::new(path, id, ResourceType::SpriteSheet, loading_op);
+
+        engine.loader.load(ip_resource, engine.get_proxy());
+
+        typed_id
+    }
+
+    /// Creates a sprite sheet from a grid, this is ready to use immediately
+    pub fn from_grid(engine: &mut Engine, grid: SpriteGrid) -> ResourceId<SpriteSheet> {
+        let typed_id = resource::generate_id::<SpriteSheet>();
+        engine
+            .resource_manager
+            .insert_sprite_sheet(typed_id, Self::from_frames(grid.into_frames()));
+
+        typed_id
+    }
+
+    pub(crate) fn from_resource_data(data: &[u8]) -> Result<Self, SpriteSheetError> {
+        let json: Value = serde_json::from_slice(data)?;
+
+        let frames = match json.get("frames") {
+            // {"frames": {"name": {...}}}
+            Some(Value::Object(frames)) => {
+                // the keys come back sorted like strings so "walk 10" would be before
+                // "walk 2", sorting them by their numbers gives back aseprite's order
+                let mut frames: Vec<(&String, &Value)> = frames.iter().collect();
+                frames.sort_by(|(a, _), (b, _)| natural_cmp(a, b));
+                frames
+                    .into_iter()
+                    .map(|(name, frame)| parse_frame(name, frame))
+                    .collect::<Result<Vec<SpriteFrame>, SpriteSheetError>>()?
+            }
+            // {"frames": [{"filename": "name", ...}]}
+            Some(Value::Array(frames)) => frames
+                .iter()
+                .map(|frame| {
+                    let name = frame
+                        .get("filename")
+                        .and_then(Value::as_str)
+                        .ok_or(SpriteSheetError::MissingField("filename"))?;
+                    parse_frame(name, frame)
+                })
+                .collect::<Result<Vec<SpriteFrame>, SpriteSheetError>>()?,
+            _ => return Err(SpriteSheetError::MissingField("frames")),
+        };
+
+        let tags = match json.pointer("/meta/frameTags") {
+            Some(Value::Array(tags)) => tags
+                .iter()
+                .map(|tag| parse_tag(tag, frames.len()))
+                .collect::<Result<Vec<FrameTag>, SpriteSheetError>>()?,
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            tags,
+            ..Self::from_frames(frames)
+        })
+    }
+
+    fn from_frames(frames: Vec<SpriteFrame>) -> Self {
+        let names = frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| (frame.name.clone(), i))
+            .collect();
+
+        Self {
+            frames,
+            names,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Gets a frame by its name
+    pub fn get_frame(&self, name: &str) -> Option<&SpriteFrame> {
+        self.names.get(name).map(|i| &self.frames[*i])
+    }
+
+    /// Gets a frame by its position in the file, or for grids its position
+    /// reading left to right then top to bottom. Frames from the hash layout are
+    /// ordered by name with numbers compared by their value, so `walk 2` comes
+    /// before `walk 10`
+    pub fn get_frame_by_index(&self, index: usize) -> Option<&SpriteFrame> {
+        self.frames.get(index)
+    }
+
+    /// All the frames in the same order as [SpriteSheet::get_frame_by_index]
+    pub fn get_frames(&self) -> &[SpriteFrame] {
+        &self.frames
+    }
+
+    /// Gets an Aseprite frame tag by its name
+    pub fn get_tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    /// All the Aseprite frame tags in the sheet
+    pub fn get_tags(&self) -> &[FrameTag] {
+        &self.tags
+    }
+
+    /// The amount of frames in the sheet
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Checks if the sheet has no frames, this is the case for
+    /// sheets that failed to load
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+fn parse_frame(name: &str, data: &Value) -> Result<SpriteFrame, SpriteSheetError> {
+    let rect = data
+        .get("frame")
+        .ok_or(SpriteSheetError::MissingField("frame"))?;
+    let number = |field: &'static str| {
+        rect.get(field)
+            .and_then(Value::as_f64)
+            .map(|n| n as f32)
+            .ok_or(SpriteSheetError::MissingField(field))
+    };
+
+    if data.get("rotated").and_then(Value::as_bool) == Some(true) {
+        return Err(SpriteSheetError::RotatedFrame(name.to_string()));
+    }
+
+    let name = IMAGE_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name);
+
+    Ok(SpriteFrame {
+        name: name.to_string(),
+        position: Vec2 {
+            x: number("x")?,
+            y: number("y")?,
+        },
+        size: Vec2 {
+            x: number("w")?,
+            y: number("h")?,
+        },
+        // aseprite uses milliseconds
+        duration: data
+            .get("duration")
+            .and_then(Value::as_f64)
+            .map(|ms| ms as f32 / 1000.0),
+    })
+}
+
+// compares runs of digits by their value so "walk 2" is before "walk 10"
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (chunks(a), chunks(b));
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) => {
+                let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                };
+
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (a, b) => return a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+// splits text into runs of digits and runs of everything else
+fn chunks(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let digit = rest.chars().next()?.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digit)
+            .unwrap_or(rest.len());
+        let (chunk, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(chunk)
+    })
+}
+
+fn parse_tag(data: &Value, frame_count: usize) -> Result<FrameTag, SpriteSheetError> {
+    let name = data
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or(SpriteSheetError::MissingField("name"))?;
+    let index = |field: &'static str| {
+        data.get(field)
+            .and_then(Value::as_u64)
+            .map(|n| n as usize)
+            .ok_or(SpriteSheetError::MissingField(field))
+    };
+
+    let (from, to) = (index("from")?, index("to")?);
+    if from > to || to >= frame_count {
+        return Err(SpriteSheetError::InvalidTag(name.to_string()));
+    }
+
+    let direction = match data.get("direction").and_then(Value::as_str) {
+        Some("reverse") => TagDirection::Reverse,
+        Some("pingpong") => TagDirection::PingPong,
+        Some("pingpong_reverse") => TagDirection::PingPongReverse,
+        _ => TagDirection::Forward,
+    };
+
+    Ok(FrameTag {
+        name: name.to_string(),
+        from,
+        to,
+        direction,
+    })
+}
+
+/// Describes a sprite sheet where all the frames are the same size and laid out in a grid.
+/// Frames are named by their index reading left to right then top to bottom, so the
+/// first frame is `"0"`. Rows can be given names with [SpriteGrid::with_row_names] in
+/// which case the frames are named `"{row name}_{column}"`.
+#[derive(Clone, Debug)]
+pub struct SpriteGrid {
+    frame_size: Vec2<u32>,
+    columns: u32,
+    rows: u32,
+    offset: Vec2<u32>,
+    spacing: Vec2<u32>,
+    row_names: Vec<String>,
+}
+
+impl SpriteGrid {
+    /// Creates a grid of frames with no space between them starting at the top left of the texture
+    pub fn new(frame_size: Vec2<u32>, columns: u32, rows: u32) -> Self {
+        Self {
+            frame_size,
+            columns,
+            rows,
+            offset: Vec2 { x: 0, y: 0 },
+            spacing: Vec2 { x: 0, y: 0 },
+            row_names: Vec::new(),
+        }
+    }
+
+    /// Sets where the top left of the first frame is in pixels
+    pub fn with_offset(self, offset: Vec2<u32>) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Sets the gap in pixels between frames
+    pub fn with_spacing(self, spacing: Vec2<u32>) -> Self {
+        Self { spacing, ..self }
+    }
+
+    /// Names the rows starting from the top, rows without a name keep using their index
+    pub fn with_row_names(self, names: &[&str]) -> Self {
+        Self {
+            row_names: names.iter().map(|n| n.to_string()).collect(),
+            ..self
+        }
+    }
+
+    fn into_frames(self) -> Vec<SpriteFrame> {
+        let mut frames = Vec::with_capacity((self.columns * self.rows) as usize);
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let name = match self.row_names.get(row as usize) {
+                    Some(row_name) => format!("{}_{}", row_name, column),
+                    None => (row * self.columns + column).to_string(),
+                };
+
+                let x = self.offset.x + column * (self.frame_size.x + self.spacing.x);
+                let y = self.offset.y + row * (self.frame_size.y + self.spacing.y);
+                frames.push(SpriteFrame {
+                    name,
+                    position: Vec2 {
+                        x: x as f32,
+                        y: y as f32,
+                    },
+                    size: Vec2 {
+                        x: self.frame_size.x as f32,
+                        y: self.frame_size.y as f32,
+                    },
+                    duration: None,
+                });
+            }
+        }
+
+        frames
+    }
+}
+
+/// Errors that can occur when loading a [SpriteSheet]
+#[derive(Debug)]
+pub enum SpriteSheetError {
+    JsonError(serde_json::Error),
+    /// The file is missing something it needs, like the `frames` list
+    MissingField(&'static str),
+    /// Frames rotated by TexturePacker are not supported
+    RotatedFrame(String),
+    /// The frame tag uses frames that do not exist
+    InvalidTag(String),
+}
+
+impl Display for SpriteSheetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::JsonError(e) => write!(f, "{}", e),
+            Self::MissingField(field) => write!(f, "sprite sheet is missing the field: {}", field),
+            Self::RotatedFrame(name) => {
+                write!(f, "frame {} is rotated which is not supported", name)
+            }
+            Self::InvalidTag(name) => write!(f, "frame tag {} uses frames that do not exist", name),
+        }
+    }
+}
+
+impl Error for SpriteSheetError {}
+
+impl From<serde_json::Error> for SpriteSheetError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::JsonError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(sheet: &SpriteSheet, index: usize) -> (&str, Vec2<f32>, Vec2<f32>) {
+        let frame = sheet.get_frame_by_index(index).unwrap();
+        (frame.name.as_str(), frame.position, frame.size)
+    }
+
+    #[test]
+    fn aseprite_hash_layout() {
+        let json = br#"{
+            "frames": {
+                "walk 10.aseprite": { "frame": { "x": 20, "y": 0, "w": 10, "h": 12 }, "duration": 50 },
+                "walk 2.aseprite": { "frame": { "x": 10, "y": 0, "w": 10, "h": 12 }, "duration": 100 },
+                "walk 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 10, "h": 12 }, "duration": 100 }
+            },
+            "meta": {
+                "frameTags": [
+                    { "name": "forward", "from": 0, "to": 2, "direction": "forward" },
+                    { "name": "reverse", "from": 0, "to": 1, "direction": "reverse" },
+                    { "name": "pingpong", "from": 1, "to": 2, "direction": "pingpong" },
+                    { "name": "pingpong_reverse", "from": 0, "to": 0, "direction": "pingpong_reverse" },
+                    { "name": "unknown", "from": 2, "to": 2 }
+                ]
+            }
+        }"#;
+
+        let sheet = SpriteSheet::from_resource_data(json).unwrap();
+        assert_eq!(sheet.len(), 3);
+        let size = Vec2 { x: 10.0, y: 12.0 };
+        assert_eq!(frame(&sheet, 0), ("walk 0", Vec2 { x: 0.0, y: 0.0 }, size));
+        assert_eq!(frame(&sheet, 1), ("walk 2", Vec2 { x: 10.0, y: 0.0 }, size));
+        assert_eq!(
+            frame(&sheet, 2),
+            ("walk 10", Vec2 { x: 20.0, y: 0.0 }, size)
+        );
+        assert_eq!(sheet.get_frame("walk 10").unwrap().duration, Some(0.05));
+        assert!(sheet.get_frame("walk 10.aseprite").is_none());
+
+        let directions: Vec<(&str, TagDirection)> = sheet
+            .get_tags()
+            .iter()
+            .map(|tag| (tag.name.as_str(), tag.direction))
+            .collect();
+        assert_eq!(
+            directions,
+            [
+                ("forward", TagDirection::Forward),
+                ("reverse", TagDirection::Reverse),
+                ("pingpong", TagDirection::PingPong),
+                ("pingpong_reverse", TagDirection::PingPongReverse),
+                ("unknown", TagDirection::Forward),
+            ]
+        );
+        let tag = sheet.get_tag("pingpong").unwrap();
+        assert_eq!((tag.from, tag.to), (1, 2));
+    }
+
+    #[test]
+    fn texture_packer_array_layout() {
+        let json = br#"{
+            "frames": [
+                { "filename": "zombie.png", "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "rotated": false },
+                { "filename": "arrow.jpeg", "frame": { "x": 16, "y": 0, "w": 8, "h": 4 } },
+                { "filename": "no_extension", "frame": { "x": 0, "y": 16, "w": 4, "h": 4 } },
+                { "filename": "archive.tar.gz", "frame": { "x": 4, "y": 16, "w": 4, "h": 4 } }
+            ]
+        }"#;
+
+        let sheet = SpriteSheet::from_resource_data(json).unwrap();
+        // arrays keep the order from the file
+        let names: Vec<&str> = sheet.get_frames().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["zombie", "arrow", "no_extension", "archive.tar.gz"]);
+        assert_eq!(
+            frame(&sheet, 1),
+            ("arrow", Vec2 { x: 16.0, y: 0.0 }, Vec2 { x: 8.0, y: 4.0 })
+        );
+        assert_eq!(sheet.get_frame("zombie").unwrap().duration, None);
+        assert!(sheet.get_tags().is_empty());
+    }
+
+    #[test]
+    fn rotated_frames_are_rejected() {
+        let json = br#"{
+            "frames": [
+                { "filename": "sideways.png", "frame": { "x": 0, "y": 0, "w": 8, "h": 16 }, "rotated": true }
+            ]
+        }"#;
+
+        assert!(matches!(
+            SpriteSheet::from_resource_data(json),
+            Err(SpriteSheetError::RotatedFrame(name)) if name == "sideways.png"
+        ));
+    }
+
+    #[test]
+    fn broken_sheets() {
+        let error = |json: &[u8]| SpriteSheet::from_resource_data(json).unwrap_err();
+
+        assert!(matches!(
+            error(b"{ not json"),
+            SpriteSheetError::JsonError(_)
+        ));
+        assert!(matches!(
+            error(br#"{ "meta": {} }"#),
+            SpriteSheetError::MissingField("frames")
+        ));
+        assert!(matches!(
+            error(br#"{ "frames": [{ "frame": { "x": 0, "y": 0, "w": 1, "h": 1 } }] }"#),
+            SpriteSheetError::MissingField("filename")
+        ));
+        assert!(matches!(
+            error(br#"{ "frames": { "a": { "frame": { "x": 0, "y": 0, "w": 1 } } } }"#),
+            SpriteSheetError::MissingField("h")
+        ));
+        assert!(matches!(
+            error(
+                br#"{
+                    "frames": { "a": { "frame": { "x": 0, "y": 0, "w": 1, "h": 1 } } },
+                    "meta": { "frameTags": [{ "name": "past_the_end", "from": 0, "to": 1 }] }
+                }"#
+            ),
+            SpriteSheetError::InvalidTag(name) if name == "past_the_end"
+        ));
+    }
+
+    #[test]
+    fn names_are_sorted_by_their_numbers() {
+        let mut names = ["b", "a 10", "a 2", "a", "a 1 x", "a 1"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["a", "a 1", "a 1 x", "a 2", "a 10", "b"]);
+    }
+}
@@ -37,7 +37,7 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use glyphon::fontdb::Source;
+use glyphon::fontdb::{self, Source};
 use glyphon::{
     self, Attrs, Family, FontSystem, Metrics, Shaping, SwashCache, TextArea, TextAtlas, TextBounds,
 };
@@ -108,18 +108,39 @@ impl TextRenderer {
     }
 
     /// Loads in a font from a byte array
-    pub fn load_font_from_bytes(&mut self, data: &[u8]) -> Font {
-        let id = self
+    pub fn load_font_from_bytes(&mut self, data: &[u8]) -> Result<Font, FontError> {
+        let faces = self
             .font_system
             .db_mut()
-            .load_font_source(Source::Binary(Arc::new(data.to_vec())))[0];
+            .load_font_source(Source::Binary(Arc::new(data.to_vec())));
         // I am lying to the user here
 
-        let name = self.font_system.db().face(id).unwrap().families[0]
-            .0
-            .clone();
+        let name = faces
+            .first()
+            .and_then(|face| self.font_system.db().face(*face))
+            .and_then(|face| face.families.first())
+            .map(|(name, _)| name.clone());
+
+        let font = Font {
+            name: name.unwrap_or_default(),
+            faces: faces.to_vec(),
+        };
+
+        if font.name.is_empty() {
+            // anything that was read is useless without a name
+            self.unload_font(&font);
+            return Err(FontError::NotAFont);
+        }
 
-        Font { name }
+        Ok(font)
+    }
+
+    // the defualt font has no faces so it is never removed
+    pub(crate) fn unload_font(&mut self, font: &Font) {
+        let db = self.font_system.db_mut();
+        for face in font.faces.iter() {
+            db.remove_face(*face);
+        }
     }
 }
 
@@ -134,6 +155,7 @@ pub struct TextMaterial {
     colour: Colour,
     vertex_count: u64,
     index_count: u64,
+    layer: i32,
 }
 
 impl TextMaterial {
@@ -146,6 +168,7 @@ impl TextMaterial {
             inner: None,
             vertex_count: 0,
             index_count: 0,
+            layer: 0,
         }
     }
 
@@ -316,6 +339,16 @@ impl TextMaterial {
         self.update_measurements(font_info);
     }
 
+    /// Sets the layer the text is drawn on, see
+    /// [MaterialBuilder::set_layer](crate::material::MaterialBuilder::set_layer)
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub fn get_layer(&self) -> i32 {
+        self.layer
+    }
+
     /// Measuers the text contained within the widget
     pub fn get_measurements(&self) -> Vec2<u32> {
         self.inner.as_ref().map(|c| c.size).unwrap_or(vec2!(0))
@@ -638,8 +671,9 @@ impl TextMaterial {
             .unwrap()
             .pipeline;
 
+        information.pass.set_layer(self.layer);
         information.pass.set_pipeline(pipeline);
-        information.pass.set_bind_group(0, &inner.bind_group, &[]);
+        information.pass.set_bind_group(0, &inner.bind_group);
 
         information
             .pass
@@ -803,6 +837,8 @@ pub(crate) struct FontInformation<'a> {
 /// A struct to ensure Font Families are created properly.
 pub struct Font {
     name: String,
+    // the faces in the font system, removed when the font is unloaded
+    faces: Vec<fontdb::ID>,
 }
 
 impl Font {
@@ -825,10 +861,27 @@ impl Font {
     pub(crate) fn from_str(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            faces: Vec::new(),
+        }
+    }
+}
+
+/// Fonts can fail to load when the data is not a font or is missing its name
+#[derive(Debug)]
+pub enum FontError {
+    NotAFont,
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAFont => write!(f, "the data is not a font that can be read"),
         }
     }
 }
 
+impl std::error::Error for FontError {}
+
 fn render_text_to_texture(
     text_handle: &mut TextRenderer,
     text: &glyphon::Buffer,
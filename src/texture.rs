@@ -6,7 +6,7 @@ use crate::engine_handle::Engine;
 use crate::resource::{self, InProgressResource, LoadingOp, ResourceId, ResourceType};
 use crate::vectors::Vec2;
 use crate::{layouts, ERROR_TEXTURE_DATA};
-use image::{GenericImageView, ImageError};
+use image::{imageops, DynamicImage, GenericImageView, ImageError, Rgba32FImage, RgbaImage};
 use std::fmt::Display;
 use std::io::Error;
 use std::path::Path;
@@ -14,6 +14,7 @@ use std::path::Path;
 /// Contains all the information need to render an image/texture to the screen.
 /// In order to be used it must be put inside a [Material](crate::material::Material)
 pub struct Texture {
+    pub(crate) texture: wgpu::Texture,
     pub(crate) _view: wgpu::TextureView,
     pub(crate) bind_group: wgpu::BindGroup,
     pub(crate) size: Vec2<f32>,
@@ -31,10 +32,7 @@ impl Texture {
         let ip_resource = InProgressResource::new(
             path,
             id,
-            ResourceType::Image(
-                SamplerType::LinearInterpolation,
-                SamplerType::NearestNeighbor,
-            ),
+            ResourceType::Image(TextureOptions::new()),
             loading_op,
         );
 
@@ -43,6 +41,40 @@ impl Texture {
         typed_id
     }
 
+    /// Attempts to load the file at the path and then turn it into a texture using the
+    /// [TextureOptions] for things like mipmaps and repeating.
+    /// ```rust,no_run
+    /// # use bottomless_pit::engine_handle::Engine;
+    /// # use bottomless_pit::resource::LoadingOp;
+    /// # use bottomless_pit::texture::{AddressMode, Texture, TextureOptions};
+    /// # fn load(engine: &mut Engine) {
+    /// // a background that tiles when drawn with uvs past 1.0
+    /// let options = TextureOptions::new()
+    ///     .with_address_mode(AddressMode::Repeat)
+    ///     .with_mipmaps();
+    /// let background = Texture::new_with_options(engine, "grass.png", options, LoadingOp::Blocking);
+    /// # }
+    /// ```
+    pub fn new_with_options<P>(
+        engine: &mut Engine,
+        path: P,
+        options: TextureOptions,
+        loading_op: LoadingOp,
+    ) -> ResourceId<Texture>
+    where
+        P: AsRef<Path>,
+    {
+        let typed_id = resource::generate_id::<Texture>();
+        let id = typed_id.get_id();
+        let path = path.as_ref();
+        let ip_resource =
+            InProgressResource::new(path, id, ResourceType::Image(options), loading_op);
+
+        engine.loader.load(ip_resource, engine.get_proxy());
+
+        typed_id
+    }
+
     /// Attempts to both read a file at the specified path and turn it into an image.
     pub fn new_with_sampler<P>(
         engine: &mut Engine,
@@ -56,8 +88,9 @@ impl Texture {
         let typed_id = resource::generate_id::<Texture>();
         let id = typed_id.get_id();
         let path = path.as_ref();
+        let options = TextureOptions::new().with_sampler(sampler, sampler);
         let ip_resource =
-            InProgressResource::new(path, id, ResourceType::Image(sampler, sampler), loading_op);
+            InProgressResource::new(path, id, ResourceType::Image(options), loading_op);
 
         engine.loader.blocking_load(ip_resource, engine.get_proxy());
 
@@ -80,41 +113,185 @@ impl Texture {
         let typed_id = resource::generate_id::<Texture>();
         let id = typed_id.get_id();
         let path = path.as_ref();
-        let ip_resource = InProgressResource::new(
-            path,
-            id,
-            ResourceType::Image(mag_sampler, min_sampler),
-            loading_op,
-        );
+        let options = TextureOptions::new().with_sampler(mag_sampler, min_sampler);
+        let ip_resource =
+            InProgressResource::new(path, id, ResourceType::Image(options), loading_op);
 
         engine.loader.blocking_load(ip_resource, engine.get_proxy());
 
         typed_id
     }
 
+    /// Creates a texture from an encoded image like a png that is already in memory,
+    /// this works nicely with `include_bytes!` for single executable builds. Just like
+    /// loading from a file the texture is ready by the next frame and if the data
+    /// cant be decoded the error shows up in
+    /// [Engine::get_load_status](crate::engine_handle::Engine::get_load_status).
+    pub fn from_bytes(engine: &mut Engine, data: &[u8]) -> ResourceId<Texture> {
+        Self::from_bytes_with_options(engine, data, TextureOptions::new())
+    }
+
+    /// Creates a texture from an encoded image in memory, see [Texture::from_bytes] and
+    /// [TextureOptions]
+    pub fn from_bytes_with_options(
+        engine: &mut Engine,
+        data: &[u8],
+        options: TextureOptions,
+    ) -> ResourceId<Texture> {
+        let typed_id = resource::generate_id::<Texture>();
+
+        engine.loader.load_from_memory(
+            data.to_vec(),
+            typed_id.get_id(),
+            ResourceType::Image(options),
+            engine.get_proxy(),
+        );
+
+        typed_id
+    }
+
+    /// Creates a texture from raw pixels, each pixel is 4 bytes of RGBA and they go
+    /// row by row starting at the top left. Great for procedurally generated textures.
+    /// # Panics
+    /// When `pixels` isnt `size.x * size.y * 4` bytes long or the size is zero
+    pub fn from_rgba(engine: &mut Engine, size: Vec2<u32>, pixels: &[u8]) -> ResourceId<Texture> {
+        Self::from_rgba_with_options(engine, size, pixels, TextureOptions::new())
+    }
+
+    /// Creates a texture from raw pixels, see [Texture::from_rgba] and [TextureOptions]
+    /// # Panics
+    /// When `pixels` isnt `size.x * size.y * 4` bytes long or the size is zero
+    pub fn from_rgba_with_options(
+        engine: &mut Engine,
+        size: Vec2<u32>,
+        pixels: &[u8],
+        options: TextureOptions,
+    ) -> ResourceId<Texture> {
+        assert!(
+            size.x > 0 && size.y > 0,
+            "textures cant have a size of zero"
+        );
+        assert_eq!(
+            Some(pixels.len()),
+            rgba_len(size),
+            "there must be 4 bytes for every pixel in the texture"
+        );
+
+        let typed_id = resource::generate_id::<Texture>();
+
+        engine.loader.load_from_memory(
+            pixels.to_vec(),
+            typed_id.get_id(),
+            ResourceType::RawImage(size, options),
+            engine.get_proxy(),
+        );
+
+        typed_id
+    }
+
+    /// Creates a texture from an image that was already decoded or made with the
+    /// [image](https://docs.rs/image/latest/image/) crate
+    /// # Panics
+    /// When the image has a size of zero
+    pub fn from_image(engine: &mut Engine, image: &DynamicImage) -> ResourceId<Texture> {
+        Self::from_image_with_options(engine, image, TextureOptions::new())
+    }
+
+    /// Creates a texture from an image, see [Texture::from_image] and [TextureOptions]
+    /// # Panics
+    /// When the image has a size of zero
+    pub fn from_image_with_options(
+        engine: &mut Engine,
+        image: &DynamicImage,
+        options: TextureOptions,
+    ) -> ResourceId<Texture> {
+        let (width, height) = image.dimensions();
+        let size = Vec2 {
+            x: width,
+            y: height,
+        };
+
+        Self::from_rgba_with_options(engine, size, image.to_rgba8().as_raw(), options)
+    }
+
+    /// Replaces the pixels in part of a texture, `pixels` is in the same layout as
+    /// [Texture::from_rgba]. The texture needs to be loaded first, so textures made
+    /// this frame cant be updated untill the next one. Only the full size texture is
+    /// changed so textures with mipmaps will show the old pixels when drawn smaller.
+    pub fn update_region(
+        engine: &Engine,
+        texture: ResourceId<Texture>,
+        position: Vec2<u32>,
+        size: Vec2<u32>,
+        pixels: &[u8],
+    ) -> Result<(), TextureUpdateError> {
+        let texture = engine
+            .resource_manager
+            .get_texture(&texture)
+            .ok_or(TextureUpdateError::NotLoadedYet)?;
+        let context = engine
+            .context
+            .as_ref()
+            .ok_or(TextureUpdateError::NotLoadedYet)?;
+
+        let fits = |position: u32, size: u32, texture_size: f32| {
+            (position as usize)
+                .checked_add(size as usize)
+                .is_some_and(|end| end <= texture_size as usize)
+        };
+        if !fits(position.x, size.x, texture.size.x) || !fits(position.y, size.y, texture.size.y) {
+            return Err(TextureUpdateError::OutOfBounds);
+        }
+
+        if Some(pixels.len()) != rgba_len(size) {
+            return Err(TextureUpdateError::WrongSize);
+        }
+
+        context.wgpu.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: position.x,
+                    y: position.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.x),
+                rows_per_image: Some(size.y),
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Ok(())
+    }
+
     pub(crate) fn from_resource_data(
         engine: &Engine,
         label: Option<&str>,
         data: Vec<u8>,
-        mag_sampler: SamplerType,
-        min_sampler: SamplerType,
+        options: TextureOptions,
     ) -> Result<Self, TextureError> {
         let img = image::load_from_memory(&data)?;
-        Ok(Self::from_image(
-            engine,
-            img,
-            label,
-            mag_sampler,
-            min_sampler,
-        ))
+        Ok(Self::from_dynamic_image(engine, img, label, options))
     }
 
     pub(crate) fn new_direct(
+        texture: wgpu::Texture,
         view: wgpu::TextureView,
         bind_group: wgpu::BindGroup,
         size: Vec2<f32>,
     ) -> Self {
         Self {
+            texture,
             _view: view,
             bind_group,
             size,
@@ -123,25 +300,37 @@ impl Texture {
 
     pub(crate) fn default(engine: &Engine) -> Self {
         let image = image::load_from_memory(ERROR_TEXTURE_DATA).unwrap();
-        Self::from_image(
-            engine,
-            image,
-            Some("Error Texture"),
-            SamplerType::LinearInterpolation,
-            SamplerType::NearestNeighbor,
-        )
+        Self::from_dynamic_image(engine, image, Some("Error Texture"), TextureOptions::new())
     }
 
-    fn from_image(
+    fn from_dynamic_image(
         engine: &Engine,
-        img: image::DynamicImage,
+        img: DynamicImage,
         label: Option<&str>,
-        mag_filter: SamplerType,
-        min_filter: SamplerType,
+        options: TextureOptions,
     ) -> Self {
-        let wgpu = &engine.context.as_ref().expect("need graphic context").wgpu;
         let diffuse_rgba = img.to_rgba8();
         let (width, height) = img.dimensions();
+        let size = Vec2 {
+            x: width,
+            y: height,
+        };
+
+        Self::from_rgba_data(engine, &diffuse_rgba, size, label, options)
+    }
+
+    pub(crate) fn from_rgba_data(
+        engine: &Engine,
+        diffuse_rgba: &[u8],
+        size: Vec2<u32>,
+        label: Option<&str>,
+        options: TextureOptions,
+    ) -> Self {
+        let wgpu = &engine.context.as_ref().expect("need graphic context").wgpu;
+        let Vec2 {
+            x: width,
+            y: height,
+        } = size;
 
         let texture_size = wgpu::Extent3d {
             width,
@@ -149,9 +338,16 @@ impl Texture {
             depth_or_array_layers: 1,
         };
 
+        // each mip is half the size of the last one down to 1x1
+        let mip_level_count = if options.mipmaps {
+            u32::BITS - width.max(height).leading_zeros()
+        } else {
+            1
+        };
+
         let texture = wgpu.device.create_texture(&wgpu::TextureDescriptor {
             size: texture_size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -169,7 +365,7 @@ impl Texture {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            &diffuse_rgba,
+            diffuse_rgba,
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
                 bytes_per_row: Some(4 * width),
@@ -178,19 +374,39 @@ impl Texture {
             texture_size,
         );
 
+        if mip_level_count > 1 {
+            write_mipmaps(wgpu, &texture, diffuse_rgba, size, mip_level_count);
+        }
+
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         let bind_group_layout = layouts::create_texture_layout(&wgpu.device);
 
+        let all_linear = [
+            options.mag_sampler,
+            options.min_sampler,
+            options.mipmap_sampler,
+        ]
+        .iter()
+        .all(|sampler| *sampler == SamplerType::LinearInterpolation);
+        // wgpu only allows anisotropic filtering when everything is linear
+        let anisotropy_clamp = if options.anisotropy > 1 && !all_linear {
+            log::warn!("anisotropic filtering needs every sampler to be linear, ignoring it");
+            1
+        } else {
+            options.anisotropy.clamp(1, 16)
+        };
+
         let texture_sampler = wgpu.device.create_sampler(&wgpu::SamplerDescriptor {
             // what to do when given cordinates outside the textures height/width
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_u: options.address_mode_u.into(),
+            address_mode_v: options.address_mode_v.into(),
             address_mode_w: wgpu::AddressMode::ClampToEdge,
             // what do when give less or more than 1 pixel to sample
             // linear interprelates between all of them nearest gives the closet colour
-            mag_filter: mag_filter.into(),
-            min_filter: min_filter.into(),
-            mipmap_filter: wgpu::FilterMode::Nearest,
+            mag_filter: options.mag_sampler.into(),
+            min_filter: options.min_sampler.into(),
+            mipmap_filter: options.mipmap_sampler.into(),
+            anisotropy_clamp,
             ..Default::default()
         });
 
@@ -215,6 +431,7 @@ impl Texture {
         };
 
         Self {
+            texture,
             _view: view,
             bind_group,
             size,
@@ -222,6 +439,133 @@ impl Texture {
     }
 }
 
+// how many bytes of rgba a texture of this size takes, None if it doesnt fit in a usize
+fn rgba_len(size: Vec2<u32>) -> Option<usize> {
+    (size.x as usize)
+        .checked_mul(size.y as usize)?
+        .checked_mul(4)
+}
+
+// shrinks the image on the cpu for every mip level, slower than doing it on the
+// gpu but works the same on every backend
+fn write_mipmaps(
+    wgpu: &WgpuClump,
+    texture: &wgpu::Texture,
+    rgba: &[u8],
+    size: Vec2<u32>,
+    mip_level_count: u32,
+) {
+    let image = RgbaImage::from_raw(size.x, size.y, rgba.to_vec()).unwrap();
+    // the texture is srgb so the pixels have to be made linear before averaging them,
+    // otherwise every level gets darker than the one before it
+    let mut linear = to_linear(&image);
+
+    for mip_level in 1..mip_level_count {
+        let width = (size.x >> mip_level).max(1);
+        let height = (size.y >> mip_level).max(1);
+        // each level is made from the last one so big textures dont get slower each level
+        linear = imageops::resize(&linear, width, height, imageops::FilterType::Triangle);
+        let image = to_srgb(&linear);
+
+        wgpu.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &image,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+// alpha isnt gamma corrected so it is only scaled
+fn to_linear(image: &RgbaImage) -> Rgba32FImage {
+    let table: Vec<f32> = (0..=255u8)
+        .map(|value| {
+            let value = value as f32 / 255.0;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        })
+        .collect();
+
+    // premultiplied so the colour of see through pixels doesnt bleed into their neighbours
+    Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let a = a as f32 / 255.0;
+        image::Rgba([
+            table[r as usize] * a,
+            table[g as usize] * a,
+            table[b as usize] * a,
+            a,
+        ])
+    })
+}
+
+fn to_srgb(image: &Rgba32FImage) -> RgbaImage {
+    let encode = |value: f32| {
+        let value = if value <= 0.0031308 {
+            value * 12.92
+        } else {
+            1.055 * value.powf(1.0 / 2.4) - 0.055
+        };
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        // fully transparent pixels have no colour left to get back
+        if a <= 0.0 {
+            return image::Rgba([0, 0, 0, 0]);
+        }
+
+        image::Rgba([
+            encode(r / a),
+            encode(g / a),
+            encode(b / a),
+            (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ])
+    })
+}
+
+/// Errors that can occur when using [Texture::update_region]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureUpdateError {
+    /// The texture isnt loaded yet or was unloaded
+    NotLoadedYet,
+    /// Part of the region is outside of the texture
+    OutOfBounds,
+    /// There isnt exactly 4 bytes for every pixel in the region
+    WrongSize,
+}
+
+impl Display for TextureUpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotLoadedYet => {
+                write!(f, "The texture has not loaded yet please try again later")
+            }
+            Self::OutOfBounds => write!(f, "The region is outside of the texture"),
+            Self::WrongSize => write!(f, "The amount of pixels does not match the region"),
+        }
+    }
+}
+
+impl std::error::Error for TextureUpdateError {}
+
 /// Loading a texture can fail in two senarios. Either the file cant be opened, or the
 /// file loaded is not a supported image file type.
 #[derive(Debug)]
@@ -341,6 +685,11 @@ impl UniformTexture {
         &self.inner_texture.as_ref().unwrap().sampler
     }
 
+    /// returns `None` if the texture has not been created on the GPU yet
+    pub(crate) fn get_texture(&self) -> Option<&wgpu::Texture> {
+        self.inner_texture.as_ref().map(|t| &t.inner_texture)
+    }
+
     pub(crate) fn get_sampler_info(&self) -> (SamplerType, SamplerType) {
         (self.mag_sampler, self.min_sampler)
     }
@@ -426,7 +775,9 @@ impl InnerTexture {
             mip_level_count: 1,
             sample_count: 1,
             format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
 
@@ -451,7 +802,9 @@ impl InnerTexture {
             mip_level_count: 1,
             sample_count: 1,
             format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
 
@@ -517,3 +870,184 @@ impl From<SamplerType> for wgpu::FilterMode {
         }
     }
 }
+
+/// What happens when a texture is sampled with uvs outside of 0.0 to 1.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressMode {
+    /// Uses the colour of the closest edge pixel
+    ClampToEdge,
+    /// Tiles the texture, great for backgrounds
+    Repeat,
+    /// Tiles the texture but every other tile is flipped
+    MirrorRepeat,
+}
+
+impl From<AddressMode> for wgpu::AddressMode {
+    fn from(value: AddressMode) -> Self {
+        match value {
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Controls how a texture is sampled and if it has mipmaps. Used with constructors
+/// like [Texture::new_with_options]. The defualts match [Texture::new]:
+/// ```rust
+/// # use bottomless_pit::texture::{AddressMode, SamplerType, TextureOptions};
+/// # let options = TextureOptions::new();
+/// # assert_eq!(options, TextureOptions::new()
+/// #     .with_sampler(SamplerType::LinearInterpolation, SamplerType::NearestNeighbor)
+/// #     .with_mipmap_sampler(SamplerType::NearestNeighbor)
+/// #     .with_address_mode(AddressMode::Repeat)
+/// #     .with_anisotropy(1));
+/// // mag_sampler: LinearInterpolation
+/// // min_sampler: NearestNeighbor
+/// // mipmap_sampler: NearestNeighbor
+/// // address_mode: Repeat
+/// // anisotropy: 1
+/// // mipmaps: false
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub(crate) mag_sampler: SamplerType,
+    pub(crate) min_sampler: SamplerType,
+    pub(crate) mipmap_sampler: SamplerType,
+    pub(crate) address_mode_u: AddressMode,
+    pub(crate) address_mode_v: AddressMode,
+    pub(crate) anisotropy: u16,
+    pub(crate) mipmaps: bool,
+}
+
+impl TextureOptions {
+    pub fn new() -> Self {
+        Self {
+            mag_sampler: SamplerType::LinearInterpolation,
+            min_sampler: SamplerType::NearestNeighbor,
+            mipmap_sampler: SamplerType::NearestNeighbor,
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v: AddressMode::Repeat,
+            anisotropy: 1,
+            mipmaps: false,
+        }
+    }
+
+    /// Sets the sampling for when the texture is drawn larger (`mag_sampler`) and
+    /// smaller (`min_sampler`) than its original resolution
+    pub fn with_sampler(self, mag_sampler: SamplerType, min_sampler: SamplerType) -> Self {
+        Self {
+            mag_sampler,
+            min_sampler,
+            ..self
+        }
+    }
+
+    /// Sets how the texture blends between mipmaps, linear gives smoother transitions
+    /// when zooming out
+    pub fn with_mipmap_sampler(self, mipmap_sampler: SamplerType) -> Self {
+        Self {
+            mipmap_sampler,
+            ..self
+        }
+    }
+
+    /// Sets what happens to uvs outside of 0.0 to 1.0 in both directions
+    pub fn with_address_mode(self, address_mode: AddressMode) -> Self {
+        Self {
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            ..self
+        }
+    }
+
+    /// Sets what happens to uvs outside of 0.0 to 1.0 seperately for the
+    /// horizontal (`u`) and vertical (`v`) directions
+    pub fn with_address_modes(self, u: AddressMode, v: AddressMode) -> Self {
+        Self {
+            address_mode_u: u,
+            address_mode_v: v,
+            ..self
+        }
+    }
+
+    /// Sets the max amount of anisotropic filtering from 1 to 16, this keeps textures
+    /// sharp when seen at steep angles or stretched. Only works when the mag, min and
+    /// mipmap samplers are all [SamplerType::LinearInterpolation], otherwise it is ignored.
+    pub fn with_anisotropy(self, anisotropy: u16) -> Self {
+        Self { anisotropy, ..self }
+    }
+
+    /// Generates mipmaps when the texture is loaded, these are smaller versions of the
+    /// texture used when it is drawn smaller which stops shimmering when zoomed out
+    pub fn with_mipmaps(self) -> Self {
+        Self {
+            mipmaps: true,
+            ..self
+        }
+    }
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_round_trips() {
+        let mut image = RgbaImage::from_fn(256, 1, |x, _| {
+            image::Rgba([x as u8, x as u8, 255 - x as u8, x as u8])
+        });
+        let round_trip = to_srgb(&to_linear(&image));
+
+        // the only pixel with no alpha loses its colour
+        image.put_pixel(0, 0, image::Rgba([0, 0, 0, 0]));
+        assert_eq!(round_trip, image);
+    }
+
+    #[test]
+    fn mips_are_averaged_in_linear_space() {
+        let image = RgbaImage::from_fn(2, 2, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgba([255, 255, 255, 255])
+            } else {
+                image::Rgba([0, 0, 0, 255])
+            }
+        });
+
+        let linear = imageops::resize(&to_linear(&image), 1, 1, imageops::FilterType::Triangle);
+        // half as much light is 188 in srgb, averaging the bytes would give 128
+        assert_eq!(
+            *to_srgb(&linear).get_pixel(0, 0),
+            image::Rgba([188, 188, 188, 255])
+        );
+
+        // the white of a transparent pixel doesnt turn its red neighbour pink
+        let image = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 0])
+            }
+        });
+        let linear = imageops::resize(&to_linear(&image), 1, 1, imageops::FilterType::Triangle);
+        assert_eq!(
+            *to_srgb(&linear).get_pixel(0, 0),
+            image::Rgba([255, 0, 0, 128])
+        );
+
+        let transparent = RgbaImage::from_pixel(2, 2, image::Rgba([255, 255, 255, 0]));
+        let linear = imageops::resize(
+            &to_linear(&transparent),
+            1,
+            1,
+            imageops::FilterType::Triangle,
+        );
+        assert_eq!(*to_srgb(&linear).get_pixel(0, 0), image::Rgba([0, 0, 0, 0]));
+    }
+}
@@ -0,0 +1,1823 @@
+//! Contains the [Tilemap] which loads maps made with [Tiled](https://www.mapeditor.org/)
+//! and the [TilemapMaterial] which draws their tile layers.
+//! ```rust,no_run
+//! # use bottomless_pit::camera::Camera;
+//! # use bottomless_pit::engine_handle::EngineBuilder;
+//! # use bottomless_pit::render::Renderer;
+//! # use bottomless_pit::resource::LoadingOp;
+//! # use bottomless_pit::texture::{SamplerType, Texture};
+//! # use bottomless_pit::tilemap::{Tilemap, TilemapMaterial};
+//! let mut engine = EngineBuilder::new().build().unwrap();
+//! // both the XML (.tmx) and JSON (.tmj) formats can be loaded
+//! let map = Tilemap::new(&mut engine, "level_1.tmx", LoadingOp::Blocking);
+//! // one texture for every tileset in the same order as they are in the map
+//! let tiles = Texture::new_with_sampler(&mut engine, "tiles.png", SamplerType::NearestNeighbor, LoadingOp::Blocking);
+//! let mut map_material = TilemapMaterial::new(map, &[tiles], &engine);
+//!
+//! // the objects and properties can be read once the map is loaded
+//! # fn update(engine: &bottomless_pit::engine_handle::Engine, map: bottomless_pit::resource::ResourceId<Tilemap>) {
+//! let spawn = engine
+//!     .get_tilemap(map)
+//!     .and_then(|map| map.get_object_layer("entities"))
+//!     .and_then(|layer| layer.objects.iter().find(|o| o.name == "player_spawn"));
+//! # }
+//! # fn render<'o>(map_material: &'o mut TilemapMaterial, camera: &'o mut Camera, mut renderer: Renderer<'o, 'o>) {
+//! // later inside of Game::render, only the chunks the camera can see are drawn
+//! camera.set_active(&mut renderer);
+//! map_material.draw(&mut renderer);
+//! # }
+//! ```
+//! Tilesets can be embedded in the map or saved in their own `.tsx` or `.tsj` file, which
+//! is loaded along with the map. Only finite orthogonal maps are supported.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use base64::Engine as _;
+use roxmltree::Node;
+use serde_json::Value;
+use wgpu::util::DeviceExt;
+
+use crate::colour::Colour;
+use crate::engine_handle::Engine;
+use crate::render::Renderer;
+use crate::resource::{self, ExtraFiles, InProgressResource, LoadingOp, ResourceId, ResourceType};
+use crate::shader::Shader;
+use crate::texture::Texture;
+use crate::vectors::Vec2;
+use crate::vertex::Vertex;
+
+// Tiled stores how a tile is flipped in the highest bits of its global id
+const FLIPPED_HORIZONTALLY: u32 = 0x80000000;
+const FLIPPED_VERTICALLY: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY: u32 = 0x20000000;
+// only used by hexagonal maps
+const ROTATED_HEXAGONAL: u32 = 0x10000000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL);
+
+// width and height of a chunk in tiles, 16x16 tiles keeps the
+// 4 verticies per tile inside of u16 indicies
+const CHUNK_SIZE: u32 = 16;
+
+/// The custom properties set on maps, layers, tilesets, tiles and objects
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// A custom property from Tiled
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Colour(Colour),
+    /// A path to a file relative to the map
+    File(String),
+    /// The id of an object on the map
+    Object(u32),
+    /// A custom class made of more properties
+    Class(Properties),
+}
+
+/// A map made with Tiled, loaded with [Tilemap::new] and drawn with a [TilemapMaterial]
+#[derive(Clone, Debug)]
+pub struct Tilemap {
+    size: Vec2<u32>,
+    tile_size: Vec2<u32>,
+    tilesets: Vec<Tileset>,
+    layers: Vec<Layer>,
+    properties: Properties,
+}
+
+impl Default for Tilemap {
+    /// An empty map used when a map fails to load
+    fn default() -> Self {
+        Self {
+            size: Vec2 { x: 0, y: 0 },
+            tile_size: Vec2 { x: 0, y: 0 },
+            tilesets: Vec::new(),
+            layers: Vec::new(),
+            properties: Properties::new(),
+        }
+    }
+}
+
+/// A set of tiles cut out of a single image
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tileset {
+    pub name: String,
+    /// The global id of the first tile in the set
+    pub first_gid: u32,
+    /// The size of each tile in pixels, this can be bigger than the map's tiles
+    pub tile_size: Vec2<u32>,
+    pub columns: u32,
+    pub tile_count: u32,
+    /// The gap in pixels between tiles
+    pub spacing: u32,
+    /// The gap in pixels around the edge of the image
+    pub margin: u32,
+    /// Where the image is relative to the working directory, use this to load the
+    /// texture given to [TilemapMaterial::new]
+    pub image: Option<PathBuf>,
+    pub image_size: Vec2<u32>,
+    pub properties: Properties,
+    /// The properties of individual tiles by their id inside of the tileset
+    pub tile_properties: HashMap<u32, Properties>,
+}
+
+/// A layer of the map, group layers are flattened into the layers they contain
+#[derive(Clone, Debug, PartialEq)]
+pub enum Layer {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+}
+
+impl Layer {
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::Tiles(layer) => &layer.name,
+            Self::Objects(layer) => &layer.name,
+        }
+    }
+}
+
+/// A grid of tiles the size of the map
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    pub tint: Colour,
+    /// How far the layer is moved in pixels
+    pub offset: Vec2<f32>,
+    pub properties: Properties,
+    /// The global ids of the tiles row by row, 0 means there is no tile. Use
+    /// [Tilemap::get_tile] to turn these into a [Tile]
+    pub tiles: Vec<u32>,
+}
+
+/// A layer of shapes and points placed on the map
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    /// How far the layer is moved in pixels
+    pub offset: Vec2<f32>,
+    pub properties: Properties,
+    pub objects: Vec<MapObject>,
+}
+
+/// An object placed on the map
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    /// Called type in versions of Tiled before 1.9
+    pub class: String,
+    /// The top left of the object in pixels, tile objects use their bottom left instead
+    pub position: Vec2<f32>,
+    pub size: Vec2<f32>,
+    /// Rotation in degrees clockwise around the position
+    pub rotation: f32,
+    pub visible: bool,
+    /// The global id of the tile for tile objects
+    pub gid: Option<u32>,
+    pub shape: ObjectShape,
+    pub properties: Properties,
+}
+
+/// The shape of a [MapObject]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    /// Points relative to the position of the object
+    Polygon(Vec<Vec2<f32>>),
+    /// Points relative to the position of the object
+    Polyline(Vec<Vec2<f32>>),
+    Text(String),
+}
+
+/// A tile found from its global id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// The index of the tileset in [Tilemap::get_tilesets]
+    pub tileset: usize,
+    /// The id of the tile inside of its tileset
+    pub id: u32,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// Flipped along the line from the top left to the bottom right,
+    /// combined with the other flips this rotates the tile
+    pub flip_diagonal: bool,
+}
+
+impl Tilemap {
+    /// Attempts to load a map saved by Tiled as either `.tmx` or `.tmj`, tilesets saved
+    /// outside of the map are loaded from their path relative to the map
+    pub fn new<P>(engine: &mut Engine, path: P, loading_op: LoadingOp) -> ResourceId<Tilemap>
+    where
+        P: AsRef<Path>,
+    {
+        let typed_id = resource::generate_id::<Tilemap>();
+        let id = typed_id.get_id();
+        let path = path.as_ref();
+        let ip_resource = InProgressResource::new(path, id, ResourceType::Tilemap, loading_op);
+
+        engine.loader.load(ip_resource, engine.get_proxy());
+
+        typed_id
+    }
+
+    pub(crate) fn from_resource_data(
+        data: &[u8],
+        path: &Path,
+        files: &ExtraFiles,
+    ) -> Result<Self, TilemapError> {
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let text = std::str::from_utf8(data).map_err(|_| TilemapError::NotText)?;
+
+        if is_xml(text) {
+            Self::from_xml(text, directory, files)
+        } else {
+            Self::from_json(text, directory, files)
+        }
+    }
+
+    // the paths of tilesets saved in their own file, these are read by the loader along
+    // with the map. broken maps have none and fail to parse later on
+    pub(crate) fn external_tilesets(data: &[u8], path: &Path) -> Vec<PathBuf> {
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let Ok(text) = std::str::from_utf8(data) else {
+            return Vec::new();
+        };
+
+        let sources: Vec<String> = if is_xml(text) {
+            let Ok(document) = roxmltree::Document::parse(text) else {
+                return Vec::new();
+            };
+            document
+                .root_element()
+                .children()
+                .filter(|node| node.has_tag_name("tileset"))
+                .filter_map(|node| node.attribute("source").map(str::to_string))
+                .collect()
+        } else {
+            let Ok(map) = serde_json::from_str::<Value>(text) else {
+                return Vec::new();
+            };
+            match map.get("tilesets") {
+                Some(Value::Array(tilesets)) => tilesets
+                    .iter()
+                    .filter_map(|tileset| tileset.get("source").and_then(Value::as_str))
+                    .map(str::to_string)
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        sources
+            .iter()
+            .map(|source| directory.join(source))
+            .collect()
+    }
+
+    /// The size of the map in tiles
+    pub fn get_size(&self) -> Vec2<u32> {
+        self.size
+    }
+
+    /// The size of a single tile of the map in pixels
+    pub fn get_tile_size(&self) -> Vec2<u32> {
+        self.tile_size
+    }
+
+    /// The size of the whole map in pixels
+    pub fn get_pixel_size(&self) -> Vec2<f32> {
+        Vec2 {
+            x: (self.size.x * self.tile_size.x) as f32,
+            y: (self.size.y * self.tile_size.y) as f32,
+        }
+    }
+
+    /// All the tilesets in the order they are in the map
+    pub fn get_tilesets(&self) -> &[Tileset] {
+        &self.tilesets
+    }
+
+    /// All the layers from the bottom to the top
+    pub fn get_layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Gets a layer by its name
+    pub fn get_layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|layer| layer.get_name() == name)
+    }
+
+    /// Gets a tile layer by its name
+    pub fn get_tile_layer(&self, name: &str) -> Option<&TileLayer> {
+        match self.get_layer(name)? {
+            Layer::Tiles(layer) => Some(layer),
+            Layer::Objects(_) => None,
+        }
+    }
+
+    /// Gets an object layer by its name
+    pub fn get_object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        match self.get_layer(name)? {
+            Layer::Objects(layer) => Some(layer),
+            Layer::Tiles(_) => None,
+        }
+    }
+
+    /// The custom properties of the map
+    pub fn get_properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Gets the tile at a position in tiles on a tile layer. Returns None when
+    /// there is no tile there or the layer does not exist.
+    pub fn get_tile(&self, layer: &str, x: u32, y: u32) -> Option<Tile> {
+        if x >= self.size.x || y >= self.size.y {
+            return None;
+        }
+
+        let layer = self.get_tile_layer(layer)?;
+        self.get_tile_from_gid(layer.tiles[(y * self.size.x + x) as usize])
+    }
+
+    /// Turns a global id like the ones in [TileLayer::tiles] into a [Tile]
+    pub fn get_tile_from_gid(&self, gid: u32) -> Option<Tile> {
+        let id = gid & GID_MASK;
+        if id == 0 {
+            return None;
+        }
+
+        // tilesets are sorted by their first gid
+        let tileset = self
+            .tilesets
+            .iter()
+            .rposition(|tileset| tileset.first_gid <= id)?;
+
+        Some(Tile {
+            tileset,
+            id: id - self.tilesets[tileset].first_gid,
+            flip_horizontal: gid & FLIPPED_HORIZONTALLY != 0,
+            flip_vertical: gid & FLIPPED_VERTICALLY != 0,
+            flip_diagonal: gid & FLIPPED_DIAGONALLY != 0,
+        })
+    }
+
+    /// Gets the custom properties set on a single tile in its tileset
+    pub fn get_tile_properties(&self, tile: &Tile) -> Option<&Properties> {
+        self.tilesets
+            .get(tile.tileset)?
+            .tile_properties
+            .get(&tile.id)
+    }
+
+    fn from_xml(text: &str, directory: &Path, files: &ExtraFiles) -> Result<Self, TilemapError> {
+        let document = roxmltree::Document::parse(text)?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return Err(TilemapError::MissingField("map"));
+        }
+
+        check_map_kind(
+            map.attribute("orientation").unwrap_or("orthogonal"),
+            map.attribute("infinite") == Some("1"),
+        )?;
+
+        let size = Vec2 {
+            x: required_attribute(map, "width")?,
+            y: required_attribute(map, "height")?,
+        };
+        let tile_size = Vec2 {
+            x: required_attribute(map, "tilewidth")?,
+            y: required_attribute(map, "tileheight")?,
+        };
+
+        let mut tilesets = map
+            .children()
+            .filter(|node| node.has_tag_name("tileset"))
+            .map(|node| xml_tileset(node, directory, files))
+            .collect::<Result<Vec<Tileset>, TilemapError>>()?;
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        xml_layers(map, LayerParent::ROOT, size, &mut layers)?;
+
+        Ok(Self {
+            size,
+            tile_size,
+            tilesets,
+            layers,
+            properties: xml_properties(map)?,
+        })
+    }
+
+    fn from_json(text: &str, directory: &Path, files: &ExtraFiles) -> Result<Self, TilemapError> {
+        let map: Value = serde_json::from_str(text)?;
+
+        check_map_kind(
+            map.get("orientation")
+                .and_then(Value::as_str)
+                .unwrap_or("orthogonal"),
+            map.get("infinite").and_then(Value::as_bool) == Some(true),
+        )?;
+
+        let size = Vec2 {
+            x: required_number(&map, "width")?,
+            y: required_number(&map, "height")?,
+        };
+        let tile_size = Vec2 {
+            x: required_number(&map, "tilewidth")?,
+            y: required_number(&map, "tileheight")?,
+        };
+
+        let mut tilesets = match map.get("tilesets") {
+            Some(Value::Array(tilesets)) => tilesets
+                .iter()
+                .map(|tileset| json_tileset(tileset, directory, files))
+                .collect::<Result<Vec<Tileset>, TilemapError>>()?,
+            _ => Vec::new(),
+        };
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        json_layers(&map, LayerParent::ROOT, size, &mut layers)?;
+
+        Ok(Self {
+            size,
+            tile_size,
+            tilesets,
+            layers,
+            properties: json_properties(&map),
+        })
+    }
+}
+
+// tmx and tsx files are xml and always start with a tag
+fn is_xml(text: &str) -> bool {
+    text.trim_start().starts_with('<')
+}
+
+// the map only has the first gid and where the rest of the tileset is saved
+fn external_tileset(
+    first_gid: u32,
+    path: &Path,
+    files: &ExtraFiles,
+) -> Result<Tileset, TilemapError> {
+    let data = files
+        .get(path)
+        .ok_or_else(|| TilemapError::MissingTileset(path.to_path_buf()))?;
+    let text = std::str::from_utf8(data).map_err(|_| TilemapError::NotText)?;
+    // the image is relative to the tileset not the map
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    if is_xml(text) {
+        let document = roxmltree::Document::parse(text)?;
+        let tileset = document.root_element();
+        if !tileset.has_tag_name("tileset") {
+            return Err(TilemapError::MissingField("tileset"));
+        }
+
+        xml_tileset_contents(tileset, first_gid, directory)
+    } else {
+        let tileset: Value = serde_json::from_str(text)?;
+        json_tileset_contents(&tileset, first_gid, directory)
+    }
+}
+
+fn check_map_kind(orientation: &str, infinite: bool) -> Result<(), TilemapError> {
+    if orientation != "orthogonal" {
+        return Err(TilemapError::UnsupportedOrientation(
+            orientation.to_string(),
+        ));
+    }
+
+    if infinite {
+        return Err(TilemapError::InfiniteMap);
+    }
+
+    Ok(())
+}
+
+// group layers pass their offset, opacity and visibility down to their children
+#[derive(Clone, Copy)]
+struct LayerParent {
+    offset: Vec2<f32>,
+    opacity: f32,
+    visible: bool,
+}
+
+impl LayerParent {
+    const ROOT: Self = Self {
+        offset: Vec2 { x: 0.0, y: 0.0 },
+        opacity: 1.0,
+        visible: true,
+    };
+
+    fn child(self, offset: Vec2<f32>, opacity: f32, visible: bool) -> Self {
+        Self {
+            offset: self.offset + offset,
+            opacity: self.opacity * opacity,
+            visible: self.visible && visible,
+        }
+    }
+}
+
+fn attribute<T: std::str::FromStr>(node: Node, name: &str) -> Option<T> {
+    node.attribute(name).and_then(|value| value.parse().ok())
+}
+
+fn required_attribute<T: std::str::FromStr>(
+    node: Node,
+    name: &'static str,
+) -> Result<T, TilemapError> {
+    attribute(node, name).ok_or(TilemapError::MissingField(name))
+}
+
+fn xml_tileset(node: Node, directory: &Path, files: &ExtraFiles) -> Result<Tileset, TilemapError> {
+    let first_gid = required_attribute(node, "firstgid")?;
+    match node.attribute("source") {
+        Some(source) => external_tileset(first_gid, &directory.join(source), files),
+        None => xml_tileset_contents(node, first_gid, directory),
+    }
+}
+
+fn xml_tileset_contents(
+    node: Node,
+    first_gid: u32,
+    directory: &Path,
+) -> Result<Tileset, TilemapError> {
+    let image = node.children().find(|child| child.has_tag_name("image"));
+    let tile_properties = node
+        .children()
+        .filter(|child| child.has_tag_name("tile"))
+        .map(|tile| Ok((required_attribute(tile, "id")?, xml_properties(tile)?)))
+        .collect::<Result<HashMap<u32, Properties>, TilemapError>>()?;
+
+    Ok(Tileset {
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        first_gid,
+        tile_size: Vec2 {
+            x: required_attribute(node, "tilewidth")?,
+            y: required_attribute(node, "tileheight")?,
+        },
+        columns: required_attribute(node, "columns")?,
+        tile_count: required_attribute(node, "tilecount")?,
+        spacing: attribute(node, "spacing").unwrap_or(0),
+        margin: attribute(node, "margin").unwrap_or(0),
+        image: image
+            .and_then(|image| image.attribute("source"))
+            .map(|source| directory.join(source)),
+        image_size: Vec2 {
+            x: image.and_then(|i| attribute(i, "width")).unwrap_or(0),
+            y: image.and_then(|i| attribute(i, "height")).unwrap_or(0),
+        },
+        properties: xml_properties(node)?,
+        tile_properties,
+    })
+}
+
+fn xml_layers(
+    node: Node,
+    parent: LayerParent,
+    map_size: Vec2<u32>,
+    layers: &mut Vec<Layer>,
+) -> Result<(), TilemapError> {
+    for child in node.children().filter(Node::is_element) {
+        let name = child.attribute("name").unwrap_or_default().to_string();
+        let offset = Vec2 {
+            x: attribute(child, "offsetx").unwrap_or(0.0),
+            y: attribute(child, "offsety").unwrap_or(0.0),
+        };
+        let opacity = attribute(child, "opacity").unwrap_or(1.0);
+        let visible = child.attribute("visible") != Some("0");
+        let parent = parent.child(offset, opacity, visible);
+
+        match child.tag_name().name() {
+            "layer" => {
+                let data = child
+                    .children()
+                    .find(|n| n.has_tag_name("data"))
+                    .ok_or(TilemapError::MissingField("data"))?;
+
+                let tiles = match data.attribute("encoding") {
+                    // really old maps list every tile as an element
+                    None => data
+                        .children()
+                        .filter(|n| n.has_tag_name("tile"))
+                        .map(|tile| attribute(tile, "gid").unwrap_or(0))
+                        .collect(),
+                    Some(encoding) => decode_tiles(
+                        data.text().unwrap_or_default(),
+                        encoding,
+                        data.attribute("compression"),
+                    )?,
+                };
+
+                layers.push(Layer::Tiles(TileLayer {
+                    name,
+                    visible: parent.visible,
+                    opacity: parent.opacity,
+                    tint: child
+                        .attribute("tintcolor")
+                        .and_then(parse_colour)
+                        .unwrap_or(Colour::WHITE),
+                    offset: parent.offset,
+                    properties: xml_properties(child)?,
+                    tiles: check_tile_count(tiles, map_size)?,
+                }));
+            }
+            "objectgroup" => {
+                let objects = child
+                    .children()
+                    .filter(|n| n.has_tag_name("object"))
+                    .map(xml_object)
+                    .collect::<Result<Vec<MapObject>, TilemapError>>()?;
+
+                layers.push(Layer::Objects(ObjectLayer {
+                    name,
+                    visible: parent.visible,
+                    opacity: parent.opacity,
+                    offset: parent.offset,
+                    properties: xml_properties(child)?,
+                    objects,
+                }));
+            }
+            "group" => xml_layers(child, parent, map_size, layers)?,
+            // image layers and everything else are not supported
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn xml_object(node: Node) -> Result<MapObject, TilemapError> {
+    let points = |shape: Node| {
+        shape
+            .attribute("points")
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter_map(|point| {
+                let (x, y) = point.split_once(',')?;
+                Some(Vec2 {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                })
+            })
+            .collect()
+    };
+
+    let mut shape = ObjectShape::Rectangle;
+    for child in node.children() {
+        match child.tag_name().name() {
+            "ellipse" => shape = ObjectShape::Ellipse,
+            "point" => shape = ObjectShape::Point,
+            "polygon" => shape = ObjectShape::Polygon(points(child)),
+            "polyline" => shape = ObjectShape::Polyline(points(child)),
+            "text" => shape = ObjectShape::Text(child.text().unwrap_or_default().to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(MapObject {
+        id: attribute(node, "id").unwrap_or(0),
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        class: node
+            .attribute("class")
+            .or(node.attribute("type"))
+            .unwrap_or_default()
+            .to_string(),
+        position: Vec2 {
+            x: attribute(node, "x").unwrap_or(0.0),
+            y: attribute(node, "y").unwrap_or(0.0),
+        },
+        size: Vec2 {
+            x: attribute(node, "width").unwrap_or(0.0),
+            y: attribute(node, "height").unwrap_or(0.0),
+        },
+        rotation: attribute(node, "rotation").unwrap_or(0.0),
+        visible: node.attribute("visible") != Some("0"),
+        gid: attribute(node, "gid"),
+        shape,
+        properties: xml_properties(node)?,
+    })
+}
+
+fn xml_properties(node: Node) -> Result<Properties, TilemapError> {
+    let Some(properties) = node.children().find(|n| n.has_tag_name("properties")) else {
+        return Ok(Properties::new());
+    };
+
+    properties
+        .children()
+        .filter(|n| n.has_tag_name("property"))
+        .map(|property| {
+            let name = property
+                .attribute("name")
+                .ok_or(TilemapError::MissingField("name"))?;
+            // multi line strings are stored as text instead of in the value
+            let value = property
+                .attribute("value")
+                .or(property.text())
+                .unwrap_or_default();
+
+            let value = match property.attribute("type").unwrap_or("string") {
+                "class" => PropertyValue::Class(xml_properties(property)?),
+                kind => parse_property(name, kind, value)?,
+            };
+
+            Ok((name.to_string(), value))
+        })
+        .collect()
+}
+
+fn parse_property(name: &str, kind: &str, value: &str) -> Result<PropertyValue, TilemapError> {
+    let invalid = || TilemapError::InvalidProperty(name.to_string());
+
+    let value = match kind {
+        "bool" => PropertyValue::Bool(value == "true"),
+        "int" => PropertyValue::Int(value.parse().map_err(|_| invalid())?),
+        "float" => PropertyValue::Float(value.parse().map_err(|_| invalid())?),
+        "color" => PropertyValue::Colour(parse_colour(value).unwrap_or(Colour::WHITE)),
+        "file" => PropertyValue::File(value.to_string()),
+        "object" => PropertyValue::Object(value.parse().map_err(|_| invalid())?),
+        _ => PropertyValue::String(value.to_string()),
+    };
+
+    Ok(value)
+}
+
+fn required_number<T: TryFrom<u64>>(value: &Value, name: &'static str) -> Result<T, TilemapError> {
+    value
+        .get(name)
+        .and_then(Value::as_u64)
+        .and_then(|n| T::try_from(n).ok())
+        .ok_or(TilemapError::MissingField(name))
+}
+
+fn json_float(value: &Value, name: &str, defualt: f32) -> f32 {
+    value
+        .get(name)
+        .and_then(Value::as_f64)
+        .map(|n| n as f32)
+        .unwrap_or(defualt)
+}
+
+fn json_string(value: &Value, name: &str) -> String {
+    value
+        .get(name)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn json_tileset(
+    tileset: &Value,
+    directory: &Path,
+    files: &ExtraFiles,
+) -> Result<Tileset, TilemapError> {
+    let first_gid = required_number(tileset, "firstgid")?;
+    match tileset.get("source").and_then(Value::as_str) {
+        Some(source) => external_tileset(first_gid, &directory.join(source), files),
+        None => json_tileset_contents(tileset, first_gid, directory),
+    }
+}
+
+fn json_tileset_contents(
+    tileset: &Value,
+    first_gid: u32,
+    directory: &Path,
+) -> Result<Tileset, TilemapError> {
+    let tile_properties = match tileset.get("tiles") {
+        Some(Value::Array(tiles)) => tiles
+            .iter()
+            .map(|tile| Ok((required_number(tile, "id")?, json_properties(tile))))
+            .collect::<Result<HashMap<u32, Properties>, TilemapError>>()?,
+        _ => HashMap::new(),
+    };
+
+    Ok(Tileset {
+        name: json_string(tileset, "name"),
+        first_gid,
+        tile_size: Vec2 {
+            x: required_number(tileset, "tilewidth")?,
+            y: required_number(tileset, "tileheight")?,
+        },
+        columns: required_number(tileset, "columns")?,
+        tile_count: required_number(tileset, "tilecount")?,
+        spacing: required_number(tileset, "spacing").unwrap_or(0),
+        margin: required_number(tileset, "margin").unwrap_or(0),
+        image: tileset
+            .get("image")
+            .and_then(Value::as_str)
+            .map(|image| directory.join(image)),
+        image_size: Vec2 {
+            x: required_number(tileset, "imagewidth").unwrap_or(0),
+            y: required_number(tileset, "imageheight").unwrap_or(0),
+        },
+        properties: json_properties(tileset),
+        tile_properties,
+    })
+}
+
+fn json_layers(
+    value: &Value,
+    parent: LayerParent,
+    map_size: Vec2<u32>,
+    layers: &mut Vec<Layer>,
+) -> Result<(), TilemapError> {
+    let Some(Value::Array(children)) = value.get("layers") else {
+        return Ok(());
+    };
+
+    for child in children {
+        let offset = Vec2 {
+            x: json_float(child, "offsetx", 0.0),
+            y: json_float(child, "offsety", 0.0),
+        };
+        let opacity = json_float(child, "opacity", 1.0);
+        let visible = child
+            .get("visible")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let parent = parent.child(offset, opacity, visible);
+
+        match child.get("type").and_then(Value::as_str) {
+            Some("tilelayer") => {
+                let tiles = match child.get("data") {
+                    Some(Value::Array(data)) => data
+                        .iter()
+                        .map(|gid| gid.as_u64().map(|gid| gid as u32))
+                        .collect::<Option<Vec<u32>>>()
+                        .ok_or(TilemapError::InvalidTileData)?,
+                    Some(Value::String(data)) => decode_tiles(
+                        data,
+                        child
+                            .get("encoding")
+                            .and_then(Value::as_str)
+                            .unwrap_or("base64"),
+                        child.get("compression").and_then(Value::as_str),
+                    )?,
+                    _ => return Err(TilemapError::MissingField("data")),
+                };
+
+                layers.push(Layer::Tiles(TileLayer {
+                    name: json_string(child, "name"),
+                    visible: parent.visible,
+                    opacity: parent.opacity,
+                    tint: child
+                        .get("tintcolor")
+                        .and_then(Value::as_str)
+                        .and_then(parse_colour)
+                        .unwrap_or(Colour::WHITE),
+                    offset: parent.offset,
+                    properties: json_properties(child),
+                    tiles: check_tile_count(tiles, map_size)?,
+                }));
+            }
+            Some("objectgroup") => {
+                let objects = match child.get("objects") {
+                    Some(Value::Array(objects)) => objects.iter().map(json_object).collect(),
+                    _ => Vec::new(),
+                };
+
+                layers.push(Layer::Objects(ObjectLayer {
+                    name: json_string(child, "name"),
+                    visible: parent.visible,
+                    opacity: parent.opacity,
+                    offset: parent.offset,
+                    properties: json_properties(child),
+                    objects,
+                }));
+            }
+            Some("group") => json_layers(child, parent, map_size, layers)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn json_object(object: &Value) -> MapObject {
+    let points = |points: &Value| {
+        points
+            .as_array()
+            .map(|points| {
+                points
+                    .iter()
+                    .map(|point| Vec2 {
+                        x: json_float(point, "x", 0.0),
+                        y: json_float(point, "y", 0.0),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let is_set = |name: &str| object.get(name).and_then(Value::as_bool) == Some(true);
+    let shape = if is_set("ellipse") {
+        ObjectShape::Ellipse
+    } else if is_set("point") {
+        ObjectShape::Point
+    } else if let Some(polygon) = object.get("polygon") {
+        ObjectShape::Polygon(points(polygon))
+    } else if let Some(polyline) = object.get("polyline") {
+        ObjectShape::Polyline(points(polyline))
+    } else if let Some(text) = object.get("text") {
+        ObjectShape::Text(json_string(text, "text"))
+    } else {
+        ObjectShape::Rectangle
+    };
+
+    let class = match object.get("class").or(object.get("type")) {
+        Some(Value::String(class)) => class.clone(),
+        _ => String::new(),
+    };
+
+    MapObject {
+        id: required_number(object, "id").unwrap_or(0),
+        name: json_string(object, "name"),
+        class,
+        position: Vec2 {
+            x: json_float(object, "x", 0.0),
+            y: json_float(object, "y", 0.0),
+        },
+        size: Vec2 {
+            x: json_float(object, "width", 0.0),
+            y: json_float(object, "height", 0.0),
+        },
+        rotation: json_float(object, "rotation", 0.0),
+        visible: object
+            .get("visible")
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        gid: required_number(object, "gid").ok(),
+        shape,
+        properties: json_properties(object),
+    }
+}
+
+fn json_properties(value: &Value) -> Properties {
+    let Some(Value::Array(properties)) = value.get("properties") else {
+        return Properties::new();
+    };
+
+    properties
+        .iter()
+        .filter_map(|property| {
+            let name = property.get("name").and_then(Value::as_str)?;
+            let value = property.get("value")?;
+            let kind = property.get("type").and_then(Value::as_str);
+
+            let value = match (kind, value) {
+                (Some("color"), Value::String(colour)) => {
+                    PropertyValue::Colour(parse_colour(colour).unwrap_or(Colour::WHITE))
+                }
+                (Some("file"), Value::String(file)) => PropertyValue::File(file.clone()),
+                (Some("object"), Value::Number(id)) => PropertyValue::Object(id.as_u64()? as u32),
+                (_, value) => json_value(value)?,
+            };
+
+            Some((name.to_string(), value))
+        })
+        .collect()
+}
+
+// class properties in json only store the values without their types
+fn json_value(value: &Value) -> Option<PropertyValue> {
+    let value = match value {
+        Value::Bool(b) => PropertyValue::Bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(int) => PropertyValue::Int(int),
+            None => PropertyValue::Float(n.as_f64()?),
+        },
+        Value::String(s) => PropertyValue::String(s.clone()),
+        Value::Object(members) => PropertyValue::Class(
+            members
+                .iter()
+                .filter_map(|(name, value)| Some((name.clone(), json_value(value)?)))
+                .collect(),
+        ),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+// "#AARRGGBB" or "#RRGGBB"
+fn parse_colour(colour: &str) -> Option<Colour> {
+    let hex = colour.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let alpha = match hex.len() {
+        6 => 255,
+        8 => value >> 24,
+        _ => return None,
+    };
+
+    Some(Colour::from_rgba(
+        ((value >> 16) & 0xFF) as f32,
+        ((value >> 8) & 0xFF) as f32,
+        (value & 0xFF) as f32,
+        alpha as f32 / 255.0,
+    ))
+}
+
+fn decode_tiles(
+    data: &str,
+    encoding: &str,
+    compression: Option<&str>,
+) -> Result<Vec<u32>, TilemapError> {
+    match encoding {
+        "csv" => data
+            .split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| gid.parse().map_err(|_| TilemapError::InvalidTileData))
+            .collect(),
+        "base64" => {
+            let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+            let bytes = base64::engine::general_purpose::STANDARD.decode(data)?;
+
+            let bytes = match compression.unwrap_or_default() {
+                "" => bytes,
+                "zlib" => {
+                    let mut decoded = Vec::new();
+                    flate2::read::ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+                    decoded
+                }
+                "gzip" => {
+                    let mut decoded = Vec::new();
+                    flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+                    decoded
+                }
+                other => return Err(TilemapError::UnsupportedCompression(other.to_string())),
+            };
+
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        other => Err(TilemapError::UnsupportedEncoding(other.to_string())),
+    }
+}
+
+fn check_tile_count(tiles: Vec<u32>, map_size: Vec2<u32>) -> Result<Vec<u32>, TilemapError> {
+    if tiles.len() != (map_size.x * map_size.y) as usize {
+        return Err(TilemapError::InvalidTileData);
+    }
+
+    Ok(tiles)
+}
+
+/// Draws the tile layers of a [Tilemap]. The tiles are put into GPU buffers once
+/// the map and all of its textures have loaded and are reused every frame after that.
+/// The map is split into chunks of 16x16 tiles and only the chunks inside of the
+/// area shown by the active [Camera](crate::camera::Camera) are drawn.
+pub struct TilemapMaterial {
+    map: ResourceId<Tilemap>,
+    textures: Vec<ResourceId<Texture>>,
+    pipeline_id: ResourceId<Shader>,
+    hidden_layers: Vec<String>,
+    // made the first time the map is drawn after everything has loaded
+    layers: Option<Vec<LayerMesh>>,
+    // the texture sizes the uvs in `layers` were made with
+    built_sizes: Vec<Vec2<f32>>,
+    layer: i32,
+}
+
+impl TilemapMaterial {
+    /// Creates a material for a map. There should be one texture for every tileset in the
+    /// order they are in the map, [Tileset::image] has the path to the image for each one.
+    pub fn new(
+        map: ResourceId<Tilemap>,
+        textures: &[ResourceId<Texture>],
+        engine: &Engine,
+    ) -> Self {
+        Self {
+            map,
+            textures: textures.to_vec(),
+            pipeline_id: engine.defualt_pipe_id(),
+            hidden_layers: Vec::new(),
+            layers: None,
+            built_sizes: Vec::new(),
+            layer: 0,
+        }
+    }
+
+    /// Hides or shows a layer, this is on top of the visibility set in Tiled
+    pub fn set_layer_visible(&mut self, layer: &str, visible: bool) {
+        self.hidden_layers.retain(|name| name != layer);
+        if !visible {
+            self.hidden_layers.push(layer.to_string());
+        }
+    }
+
+    /// Throws away the tiles on the GPU so they are made again on the next draw.
+    /// This happens on its own when a texture is reloaded with a diffrent size or
+    /// when the map or one of the textures is unloaded.
+    pub fn rebuild(&mut self) {
+        self.layers = None;
+    }
+
+    /// Changes the layer the whole map is drawn on, see
+    /// [MaterialBuilder::set_layer](crate::material::MaterialBuilder::set_layer).
+    /// This has nothing to do with the tile layers from Tiled.
+    pub fn change_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    /// Gets the layer the whole map is drawn on, not to be confused with [Tilemap::get_layer]
+    pub fn get_layer(&self) -> i32 {
+        self.layer
+    }
+
+    /// Swaps the textures used for each tileset
+    pub fn change_textures(&mut self, textures: &[ResourceId<Texture>]) {
+        self.textures = textures.to_vec();
+        self.rebuild();
+    }
+
+    /// Draws every visible tile layer from the bottom to the top
+    pub fn draw<'others>(&'others mut self, renderer: &mut Renderer<'_, 'others>) {
+        self.draw_layers(None, renderer);
+    }
+
+    /// Draws a single tile layer, this lets things be drawn between layers
+    pub fn draw_layer<'others>(
+        &'others mut self,
+        layer: &str,
+        renderer: &mut Renderer<'_, 'others>,
+    ) {
+        self.draw_layers(Some(layer), renderer);
+    }
+
+    fn draw_layers<'others>(
+        &'others mut self,
+        only: Option<&str>,
+        renderer: &mut Renderer<'_, 'others>,
+    ) {
+        // the uvs depend on the texture sizes so the tiles are made again if a texture
+        // was reloaded at a diffrent size, and thrown away if anything was unloaded
+        let texture_sizes = self.texture_sizes(renderer);
+        let map_loaded = renderer.resources.get_tilemap(&self.map).is_some();
+        match texture_sizes {
+            Some(sizes) if map_loaded => {
+                if self.layers.is_none() || sizes != self.built_sizes {
+                    self.layers = self.build(renderer, &sizes);
+                    self.built_sizes = sizes;
+                }
+            }
+            _ => self.rebuild(),
+        }
+
+        // returns early bc stuff inst loaded so we just ignore it
+        let Some(layers) = &self.layers else {
+            return;
+        };
+
+        let Some(shader) = renderer.resources.get_pipeline(&self.pipeline_id) else {
+            return;
+        };
+
+        let (view_start, view_end) = renderer.get_visible_area();
+        renderer.pass.set_layer(self.layer);
+        shader.set_active(renderer, None);
+
+        let layers = layers
+            .iter()
+            .filter(|layer| only.is_none_or(|name| layer.name == name))
+            .filter(|layer| layer.visible && !self.hidden_layers.contains(&layer.name));
+
+        for layer in layers {
+            let chunks = layer.chunks.iter().filter(|chunk| {
+                chunk.start.x < view_end.x
+                    && chunk.end.x > view_start.x
+                    && chunk.start.y < view_end.y
+                    && chunk.end.y > view_start.y
+            });
+
+            for mesh in chunks.flat_map(|chunk| &chunk.meshes) {
+                let Some(texture) = renderer.resources.get_texture(&self.textures[mesh.tileset])
+                else {
+                    continue;
+                };
+
+                renderer.pass.set_bind_group(0, &texture.bind_group);
+                renderer
+                    .pass
+                    .set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                renderer
+                    .pass
+                    .set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                renderer.pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            }
+        }
+    }
+
+    // returns None untill all of the textures are loaded
+    fn texture_sizes(&self, renderer: &Renderer) -> Option<Vec<Vec2<f32>>> {
+        self.textures
+            .iter()
+            .map(|id| renderer.resources.get_texture(id).map(|t| t.size))
+            .collect()
+    }
+
+    // returns None untill the map is loaded
+    fn build(&self, renderer: &Renderer, texture_sizes: &[Vec2<f32>]) -> Option<Vec<LayerMesh>> {
+        let map = renderer.resources.get_tilemap(&self.map)?;
+
+        if texture_sizes.len() < map.tilesets.len() {
+            log::warn!(
+                "tilemap has {} tilesets but only {} textures were given, the rest wont be drawn",
+                map.tilesets.len(),
+                texture_sizes.len()
+            );
+        }
+
+        let chunks = Vec2 {
+            x: map.size.x.div_ceil(CHUNK_SIZE),
+            y: map.size.y.div_ceil(CHUNK_SIZE),
+        };
+
+        let layers = map
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Tiles(layer) => Some(layer),
+                Layer::Objects(_) => None,
+            })
+            .map(|layer| LayerMesh {
+                name: layer.name.clone(),
+                visible: layer.visible,
+                chunks: (0..chunks.y)
+                    .flat_map(|y| (0..chunks.x).map(move |x| Vec2 { x, y }))
+                    .filter_map(|chunk| build_chunk(map, layer, chunk, texture_sizes, renderer))
+                    .collect(),
+            })
+            .collect();
+
+        Some(layers)
+    }
+}
+
+fn build_chunk(
+    map: &Tilemap,
+    layer: &TileLayer,
+    chunk: Vec2<u32>,
+    texture_sizes: &[Vec2<f32>],
+    renderer: &Renderer,
+) -> Option<Chunk> {
+    let colour = layer.tint.as_raw();
+    let colour = [colour[0], colour[1], colour[2], colour[3] * layer.opacity];
+
+    let mut geometry: Vec<(Vec<Vertex>, Vec<u16>)> = vec![Default::default(); texture_sizes.len()];
+    let mut start = Vec2 {
+        x: f32::MAX,
+        y: f32::MAX,
+    };
+    let mut end = Vec2 {
+        x: f32::MIN,
+        y: f32::MIN,
+    };
+
+    let first = Vec2 {
+        x: chunk.x * CHUNK_SIZE,
+        y: chunk.y * CHUNK_SIZE,
+    };
+    for y in first.y..(first.y + CHUNK_SIZE).min(map.size.y) {
+        for x in first.x..(first.x + CHUNK_SIZE).min(map.size.x) {
+            let gid = layer.tiles[(y * map.size.x + x) as usize];
+            let Some(tile) = map.get_tile_from_gid(gid) else {
+                continue;
+            };
+
+            let Some(texture_size) = texture_sizes.get(tile.tileset) else {
+                continue;
+            };
+
+            let tileset = &map.tilesets[tile.tileset];
+            let size = Vec2 {
+                x: tileset.tile_size.x as f32,
+                y: tileset.tile_size.y as f32,
+            };
+            // tiles bigger than the grid stick out of the top of their cell
+            let position = Vec2 {
+                x: (x * map.tile_size.x) as f32,
+                y: ((y + 1) * map.tile_size.y) as f32 - size.y,
+            } + layer.offset;
+
+            let column = tile.id % tileset.columns.max(1);
+            let row = tile.id / tileset.columns.max(1);
+            let uv_position = Vec2 {
+                x: (tileset.margin + column * (tileset.tile_size.x + tileset.spacing)) as f32,
+                y: (tileset.margin + row * (tileset.tile_size.y + tileset.spacing)) as f32,
+            } / *texture_size;
+            let uv_size = size / *texture_size;
+
+            // the flips are undone in reverse order to find which corner of the
+            // tile shows up at each corner of the quad
+            let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+            let uv = corners.map(|(mut u, mut v)| {
+                if tile.flip_vertical {
+                    v = 1.0 - v;
+                }
+                if tile.flip_horizontal {
+                    u = 1.0 - u;
+                }
+                if tile.flip_diagonal {
+                    std::mem::swap(&mut u, &mut v);
+                }
+
+                [uv_position.x + u * uv_size.x, uv_position.y + v * uv_size.y]
+            });
+
+            let (vertices, indicies) = &mut geometry[tile.tileset];
+            let first_vertex = vertices.len() as u16;
+            for (i, (u, v)) in corners.into_iter().enumerate() {
+                let point = [position.x + u * size.x, position.y + v * size.y];
+                vertices.push(Vertex::from_2d(point, uv[i], colour));
+            }
+            indicies.extend_from_slice(&[0, 1, 2, 3, 0, 2].map(|i| first_vertex + i));
+
+            start = Vec2 {
+                x: start.x.min(position.x),
+                y: start.y.min(position.y),
+            };
+            end = Vec2 {
+                x: end.x.max(position.x + size.x),
+                y: end.y.max(position.y + size.y),
+            };
+        }
+    }
+
+    let device = &renderer.wgpu.device;
+    let meshes: Vec<ChunkMesh> = geometry
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (vertices, _))| !vertices.is_empty())
+        .map(|(tileset, (vertices, indicies))| ChunkMesh {
+            tileset,
+            vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Tilemap Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Tilemap Index Buffer"),
+                contents: bytemuck::cast_slice(&indicies),
+                usage: wgpu::BufferUsages::INDEX,
+            }),
+            index_count: indicies.len() as u32,
+        })
+        .collect();
+
+    if meshes.is_empty() {
+        return None;
+    }
+
+    Some(Chunk { start, end, meshes })
+}
+
+struct LayerMesh {
+    name: String,
+    visible: bool,
+    chunks: Vec<Chunk>,
+}
+
+struct Chunk {
+    // the area covered by the chunk in pixels
+    start: Vec2<f32>,
+    end: Vec2<f32>,
+    // one for each tileset used in the chunk
+    meshes: Vec<ChunkMesh>,
+}
+
+struct ChunkMesh {
+    tileset: usize,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+/// Errors that can occur when loading a [Tilemap]
+#[derive(Debug)]
+pub enum TilemapError {
+    /// The file is not UTF-8 text
+    NotText,
+    XmlError(roxmltree::Error),
+    JsonError(serde_json::Error),
+    /// The map is missing something it needs, like its width
+    MissingField(&'static str),
+    /// A custom property has a value that does not match its type
+    InvalidProperty(String),
+    /// A tileset saved in its own file was not loaded with the map
+    MissingTileset(PathBuf),
+    /// Only orthogonal maps are supported
+    UnsupportedOrientation(String),
+    /// Infinite maps are not supported
+    InfiniteMap,
+    UnsupportedEncoding(String),
+    /// Only zlib and gzip compression are supported
+    UnsupportedCompression(String),
+    Base64Error(base64::DecodeError),
+    DecompressError(std::io::Error),
+    /// The tile data could not be read or does not match the size of the map
+    InvalidTileData,
+}
+
+impl Display for TilemapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotText => write!(f, "tilemap is not valid UTF-8"),
+            Self::XmlError(e) => write!(f, "{}", e),
+            Self::JsonError(e) => write!(f, "{}", e),
+            Self::MissingField(field) => write!(f, "tilemap is missing the field: {}", field),
+            Self::InvalidProperty(name) => write!(f, "property {} has an invalid value", name),
+            Self::MissingTileset(path) => write!(f, "tileset {:?} was not loaded", path),
+            Self::UnsupportedOrientation(o) => write!(f, "{} maps are not supported", o),
+            Self::InfiniteMap => write!(f, "infinite maps are not supported"),
+            Self::UnsupportedEncoding(e) => write!(f, "tile encoding {} is not supported", e),
+            Self::UnsupportedCompression(c) => {
+                write!(f, "tile compression {} is not supported", c)
+            }
+            Self::Base64Error(e) => write!(f, "{}", e),
+            Self::DecompressError(e) => write!(f, "{}", e),
+            Self::InvalidTileData => write!(f, "tile data does not match the size of the map"),
+        }
+    }
+}
+
+impl Error for TilemapError {}
+
+impl From<roxmltree::Error> for TilemapError {
+    fn from(value: roxmltree::Error) -> Self {
+        Self::XmlError(value)
+    }
+}
+
+impl From<serde_json::Error> for TilemapError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::JsonError(value)
+    }
+}
+
+impl From<base64::DecodeError> for TilemapError {
+    fn from(value: base64::DecodeError) -> Self {
+        Self::Base64Error(value)
+    }
+}
+
+impl From<std::io::Error> for TilemapError {
+    fn from(value: std::io::Error) -> Self {
+        Self::DecompressError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TILES: [u32; 6] = [1, 0, 3, 0x8000_0002, 7, 0];
+
+    fn tile_bytes() -> Vec<u8> {
+        TILES.iter().flat_map(|gid| gid.to_le_bytes()).collect()
+    }
+
+    fn encode(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    #[test]
+    fn decodes_csv() {
+        let data = "\n1,0,3,\n2147483650,7,0\n";
+        assert_eq!(decode_tiles(data, "csv", None).unwrap(), TILES);
+    }
+
+    #[test]
+    fn bad_csv_is_an_error() {
+        assert!(matches!(
+            decode_tiles("1,two,3", "csv", None),
+            Err(TilemapError::InvalidTileData)
+        ));
+    }
+
+    #[test]
+    fn decodes_uncompressed_base64() {
+        // tiled wraps the data in whitespace
+        let data = format!("\n   {}\n", encode(&tile_bytes()));
+        assert_eq!(decode_tiles(&data, "base64", None).unwrap(), TILES);
+        assert_eq!(decode_tiles(&data, "base64", Some("")).unwrap(), TILES);
+    }
+
+    #[test]
+    fn decodes_zlib_base64() {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(&tile_bytes()).unwrap();
+        let data = encode(&encoder.finish().unwrap());
+
+        assert_eq!(decode_tiles(&data, "base64", Some("zlib")).unwrap(), TILES);
+    }
+
+    #[test]
+    fn decodes_gzip_base64() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(&tile_bytes()).unwrap();
+        let data = encode(&encoder.finish().unwrap());
+
+        assert_eq!(decode_tiles(&data, "base64", Some("gzip")).unwrap(), TILES);
+    }
+
+    #[test]
+    fn bad_base64_data_is_an_error() {
+        assert!(matches!(
+            decode_tiles("not base64!", "base64", None),
+            Err(TilemapError::Base64Error(_))
+        ));
+
+        let data = encode(&tile_bytes());
+        assert!(matches!(
+            decode_tiles(&data, "base64", Some("zlib")),
+            Err(TilemapError::DecompressError(_))
+        ));
+        assert!(matches!(
+            decode_tiles(&data, "base64", Some("zstd")),
+            Err(TilemapError::UnsupportedCompression(name)) if name == "zstd"
+        ));
+        assert!(matches!(
+            decode_tiles(&data, "xml", None),
+            Err(TilemapError::UnsupportedEncoding(name)) if name == "xml"
+        ));
+    }
+
+    #[test]
+    fn tile_count_must_match_the_map() {
+        let size = Vec2 { x: 3, y: 2 };
+        assert_eq!(check_tile_count(TILES.to_vec(), size).unwrap(), TILES);
+        assert!(check_tile_count(TILES[..5].to_vec(), size).is_err());
+    }
+
+    const TMX: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <properties>
+  <property name="music" value="forest.ogg"/>
+  <property name="gravity" type="float" value="9.5"/>
+ </properties>
+ <tileset firstgid="5" source="tilesets/external.tsx"/>
+ <tileset firstgid="1" name="embedded" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+  <image source="images/embedded.png" width="32" height="32"/>
+  <tile id="1">
+   <properties>
+    <property name="solid" type="bool" value="true"/>
+   </properties>
+  </tile>
+ </tileset>
+ <layer id="1" name="ground" width="2" height="2" opacity="0.5">
+  <properties>
+   <property name="depth" type="int" value="3"/>
+  </properties>
+  <data encoding="csv">
+2,2147483653,
+1073741826,536870918
+</data>
+ </layer>
+ <group id="2" name="things" offsetx="4" offsety="2" visible="0">
+  <objectgroup id="3" name="entities" offsetx="1">
+   <object id="1" name="spawn" type="player" x="8" y="12">
+    <point/>
+   </object>
+   <object id="2" name="wall" x="0" y="0" width="32" height="4" rotation="90">
+    <properties>
+     <property name="tint" type="color" value="#80ff0000"/>
+     <property name="target" type="object" value="1"/>
+    </properties>
+   </object>
+   <object id="3" x="4" y="4">
+    <polygon points="0,0 8,0 8,8"/>
+   </object>
+  </objectgroup>
+ </group>
+</map>
+"##;
+
+    const TSX: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="external" tilewidth="16" tileheight="16" spacing="1" margin="2" tilecount="2" columns="2">
+ <properties>
+  <property name="biome" value="cave"/>
+ </properties>
+ <image source="../images/external.png" width="35" height="18"/>
+</tileset>
+"##;
+
+    const TMJ: &str = r##"{
+        "width": 2, "height": 2, "tilewidth": 16, "tileheight": 16,
+        "orientation": "orthogonal", "infinite": false,
+        "properties": [
+            { "name": "music", "type": "string", "value": "forest.ogg" },
+            { "name": "gravity", "type": "float", "value": 9.5 }
+        ],
+        "tilesets": [
+            { "firstgid": 5, "source": "tilesets/external.tsj" },
+            {
+                "firstgid": 1, "name": "embedded", "tilewidth": 16, "tileheight": 16,
+                "tilecount": 4, "columns": 2,
+                "image": "images/embedded.png", "imagewidth": 32, "imageheight": 32,
+                "tiles": [{ "id": 1, "properties": [{ "name": "solid", "type": "bool", "value": true }] }]
+            }
+        ],
+        "layers": [
+            {
+                "type": "tilelayer", "name": "ground", "width": 2, "height": 2, "opacity": 0.5,
+                "data": [2, 2147483653, 1073741826, 536870918],
+                "properties": [{ "name": "depth", "type": "int", "value": 3 }]
+            },
+            {
+                "type": "group", "name": "things", "offsetx": 4, "offsety": 2, "visible": false,
+                "layers": [{
+                    "type": "objectgroup", "name": "entities", "offsetx": 1,
+                    "objects": [
+                        { "id": 1, "name": "spawn", "type": "player", "x": 8, "y": 12, "point": true },
+                        {
+                            "id": 2, "name": "wall", "x": 0, "y": 0, "width": 32, "height": 4, "rotation": 90,
+                            "properties": [
+                                { "name": "tint", "type": "color", "value": "#80ff0000" },
+                                { "name": "target", "type": "object", "value": 1 }
+                            ]
+                        },
+                        { "id": 3, "x": 4, "y": 4, "polygon": [{ "x": 0, "y": 0 }, { "x": 8, "y": 0 }, { "x": 8, "y": 8 }] }
+                    ]
+                }]
+            }
+        ]
+    }"##;
+
+    const TSJ: &str = r##"{
+        "name": "external", "tilewidth": 16, "tileheight": 16, "spacing": 1, "margin": 2,
+        "tilecount": 2, "columns": 2,
+        "image": "../images/external.png", "imagewidth": 35, "imageheight": 18,
+        "properties": [{ "name": "biome", "type": "string", "value": "cave" }]
+    }"##;
+
+    fn load(map: &str, map_path: &str, tileset: &str, tileset_path: &str) -> Tilemap {
+        let map_path = Path::new(map_path);
+        let tileset_path = PathBuf::from(tileset_path);
+        let found = Tilemap::external_tilesets(map.as_bytes(), map_path);
+        assert_eq!(found, [tileset_path.as_path()]);
+
+        let files = ExtraFiles::from([(tileset_path, tileset.as_bytes().to_vec())]);
+        Tilemap::from_resource_data(map.as_bytes(), map_path, &files).unwrap()
+    }
+
+    fn tile(tileset: usize, id: u32, flips: [bool; 3]) -> Option<Tile> {
+        Some(Tile {
+            tileset,
+            id,
+            flip_horizontal: flips[0],
+            flip_vertical: flips[1],
+            flip_diagonal: flips[2],
+        })
+    }
+
+    // both formats describe the same map
+    fn check_map(map: &Tilemap) {
+        assert_eq!(map.get_size(), Vec2 { x: 2, y: 2 });
+        assert_eq!(map.get_tile_size(), Vec2 { x: 16, y: 16 });
+        assert_eq!(
+            map.get_properties(),
+            &Properties::from([
+                ("music".into(), PropertyValue::String("forest.ogg".into())),
+                ("gravity".into(), PropertyValue::Float(9.5)),
+            ])
+        );
+
+        // sorted by their first gid
+        let [embedded, external] = map.get_tilesets() else {
+            panic!("expected two tilesets");
+        };
+        assert_eq!(
+            (embedded.name.as_str(), embedded.first_gid),
+            ("embedded", 1)
+        );
+        assert_eq!(
+            embedded.image,
+            Some(PathBuf::from("maps/images/embedded.png"))
+        );
+        assert_eq!(
+            embedded.tile_properties[&1],
+            Properties::from([("solid".into(), PropertyValue::Bool(true))])
+        );
+
+        assert_eq!(
+            (external.name.as_str(), external.first_gid),
+            ("external", 5)
+        );
+        assert_eq!((external.columns, external.tile_count), (2, 2));
+        assert_eq!((external.spacing, external.margin), (1, 2));
+        assert_eq!(external.image_size, Vec2 { x: 35, y: 18 });
+        // relative to the tileset instead of the map
+        assert_eq!(
+            external.image,
+            Some(PathBuf::from("maps/tilesets/../images/external.png"))
+        );
+        assert_eq!(
+            external.properties,
+            Properties::from([("biome".into(), PropertyValue::String("cave".into()))])
+        );
+
+        let ground = map.get_tile_layer("ground").unwrap();
+        assert_eq!(ground.opacity, 0.5);
+        assert_eq!(
+            ground.properties,
+            Properties::from([("depth".into(), PropertyValue::Int(3))])
+        );
+        assert_eq!(map.get_tile("ground", 0, 0), tile(0, 1, [false; 3]));
+        assert_eq!(
+            map.get_tile("ground", 1, 0),
+            tile(1, 0, [true, false, false])
+        );
+        assert_eq!(
+            map.get_tile("ground", 0, 1),
+            tile(0, 1, [false, true, false])
+        );
+        assert_eq!(
+            map.get_tile("ground", 1, 1),
+            tile(1, 1, [false, false, true])
+        );
+        assert_eq!(
+            map.get_tile_properties(&map.get_tile("ground", 0, 0).unwrap()),
+            Some(&embedded.tile_properties[&1])
+        );
+
+        // the group is flattened into the layers inside of it
+        assert_eq!(map.get_layers().len(), 2);
+        let entities = map.get_object_layer("entities").unwrap();
+        assert!(!entities.visible);
+        assert_eq!(entities.offset, Vec2 { x: 5.0, y: 2.0 });
+
+        let [spawn, wall, triangle] = entities.objects.as_slice() else {
+            panic!("expected three objects");
+        };
+        assert_eq!(
+            (spawn.name.as_str(), spawn.class.as_str()),
+            ("spawn", "player")
+        );
+        assert_eq!(spawn.position, Vec2 { x: 8.0, y: 12.0 });
+        assert_eq!(spawn.shape, ObjectShape::Point);
+
+        assert_eq!(wall.shape, ObjectShape::Rectangle);
+        assert_eq!((wall.size, wall.rotation), (Vec2 { x: 32.0, y: 4.0 }, 90.0));
+        assert_eq!(
+            wall.properties,
+            Properties::from([
+                (
+                    "tint".into(),
+                    PropertyValue::Colour(Colour::from_rgba(255.0, 0.0, 0.0, 128.0 / 255.0))
+                ),
+                ("target".into(), PropertyValue::Object(1)),
+            ])
+        );
+
+        assert_eq!(triangle.id, 3);
+        assert_eq!(
+            triangle.shape,
+            ObjectShape::Polygon(vec![
+                Vec2 { x: 0.0, y: 0.0 },
+                Vec2 { x: 8.0, y: 0.0 },
+                Vec2 { x: 8.0, y: 8.0 },
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_tmx_with_an_external_tileset() {
+        let map = load(TMX, "maps/level.tmx", TSX, "maps/tilesets/external.tsx");
+        check_map(&map);
+    }
+
+    #[test]
+    fn parses_tmj_with_an_external_tileset() {
+        let map = load(TMJ, "maps/level.tmj", TSJ, "maps/tilesets/external.tsj");
+        check_map(&map);
+    }
+
+    #[test]
+    fn external_tilesets_must_be_loaded() {
+        let path = Path::new("maps/level.tmx");
+        assert!(matches!(
+            Tilemap::from_resource_data(TMX.as_bytes(), path, &ExtraFiles::new()),
+            Err(TilemapError::MissingTileset(path)) if path == Path::new("maps/tilesets/external.tsx")
+        ));
+    }
+}
@@ -46,7 +46,7 @@ impl Vertex {
         let height = screen_size.y as f32;
 
         self.position[0] = ((self.position[0] + 1.0) * width) / 2.0;
-        self.position[1] = (((self.position[1] * -1.0) + 1.0) * height) / 2.0;
+        self.position[1] = ((-self.position[1] + 1.0) * height) / 2.0;
 
         self
     }
@@ -103,12 +103,43 @@ impl LineVertex {
         let height = screen_size.y as f32;
 
         self.pos[0] = ((self.pos[0] + 1.0) * width) / 2.0;
-        self.pos[1] = (((self.pos[1] * -1.0) + 1.0) * height) / 2.0;
+        self.pos[1] = ((-self.pos[1] + 1.0) * height) / 2.0;
 
         self
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct InstanceVertex {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub rotation: f32,
+    pub uv_position: [f32; 2],
+    pub uv_size: [f32; 2],
+    pub colour: [f32; 4],
+}
+
+impl InstanceVertex {
+    // starts after the locations used by Vertex
+    const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+        3 => Float32x2,
+        4 => Float32x2,
+        5 => Float32,
+        6 => Float32x2,
+        7 => Float32x2,
+        8 => Float32x4,
+    ];
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<InstanceVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
 pub(crate) fn new(
     pos: Vec2<f32>,
     size: Vec2<f32>,
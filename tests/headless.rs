@@ -0,0 +1,405 @@
+use bottomless_pit::archive::ArchiveBuilder;
+use bottomless_pit::camera::Camera;
+use bottomless_pit::colour::Colour;
+use bottomless_pit::engine_handle::{Engine, EngineBuilder};
+use bottomless_pit::material::{InstancedMaterial, Material, MaterialBuilder};
+use bottomless_pit::post_processing::PostEffect;
+use bottomless_pit::render::{BlendMode, RenderHandle};
+use bottomless_pit::resource::{LoadStatus, LoadingOp};
+use bottomless_pit::texture::{Texture, TextureUpdateError};
+use bottomless_pit::tilemap::Tilemap;
+use bottomless_pit::vec2;
+use bottomless_pit::vectors::Vec2;
+use bottomless_pit::Game;
+use image::{Rgba, RgbaImage};
+
+struct RectangleGame {
+    material: Material,
+    frames: u32,
+    last_frame: Option<RgbaImage>,
+}
+
+impl Game for RectangleGame {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        {
+            let mut render = render_handle.begin_pass(Colour::BLACK);
+            self.material
+                .add_rectangle(vec2!(10.0), vec2!(20.0), Colour::RED, &render);
+            self.material.draw(&mut render);
+        }
+
+        self.last_frame = Some(render_handle.capture().unwrap());
+    }
+
+    fn update(&mut self, _engine_handle: &mut Engine) {
+        self.frames += 1;
+    }
+}
+
+#[test]
+fn renders_a_rectangle_without_a_window() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((64, 48))
+        .headless()
+        .build()
+        .unwrap();
+
+    let material = MaterialBuilder::new().build(&mut engine);
+    let mut game = RectangleGame {
+        material,
+        frames: 0,
+        last_frame: None,
+    };
+
+    engine.run_frames(&mut game, 3);
+
+    assert_eq!(game.frames, 3);
+    let image = game.last_frame.unwrap();
+    assert_eq!(image.dimensions(), (64, 48));
+    assert_eq!(*image.get_pixel(20, 20), Rgba([255, 0, 0, 255]));
+    assert_eq!(*image.get_pixel(5, 5), Rgba([0, 0, 0, 255]));
+    assert_eq!(*image.get_pixel(40, 40), Rgba([0, 0, 0, 255]));
+}
+
+struct BatchedGame {
+    tiny: Material,
+    red: Material,
+    green: Material,
+    last_frame: Option<RgbaImage>,
+}
+
+impl Game for BatchedGame {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        {
+            let mut render = render_handle.begin_pass(Colour::BLACK);
+            self.red
+                .add_rectangle(vec2!(0.0), vec2!(32.0, 24.0), Colour::RED, &render);
+            self.red.draw(&mut render);
+            // enough tiny shapes that the batch buffers have to grow
+            for i in 0..300 {
+                let x = (i % 30) as f32 * 0.1;
+                self.tiny
+                    .add_rectangle(vec2!(x, 40.0), vec2!(0.1), Colour::WHITE, &render);
+            }
+            self.tiny.draw(&mut render);
+
+            render.flush_batch();
+
+            // drawn after the first flush so it has to be written after it in the buffers
+            self.green
+                .add_rectangle(vec2!(32.0, 0.0), vec2!(32.0, 24.0), Colour::GREEN, &render);
+            self.green.draw(&mut render);
+        }
+
+        self.last_frame = Some(render_handle.capture().unwrap());
+    }
+
+    fn update(&mut self, _engine_handle: &mut Engine) {}
+}
+
+#[test]
+fn batches_flushed_more_than_once_a_frame_keep_their_shapes() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((64, 48))
+        .headless()
+        .build()
+        .unwrap();
+
+    let mut batched = || MaterialBuilder::new().set_batched(true).build(&mut engine);
+    let mut game = BatchedGame {
+        tiny: batched(),
+        red: batched(),
+        green: batched(),
+        last_frame: None,
+    };
+
+    engine.run_frames(&mut game, 3);
+
+    let image = game.last_frame.unwrap();
+    assert_eq!(*image.get_pixel(10, 10), Rgba([255, 0, 0, 255]));
+    assert_eq!(*image.get_pixel(50, 10), Rgba([0, 255, 0, 255]));
+    assert_eq!(*image.get_pixel(30, 40), Rgba([0, 0, 0, 255]));
+}
+
+struct LayeredGame {
+    ui: Material,
+    marker: Material,
+    bullets: InstancedMaterial,
+    world: Material,
+    camera: Camera,
+    last_frame: Option<RgbaImage>,
+}
+
+impl Game for LayeredGame {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        {
+            let mut render = render_handle.begin_pass(Colour::BLACK);
+            // everything on top is given to the renderer first
+            self.ui
+                .add_rectangle(vec2!(0.0), vec2!(16.0, 48.0), Colour::RED, &render);
+            self.ui.draw(&mut render);
+            self.marker
+                .add_rectangle(vec2!(16.0, 0.0), vec2!(16.0, 48.0), Colour::BLUE, &render);
+            self.marker.draw(&mut render);
+            self.bullets
+                .add_instance(vec2!(32.0, 0.0), vec2!(16.0, 48.0), Colour::WHITE);
+            self.bullets.draw(&mut render);
+
+            self.camera.set_active(&mut render);
+            self.world
+                .add_rectangle(vec2!(-1000.0), vec2!(3000.0), Colour::GREEN, &render);
+            self.world.draw(&mut render);
+        }
+
+        self.last_frame = Some(render_handle.capture().unwrap());
+    }
+
+    fn update(&mut self, _engine_handle: &mut Engine) {}
+}
+
+#[test]
+fn higher_layers_are_drawn_on_top_across_cameras() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((64, 48))
+        .headless()
+        .build()
+        .unwrap();
+
+    let mut game = LayeredGame {
+        ui: MaterialBuilder::new()
+            .set_batched(true)
+            .set_layer(10)
+            .build(&mut engine),
+        marker: MaterialBuilder::new().set_layer(5).build(&mut engine),
+        bullets: MaterialBuilder::new()
+            .set_layer(1)
+            .build_instanced(&mut engine),
+        world: MaterialBuilder::new().set_batched(true).build(&mut engine),
+        camera: Camera::new(vec2!(32.0, 24.0), 0.0, vec2!(1.0)),
+        last_frame: None,
+    };
+
+    engine.run_frames(&mut game, 2);
+
+    let image = game.last_frame.unwrap();
+    assert_eq!(*image.get_pixel(8, 24), Rgba([255, 0, 0, 255]));
+    assert_eq!(*image.get_pixel(24, 24), Rgba([0, 0, 255, 255]));
+    assert_eq!(*image.get_pixel(40, 24), Rgba([255, 255, 255, 255]));
+    assert_eq!(*image.get_pixel(56, 24), Rgba([0, 255, 0, 255]));
+}
+
+struct BlendGame {
+    materials: Vec<Material>,
+    last_frame: Option<RgbaImage>,
+}
+
+impl Game for BlendGame {
+    fn render<'o>(&'o mut self, mut render_handle: RenderHandle<'o>) {
+        {
+            // a linear grey of 0.5
+            let mut render = render_handle.begin_pass(Colour::from_rgba(127.5, 127.5, 127.5, 1.0));
+            // a grey of 0.2 at half alpha, which reads the same whether or not it is premultiplied
+            let half = Colour::from_rgba(51.0, 51.0, 51.0, 0.5);
+            let clear = Colour::from_rgba(0.0, 0.0, 0.0, 0.0);
+            for (i, material) in self.materials.iter_mut().enumerate() {
+                let x = i as f32 * 16.0;
+                material.add_rectangle(vec2!(x, 0.0), vec2!(16.0), half, &render);
+                material.add_rectangle(vec2!(x, 16.0), vec2!(16.0), clear, &render);
+                material.draw(&mut render);
+            }
+        }
+
+        self.last_frame = Some(render_handle.capture().unwrap());
+    }
+
+    fn update(&mut self, _engine_handle: &mut Engine) {}
+}
+
+fn to_srgb(linear: f32) -> u8 {
+    let srgb = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+#[test]
+fn blend_modes_handle_half_and_fully_transparent_colours() {
+    let modes = [
+        BlendMode::Alpha,
+        BlendMode::Premultiplied,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Replace,
+    ];
+
+    let mut engine = EngineBuilder::new()
+        .with_resolution((96, 32))
+        .headless()
+        .build()
+        .unwrap();
+
+    let materials = modes
+        .iter()
+        .map(|&mode| {
+            MaterialBuilder::new()
+                .set_blend_mode(mode)
+                .build(&mut engine)
+        })
+        .collect();
+    let mut game = BlendGame {
+        materials,
+        last_frame: None,
+    };
+
+    engine.run_frames(&mut game, 2);
+
+    let image = game.last_frame.unwrap();
+    let grey = |linear: f32, alpha: u8| {
+        let v = to_srgb(linear);
+        Rgba([v, v, v, alpha])
+    };
+    let close = |found: &Rgba<u8>, expected: Rgba<u8>| {
+        found
+            .0
+            .iter()
+            .zip(expected.0)
+            .all(|(&a, b)| a.abs_diff(b) <= 1)
+    };
+
+    // (half transparent, fully transparent) drawn over 0.5
+    let expected = [
+        (grey(0.35, 255), grey(0.5, 255)),
+        (grey(0.45, 255), grey(0.5, 255)),
+        (grey(0.6, 255), grey(0.5, 255)),
+        (grey(0.35, 255), grey(0.5, 255)),
+        (grey(0.6, 255), grey(0.5, 255)),
+        (grey(0.2, 128), Rgba([0, 0, 0, 0])),
+    ];
+
+    for (i, (mode, (half, clear))) in modes.iter().zip(expected).enumerate() {
+        let x = i as u32 * 16 + 8;
+        let found = image.get_pixel(x, 8);
+        assert!(close(found, half), "{mode:?}: {found:?} != {half:?}");
+        let found = image.get_pixel(x, 24);
+        assert!(close(found, clear), "{mode:?}: {found:?} != {clear:?}");
+    }
+}
+
+#[test]
+fn broken_post_effects_are_disabled() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((64, 48))
+        .headless()
+        .build()
+        .unwrap();
+
+    let broken = engine.add_post_effect(PostEffect::new("this is not wgsl"));
+    let material = MaterialBuilder::new().build(&mut engine);
+    let mut game = RectangleGame {
+        material,
+        frames: 0,
+        last_frame: None,
+    };
+
+    engine.run_frames(&mut game, 2);
+
+    let image = game.last_frame.unwrap();
+    assert_eq!(*image.get_pixel(20, 20), Rgba([255, 0, 0, 255]));
+    assert!(!engine.remove_post_effect(&broken).unwrap().is_enabled());
+}
+
+#[test]
+fn texture_regions_that_overflow_are_out_of_bounds() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((16, 16))
+        .headless()
+        .build()
+        .unwrap();
+
+    let texture = Texture::from_rgba(&mut engine, vec2!(2), &[255; 16]);
+    let material = MaterialBuilder::new().build(&mut engine);
+    let mut game = RectangleGame {
+        material,
+        frames: 0,
+        last_frame: None,
+    };
+    engine.run_frames(&mut game, 1);
+
+    let update = |position: Vec2<u32>, size: Vec2<u32>, pixels: &[u8]| {
+        Texture::update_region(&engine, texture, position, size, pixels)
+    };
+    assert_eq!(update(vec2!(1, 1), vec2!(1), &[0; 4]), Ok(()));
+    assert_eq!(
+        update(vec2!(u32::MAX, 0), vec2!(2, 1), &[0; 8]),
+        Err(TextureUpdateError::OutOfBounds)
+    );
+    assert_eq!(
+        update(vec2!(0, 1), vec2!(1, u32::MAX), &[0; 4]),
+        Err(TextureUpdateError::OutOfBounds)
+    );
+    assert_eq!(
+        update(vec2!(0), vec2!(2), &[0; 4]),
+        Err(TextureUpdateError::WrongSize)
+    );
+}
+
+#[test]
+#[should_panic(expected = "there must be 4 bytes for every pixel in the texture")]
+fn textures_too_big_for_memory_panic_with_a_message() {
+    let mut engine = EngineBuilder::new()
+        .with_resolution((16, 16))
+        .headless()
+        .build()
+        .unwrap();
+
+    Texture::from_rgba(&mut engine, vec2!(u32::MAX), &[0; 4]);
+}
+
+#[test]
+fn external_tilesets_are_read_like_the_map() {
+    let map = r#"<map orientation="orthogonal" width="1" height="1" tilewidth="8" tileheight="8">
+ <tileset firstgid="1" source="../tilesets/grass.tsx"/>
+ <layer name="ground" width="1" height="1"><data encoding="csv">1</data></layer>
+</map>"#;
+    let tileset = r#"<tileset name="grass" tilewidth="8" tileheight="8" tilecount="1" columns="1">
+ <image source="grass.png" width="8" height="8"/>
+</tileset>"#;
+
+    let mut engine = EngineBuilder::new()
+        .with_resolution((16, 16))
+        .headless()
+        .build()
+        .unwrap();
+    // only in the archive so the tileset has to come from the same place as the map
+    let archive = ArchiveBuilder::new()
+        .add_file("packed/maps/level.tmx", map.as_bytes().to_vec())
+        .add_file("packed/tilesets/grass.tsx", tileset.as_bytes().to_vec())
+        .add_file(
+            "packed/maps/broken.tmx",
+            map.replace("grass", "missing").into_bytes(),
+        )
+        .build();
+    engine.mount_archive(archive);
+
+    let level = Tilemap::new(&mut engine, "packed/maps/level.tmx", LoadingOp::Blocking);
+    let broken = Tilemap::new(&mut engine, "packed/maps/broken.tmx", LoadingOp::Blocking);
+    let material = MaterialBuilder::new().build(&mut engine);
+    let mut game = RectangleGame {
+        material,
+        frames: 0,
+        last_frame: None,
+    };
+    engine.run_frames(&mut game, 1);
+
+    assert_eq!(engine.get_load_status(level), Some(&LoadStatus::Loaded));
+    let tilesets = engine.get_tilemap(level).unwrap().get_tilesets();
+    assert_eq!(tilesets.len(), 1);
+    assert_eq!(tilesets[0].name, "grass");
+    assert!(matches!(
+        engine.get_load_status(broken),
+        Some(LoadStatus::Failed(_))
+    ));
+}